@@ -83,6 +83,20 @@ pub enum RuntimeFamily {
     Python,
 }
 
+impl RuntimeFamily {
+    /// Lowercase name (e.g., for `templates/docker/<family>.hbs`)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Node => "node",
+            Self::Edge => "edge",
+            Self::Bun => "bun",
+            Self::Deno => "deno",
+            Self::Rust => "rust",
+            Self::Python => "python",
+        }
+    }
+}
+
 /// Resolved toolchain information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Toolchain {
@@ -1,55 +1,304 @@
 use anyhow::{Context, Result};
 use colored::Colorize;
-use serde_json::Value;
-use std::collections::{HashMap, HashSet};
-use std::fs;
+use serde::Serialize;
+use std::collections::HashSet;
 use std::path::Path;
 use std::process::Command;
 
+use crate::dag::Dag;
+use crate::manifest::{AffectedSection, Manifest};
+use crate::pipeline;
+use crate::pnpm::{self, PnpmLock};
+use crate::workspace_graph;
+
+const LOCKFILE: &str = "pnpm-lock.yaml";
+
+/// Progress output: stdout for humans, stderr when stdout carries machine output
+macro_rules! progress {
+    ($verbose:expr, $($arg:tt)*) => {
+        if $verbose { println!($($arg)*) } else { eprintln!($($arg)*) }
+    };
+}
+
+/// Output format for `airis affected`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable summary (default)
+    Text,
+    /// `{"base", "head", "packages": [{"package", "name", "task"}]}`
+    Json,
+    /// One package path per line
+    Paths,
+    /// One package name per line
+    Names,
+    /// `{"include": [{"package", "name", "task"}]}` for `strategy.matrix`
+    GithubMatrix,
+}
+
+impl OutputFormat {
+    pub fn parse(format: &str) -> Result<Self> {
+        match format {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "paths" => Ok(Self::Paths),
+            "names" => Ok(Self::Names),
+            "github-matrix" => Ok(Self::GithubMatrix),
+            _ => anyhow::bail!(
+                "Unknown format '{}'. Expected text, json, paths, names or github-matrix",
+                format
+            ),
+        }
+    }
+}
+
+/// An affected package in machine output
+#[derive(Debug, Serialize)]
+struct AffectedPackage {
+    #[serde(rename = "package")]
+    path: String,
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    task: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct AffectedJson<'a> {
+    base: &'a str,
+    head: &'a str,
+    packages: &'a [AffectedPackage],
+}
+
+#[derive(Debug, Serialize)]
+struct GithubMatrix<'a> {
+    include: &'a [AffectedPackage],
+}
+
 /// Analyze affected packages based on git changes
+///
+/// Returns affected package paths (workspace graph IDs, e.g., "apps/web").
 pub fn run(base: &str, head: &str) -> Result<Vec<String>> {
-    println!("{}", "🔍 Analyzing affected packages...".bright_blue());
+    detect(base, head, true)
+}
+
+/// `airis affected` with output format and optional task filter
+///
+/// With `task`, only packages that have something to run for it are listed
+/// (a `[tasks.<task>].command` or a package.json script).
+pub fn output(base: &str, head: &str, format: OutputFormat, task: Option<&str>) -> Result<()> {
+    let verbose = format == OutputFormat::Text;
+    let affected = detect(base, head, verbose)?;
+
+    let root = std::env::current_dir()?;
+    let dag = workspace_graph::load(&root)?;
+    let manifest = load_manifest()?;
+    let pm = manifest
+        .as_ref()
+        .map(crate::commands::run::get_package_manager)
+        .unwrap_or("pnpm");
+
+    let packages: Vec<AffectedPackage> = affected
+        .into_iter()
+        .filter(|path| {
+            task.is_none_or(|task| {
+                let config = manifest.as_ref().and_then(|m| m.tasks.get(task));
+                pipeline::resolve_command(&root, path, task, config, pm).is_some()
+            })
+        })
+        .map(|path| AffectedPackage {
+            name: dag.get(&path).map(|n| n.name.clone()).unwrap_or_else(|| path.clone()),
+            path,
+            task: task.map(String::from),
+        })
+        .collect();
+
+    match format {
+        OutputFormat::Text => {
+            if let Some(task) = task {
+                println!();
+                println!("{}", format!("🎯 Packages with '{}':", task).green());
+                for pkg in &packages {
+                    println!("   - {}", pkg.path);
+                }
+            }
+        }
+        OutputFormat::Json => {
+            let json = AffectedJson { base, head, packages: &packages };
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        }
+        OutputFormat::Paths => packages.iter().for_each(|p| println!("{}", p.path)),
+        OutputFormat::Names => packages.iter().for_each(|p| println!("{}", p.name)),
+        OutputFormat::GithubMatrix => {
+            // Single line so it can be written to $GITHUB_OUTPUT
+            println!("{}", serde_json::to_string(&GithubMatrix { include: &packages })?);
+        }
+    }
 
+    Ok(())
+}
+
+/// Packages changed between two revisions, before adding dependents
+enum Changes {
+    /// No files changed
+    None,
+    /// A global input or root dependency changed
+    All,
+    /// Directly changed package IDs
+    Packages(Vec<String>),
+}
+
+fn detect(base: &str, head: &str, verbose: bool) -> Result<Vec<String>> {
+    progress!(verbose, "{}", "🔍 Analyzing affected packages...".bright_blue());
+
+    let dag = workspace_graph::load(Path::new("."))?;
+
+    let changed = match changes(&dag, base, head, verbose)? {
+        Changes::None => return Ok(vec![]),
+        Changes::All => return Ok(report(verbose, &dag, dag.ids())),
+        Changes::Packages(changed) => changed,
+    };
+
+    progress!(verbose, "  🎯 Directly changed: {}", changed.len());
+
+    // Find packages that depend on changed packages (transitive)
+    let mut result: Vec<String> = dag.with_dependents(&changed).into_iter().collect();
+    result.sort();
+
+    Ok(report(verbose, &dag, result))
+}
+
+/// Packages with changes since `base` (no dependents), e.g. for `--filter "[origin/main]"`
+pub fn changed_since(dag: &Dag, base: &str) -> Result<Vec<String>> {
+    Ok(match changes(dag, base, "HEAD", false)? {
+        Changes::None => vec![],
+        Changes::All => dag.ids(),
+        Changes::Packages(changed) => changed,
+    })
+}
+
+fn changes(dag: &Dag, base: &str, head: &str, verbose: bool) -> Result<Changes> {
     // 1. Get changed files from git
     let changed_files = get_changed_files(base, head)?;
     if changed_files.is_empty() {
-        println!("{}", "✅ No changes detected".green());
-        return Ok(vec![]);
+        progress!(verbose, "{}", "✅ No changes detected".green());
+        return Ok(Changes::None);
     }
 
-    println!("  📝 Changed files: {}", changed_files.len());
+    progress!(verbose, "  📝 Changed files: {}", changed_files.len());
+    progress!(verbose, "  📦 Packages found: {}", dag.nodes.len());
 
-    // 2. Build dependency graph
-    let graph = build_dependency_graph()?;
-    println!("  📦 Packages found: {}", graph.len());
+    // 2. Root files that invalidate everything
+    let config = load_affected_config()?;
+    if let Some(file) = find_global_input(&changed_files, &config.global_inputs) {
+        progress!(verbose, "  🌐 Global input changed: {} → all packages affected", file.yellow());
+        return Ok(Changes::All);
+    }
 
     // 3. Find directly changed packages
-    let mut affected: HashSet<String> = HashSet::new();
+    let mut changed: HashSet<String> = changed_files
+        .iter()
+        .filter_map(|file| dag.owner_of(file))
+        .map(|node| node.id.clone())
+        .collect();
 
-    for file in &changed_files {
-        if let Some(pkg) = get_package_from_path(file) {
-            affected.insert(pkg);
+    // 4. Importers whose resolved lockfile deps changed
+    if changed_files.iter().any(|f| f == LOCKFILE) {
+        match lockfile_changed_importers(base, head) {
+            Ok(importers) if importers.iter().any(|i| i == ".") => {
+                progress!(verbose, "  🔒 Root dependencies changed in {} → all packages affected", LOCKFILE);
+                return Ok(Changes::All);
+            }
+            Ok(importers) => {
+                progress!(verbose, "  🔒 Lockfile changes: {} importer(s)", importers.len());
+                changed.extend(importers.into_iter().filter(|i| dag.nodes.contains_key(i)));
+            }
+            Err(e) => {
+                progress!(
+                    verbose,
+                    "  {} {} ({:#}) → all packages affected",
+                    "⚠️  Could not diff".yellow(),
+                    LOCKFILE,
+                    e
+                );
+                return Ok(Changes::All);
+            }
         }
     }
 
-    println!("  🎯 Directly changed: {}", affected.len());
+    let mut changed: Vec<String> = changed.into_iter().collect();
+    changed.sort();
+    Ok(Changes::Packages(changed))
+}
 
-    // 4. Find packages that depend on changed packages (transitive)
-    let initial_affected: Vec<String> = affected.iter().cloned().collect();
-    for pkg in initial_affected {
-        find_dependents(&pkg, &graph, &mut affected);
+fn report(verbose: bool, dag: &Dag, result: Vec<String>) -> Vec<String> {
+    if !verbose {
+        eprintln!("  📊 Affected packages: {}", result.len());
+        return result;
     }
 
-    let mut result: Vec<String> = affected.into_iter().collect();
-    result.sort();
-
     println!();
     println!("{}", "📊 Affected packages:".green());
-    for pkg in &result {
-        println!("   - {}", pkg);
+    for id in &result {
+        match dag.get(id) {
+            Some(node) if node.name != *id => println!("   - {} {}", id, format!("({})", node.name).dimmed()),
+            _ => println!("   - {}", id),
+        }
+    }
+    result
+}
+
+fn load_manifest() -> Result<Option<Manifest>> {
+    let path = Path::new("manifest.toml");
+    if !path.exists() {
+        return Ok(None);
     }
+    Ok(Some(Manifest::load(path)?))
+}
+
+/// Load [affected] from manifest.toml (defaults if there is no manifest)
+fn load_affected_config() -> Result<AffectedSection> {
+    Ok(load_manifest()?.map(|m| m.affected).unwrap_or_default())
+}
+
+/// First changed file matching a global input pattern
+fn find_global_input<'a>(changed_files: &'a [String], global_inputs: &[String]) -> Option<&'a String> {
+    let patterns: Vec<glob::Pattern> = global_inputs
+        .iter()
+        .filter_map(|p| glob::Pattern::new(p).ok())
+        .collect();
 
-    Ok(result)
+    changed_files
+        .iter()
+        .find(|file| patterns.iter().any(|p| p.matches(file)))
+}
+
+/// Diff pnpm-lock.yaml between the merge base and head
+fn lockfile_changed_importers(base: &str, head: &str) -> Result<Vec<String>> {
+    let merge_base = Command::new("git")
+        .args(["merge-base", base, head])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_else(|| base.to_string());
+
+    let old = PnpmLock::parse(&git_show(&merge_base, LOCKFILE)?)?;
+    let new = PnpmLock::parse(&git_show(head, LOCKFILE)?)?;
+
+    Ok(pnpm::changed_importers(&old, &new))
+}
+
+/// Read a file at a git revision
+fn git_show(rev: &str, path: &str) -> Result<String> {
+    let output = Command::new("git")
+        .args(["show", &format!("{}:{}", rev, path)])
+        .output()
+        .context("Failed to run git show")?;
+
+    if !output.status.success() {
+        anyhow::bail!("{} not found at {}", path, rev);
+    }
+
+    String::from_utf8(output.stdout).context("Invalid UTF-8 from git")
 }
 
 /// Get list of changed files from git
@@ -84,143 +333,10 @@ fn get_changed_files(base: &str, head: &str) -> Result<Vec<String>> {
     Ok(files)
 }
 
-/// Build dependency graph from package.json files
-fn build_dependency_graph() -> Result<HashMap<String, Vec<String>>> {
-    let mut graph: HashMap<String, Vec<String>> = HashMap::new();
-
-    // Scan apps/ and libs/ directories
-    for dir in &["apps", "libs", "packages"] {
-        let dir_path = Path::new(dir);
-        if !dir_path.exists() {
-            continue;
-        }
-
-        if let Ok(entries) = fs::read_dir(dir_path) {
-            for entry in entries.flatten() {
-                let pkg_json = entry.path().join("package.json");
-                if pkg_json.exists()
-                    && let Ok((name, deps)) = parse_package_json(&pkg_json) {
-                        graph.insert(name, deps);
-                    }
-            }
-        }
-    }
-
-    // Also check nested libs (e.g., libs/supabase/*)
-    let nested_libs = Path::new("libs/supabase");
-    if nested_libs.exists()
-        && let Ok(entries) = fs::read_dir(nested_libs) {
-            for entry in entries.flatten() {
-                let pkg_json = entry.path().join("package.json");
-                if pkg_json.exists()
-                    && let Ok((name, deps)) = parse_package_json(&pkg_json) {
-                        graph.insert(name, deps);
-                    }
-            }
-        }
-
-    Ok(graph)
-}
-
-/// Parse package.json and extract name + dependencies
-fn parse_package_json(path: &Path) -> Result<(String, Vec<String>)> {
-    let content = fs::read_to_string(path)?;
-    let json: Value = serde_json::from_str(&content)?;
-
-    let name = json["name"]
-        .as_str()
-        .unwrap_or("unknown")
-        .to_string();
-
-    let mut deps = Vec::new();
-
-    // Collect dependencies
-    if let Some(dependencies) = json["dependencies"].as_object() {
-        for dep_name in dependencies.keys() {
-            // Only include workspace packages (starting with @ or workspace:)
-            if dep_name.starts_with('@') || dependencies[dep_name].as_str().map(|v| v.contains("workspace:")).unwrap_or(false) {
-                deps.push(dep_name.clone());
-            }
-        }
-    }
-
-    // Collect devDependencies
-    if let Some(dev_dependencies) = json["devDependencies"].as_object() {
-        for dep_name in dev_dependencies.keys() {
-            if dep_name.starts_with('@') || dev_dependencies[dep_name].as_str().map(|v| v.contains("workspace:")).unwrap_or(false) {
-                deps.push(dep_name.clone());
-            }
-        }
-    }
-
-    Ok((name, deps))
-}
-
-/// Extract package name from file path
-fn get_package_from_path(file_path: &str) -> Option<String> {
-    let parts: Vec<&str> = file_path.split('/').collect();
-
-    if parts.len() >= 2 {
-        match parts[0] {
-            "apps" | "packages" => {
-                // apps/dashboard/... → @workspace/dashboard or check package.json
-                let pkg_name = parts[1];
-                // Try to read the actual package name from package.json
-                let pkg_json = format!("{}/{}/package.json", parts[0], pkg_name);
-                if let Ok(content) = fs::read_to_string(&pkg_json)
-                    && let Ok(json) = serde_json::from_str::<Value>(&content)
-                        && let Some(name) = json["name"].as_str() {
-                            return Some(name.to_string());
-                        }
-                Some(format!("@workspace/{}", pkg_name))
-            }
-            "libs" => {
-                if parts.len() >= 3 && parts[1] == "supabase" {
-                    // libs/supabase/client/... → check package.json
-                    let pkg_json = format!("libs/supabase/{}/package.json", parts[2]);
-                    if let Ok(content) = fs::read_to_string(&pkg_json)
-                        && let Ok(json) = serde_json::from_str::<Value>(&content)
-                            && let Some(name) = json["name"].as_str() {
-                                return Some(name.to_string());
-                            }
-                    Some(format!("@workspace/{}", parts[2]))
-                } else {
-                    // libs/ui/... → @workspace/ui
-                    let pkg_name = parts[1];
-                    let pkg_json = format!("libs/{}/package.json", pkg_name);
-                    if let Ok(content) = fs::read_to_string(&pkg_json)
-                        && let Ok(json) = serde_json::from_str::<Value>(&content)
-                            && let Some(name) = json["name"].as_str() {
-                                return Some(name.to_string());
-                            }
-                    Some(format!("@workspace/{}", pkg_name))
-                }
-            }
-            _ => None,
-        }
-    } else {
-        None
-    }
-}
-
-/// Find all packages that depend on the given package (recursively)
-fn find_dependents(pkg: &str, graph: &HashMap<String, Vec<String>>, affected: &mut HashSet<String>) {
-    for (name, deps) in graph {
-        if deps.contains(&pkg.to_string()) && !affected.contains(name) {
-            affected.insert(name.clone());
-            // Recursively find packages that depend on this one
-            find_dependents(name, graph, affected);
-        }
-    }
-}
-
 /// List all packages in the workspace
 #[allow(dead_code)]
 pub fn list_packages() -> Result<Vec<String>> {
-    let graph = build_dependency_graph()?;
-    let mut packages: Vec<String> = graph.keys().cloned().collect();
-    packages.sort();
-    Ok(packages)
+    Ok(workspace_graph::load(Path::new("."))?.ids())
 }
 
 #[cfg(test)]
@@ -228,15 +344,40 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_get_package_from_path() {
+    fn test_output_format_parse() {
+        assert_eq!(OutputFormat::parse("github-matrix").unwrap(), OutputFormat::GithubMatrix);
+        assert_eq!(OutputFormat::parse("paths").unwrap(), OutputFormat::Paths);
+        assert!(OutputFormat::parse("yaml").is_err());
+    }
+
+    #[test]
+    fn test_github_matrix_shape() {
+        let packages = vec![AffectedPackage {
+            path: "apps/web".to_string(),
+            name: "@acme/web".to_string(),
+            task: Some("build".to_string()),
+        }];
+        let json = serde_json::to_string(&GithubMatrix { include: &packages }).unwrap();
+        assert_eq!(
+            json,
+            r#"{"include":[{"package":"apps/web","name":"@acme/web","task":"build"}]}"#
+        );
+    }
+
+    #[test]
+    fn test_find_global_input() {
+        let globals = vec!["tsconfig.base.json".to_string(), ".github/workflows/*".to_string()];
+        let changed = |files: &[&str]| files.iter().map(|f| f.to_string()).collect::<Vec<_>>();
+
         assert_eq!(
-            get_package_from_path("apps/dashboard/src/index.ts"),
-            Some("@workspace/dashboard".to_string())
+            find_global_input(&changed(&["apps/web/a.ts", "tsconfig.base.json"]), &globals),
+            Some(&"tsconfig.base.json".to_string())
         );
         assert_eq!(
-            get_package_from_path("libs/ui/components/Button.tsx"),
-            Some("@workspace/ui".to_string())
+            find_global_input(&changed(&[".github/workflows/ci.yml"]), &globals),
+            Some(&".github/workflows/ci.yml".to_string())
         );
-        assert_eq!(get_package_from_path("README.md"), None);
+        // Nested tsconfig files are package-local
+        assert_eq!(find_global_input(&changed(&["apps/web/tsconfig.base.json"]), &globals), None);
     }
 }
@@ -1,17 +1,28 @@
 //! Bundle command: Generate complete deployment packages
 //!
 //! Creates distribution-ready artifacts from built projects:
-//! - bundle.json: Metadata (version, hash, deps, timestamps)
-//! - image.tar: Docker image tarball (docker save)
+//! - bundle.json: Metadata (version, hash, deps, platform digests, timestamps)
+//! - image/: OCI image layout (every platform of multi-platform builds)
 //! - artifact.tar.gz: Standalone build artifacts
+//! - sbom.cdx.json / sbom.spdx.json: Third-party packages (see `sbom`)
+//! - provenance.intoto.json: SLSA provenance over the files above (see `provenance`)
 
 use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use crate::docker_build::{cache_hit, compute_content_hash};
+use crate::channel::{resolve_channel, RuntimeChannel, Toolchain};
+use crate::docker_build::{
+    cache_hit, cache_store, channel_hash, compute_content_hash, copy_dir_recursive, platform_hash, resolve_platforms,
+    BuildConfig, CachedArtifact,
+};
+use crate::oci;
+use crate::provenance::{self, Provenance, ResourceDescriptor, Statement};
+use crate::remote_cache::{remote_hit, Remote, Signing};
+use crate::sbom::{Sbom, SbomFormat};
 
 /// Bundle output result
 #[derive(Debug)]
@@ -19,7 +30,7 @@ use crate::docker_build::{cache_hit, compute_content_hash};
 pub struct BundleResult {
     pub output_dir: PathBuf,
     pub bundle_json: PathBuf,
-    pub image_tar: Option<PathBuf>,
+    pub image_layout: Option<PathBuf>,
     pub artifact_tar: Option<PathBuf>,
     pub k8s_dir: Option<PathBuf>,
 }
@@ -37,10 +48,40 @@ pub struct BundleMetadata {
     pub created_at: String,
     pub image_ref: Option<String>,
     pub cache_hit: bool,
+    /// Image manifest digest per platform
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub platforms: BTreeMap<String, String>,
+    /// Digest of the multi-platform image index
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index_digest: Option<String>,
+    /// SBOM files in the bundle
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sbom: Vec<String>,
+}
+
+/// What `bundle` does when there is no local cached build
+pub enum OnCacheMiss {
+    Fail,
+    /// Run `airis build --docker` for the project
+    Build,
+    /// Fetch the build's artifact from a remote cache (`--from-remote-cache`)
+    Remote { remote: Remote, signing: Signing },
 }
 
 /// Run bundle command
-pub fn run(project: &str, output_dir: Option<&Path>, k8s: bool) -> Result<BundleResult> {
+///
+/// `platforms` (`--platform`) selects the build to bundle like it did for
+/// `airis build --docker`; `sbom` lists the SBOM formats to include.
+/// The provenance is signed with `sign_key` (else `AIRIS_PROVENANCE_KEY`).
+pub fn run(
+    project: &str,
+    output_dir: Option<&Path>,
+    k8s: bool,
+    platforms: &[String],
+    sbom: &[SbomFormat],
+    sign_key: Option<&Path>,
+    on_miss: &OnCacheMiss,
+) -> Result<BundleResult> {
     use colored::Colorize;
 
     println!("{}", "==================================".bright_blue());
@@ -59,21 +100,15 @@ pub fn run(project: &str, output_dir: Option<&Path>, k8s: bool) -> Result<Bundle
         bail!("Project not found: {}", project);
     }
 
-    // 2. Calculate content hash
-    let hash = compute_content_hash(&root, project)?;
-    println!("📋 Content hash: {}", hash.yellow());
-
-    // 3. Check for cached build
-    let cached = cache_hit(project, &hash);
-    let cache_hit_status = cached.is_some();
-
-    if cached.is_none() {
-        println!("{}", "⚠️  No cached build found. Run 'airis build --docker' first.".yellow());
-        bail!("No cached build for {}. Run: airis build --docker {}", project, project);
-    }
+    // 2. Calculate content hash (`build --docker <project>` also keys by channel)
+    let platforms = resolve_platforms(&root, project, platforms)?;
+    let content_hash = platform_hash(&compute_content_hash(&root, project)?, &platforms);
+    let channel = get_runner_channel(project).unwrap_or_else(|| "lts".to_string());
+    let keys = [channel_hash(&content_hash, &channel), content_hash.clone()];
+    println!("📋 Content hash: {}", content_hash.yellow());
 
-    let cached = cached.unwrap();
-    println!("✅ Found cached build: {}", cached.image_ref.green());
+    // 3. Find the build: local cache, else remote cache or a fresh build
+    let (hash, cached, cache_hit_status) = find_build(&root, project, &keys, &channel, &platforms, on_miss)?;
 
     // 4. Create output directory
     let dist_dir = output_dir
@@ -87,18 +122,25 @@ pub fn run(project: &str, output_dir: Option<&Path>, k8s: bool) -> Result<Bundle
     println!("📦 Bundle output: {}", bundle_dir.display().to_string().cyan());
 
     // 5. Generate bundle.json
-    let metadata = generate_metadata(project, &hash, &cached.image_ref, cache_hit_status)?;
+    let mut metadata = generate_metadata(project, &hash, &cached.image_ref, cache_hit_status)?;
+    metadata.platforms = cached.platforms.clone();
+    metadata.index_digest = cached.index_digest.clone();
+    let toolchain = resolve_channel(&RuntimeChannel::parse(&metadata.runner_channel)?)?;
+    metadata.sbom = write_sbom(&root, project, &toolchain, &bundle_dir, sbom)?;
     let bundle_json_path = bundle_dir.join("bundle.json");
     let json_content = serde_json::to_string_pretty(&metadata)?;
     fs::write(&bundle_json_path, &json_content)?;
     println!("✅ Generated: bundle.json");
 
-    // 6. Export Docker image (docker save)
-    let image_tar_path = bundle_dir.join("image.tar");
-    let image_tar = export_docker_image(&cached.image_ref, &image_tar_path)?;
-    if image_tar.is_some() {
-        let size = fs::metadata(&image_tar_path)?.len();
-        println!("✅ Generated: image.tar ({})", format_size(size).dimmed());
+    // 6. Export the image as an OCI layout
+    let image_layout = export_image_layout(&cached, &bundle_dir.join("image"))?;
+    if image_layout.is_some() {
+        let platforms = if cached.platforms.is_empty() {
+            String::new()
+        } else {
+            format!(" for {}", cached.platforms.keys().cloned().collect::<Vec<_>>().join(", "))
+        };
+        println!("✅ Generated: image/ (OCI layout{})", platforms.dimmed());
     }
 
     // 7. Package build artifacts
@@ -118,7 +160,15 @@ pub fn run(project: &str, output_dir: Option<&Path>, k8s: bool) -> Result<Bundle
         None
     };
 
-    // 9. Print summary
+    // 9. Sign the provenance over everything written so far
+    let key = provenance::load_key(sign_key)?;
+    let statement = build_provenance(&root, project, &metadata, &platforms, &cached, &toolchain).statement(&bundle_dir)?;
+    let envelope = provenance::seal(&statement, key.as_deref())?;
+    fs::write(bundle_dir.join(provenance::FILE_NAME), serde_json::to_string_pretty(&envelope)?)?;
+    let signed = if key.is_some() { "signed" } else { "unsigned" };
+    println!("✅ Generated: {} ({})", provenance::FILE_NAME, signed.dimmed());
+
+    // 10. Print summary
     println!();
     println!("{}", "==================================".bright_blue());
     println!("{}", "✅ Bundle complete!".green().bold());
@@ -132,12 +182,87 @@ pub fn run(project: &str, output_dir: Option<&Path>, k8s: bool) -> Result<Bundle
     Ok(BundleResult {
         output_dir: bundle_dir,
         bundle_json: bundle_json_path,
-        image_tar,
+        image_layout,
         artifact_tar,
         k8s_dir,
     })
 }
 
+/// Cached build for one of `keys`, by cache key; false if it was just built
+fn find_build(
+    root: &Path,
+    project: &str,
+    keys: &[String],
+    channel: &str,
+    platforms: &[String],
+    on_miss: &OnCacheMiss,
+) -> Result<(String, CachedArtifact, bool)> {
+    use colored::Colorize;
+
+    for key in keys {
+        if let Some(cached) = cache_hit(project, key) {
+            println!("✅ Found cached build: {}", cached.image_ref.green());
+            return Ok((key.clone(), cached, true));
+        }
+    }
+
+    match on_miss {
+        OnCacheMiss::Fail => {
+            println!("{}", "⚠️  No cached build found. Run 'airis build --docker' first.".yellow());
+            bail!(
+                "No cached build for {}. Run: airis build --docker {} (or bundle with --build or --from-remote-cache)",
+                project,
+                project
+            );
+        }
+        OnCacheMiss::Remote { remote, signing } => {
+            for key in keys {
+                if let Some(artifact) = remote_hit(project, key, remote, signing)? {
+                    cache_store(project, key, &artifact)?;
+                    println!("✅ Found build in remote cache: {}", artifact.image_ref.green());
+                    pull_image(&artifact.image_ref);
+                    return Ok((key.clone(), artifact, true));
+                }
+            }
+            bail!("No build for {} in the remote cache (hash {})", project, keys[0]);
+        }
+        OnCacheMiss::Build => {
+            println!("{}", "🔨 No cached build, building...".cyan());
+            let config = BuildConfig {
+                target: project.to_string(),
+                channel: channel.to_string(),
+                platforms: platforms.to_vec(),
+                ..Default::default()
+            };
+            let result = crate::docker_build::docker_build(root, config)?;
+            let artifact = CachedArtifact::new(project, &keys[0], &result);
+            cache_store(project, &keys[0], &artifact)?;
+            Ok((keys[0].clone(), artifact, false))
+        }
+    }
+}
+
+/// Pull a remote-cache hit's image so it can be exported, unless Docker has it
+///
+/// Remote artifacts record the image ref only; the image itself is in a
+/// registry if the earlier stage pushed it.
+fn pull_image(image_ref: &str) {
+    use colored::Colorize;
+
+    let present = Command::new("docker")
+        .args(["image", "inspect", image_ref])
+        .output()
+        .is_ok_and(|o| o.status.success());
+    if present {
+        return;
+    }
+    println!("📥 Pulling {}...", image_ref);
+    let pulled = Command::new("docker").args(["pull", image_ref]).status().is_ok_and(|s| s.success());
+    if !pulled {
+        eprintln!("{}", format!("⚠️  Could not pull {}; the bundle may have no image", image_ref).yellow());
+    }
+}
+
 /// Generate bundle metadata
 fn generate_metadata(
     project: &str,
@@ -169,31 +294,357 @@ fn generate_metadata(
         created_at: chrono::Utc::now().to_rfc3339(),
         image_ref: Some(image_ref.to_string()),
         cache_hit,
+        platforms: BTreeMap::new(),
+        index_digest: None,
+        sbom: Vec::new(),
     })
 }
 
-/// Export Docker image to tarball
-fn export_docker_image(image_ref: &str, output_path: &Path) -> Result<Option<PathBuf>> {
+/// Write the SBOMs and return their file names
+fn write_sbom(root: &Path, project: &str, toolchain: &Toolchain, bundle_dir: &Path, formats: &[SbomFormat]) -> Result<Vec<String>> {
     use colored::Colorize;
 
-    println!("📤 Exporting Docker image...");
+    if formats.is_empty() {
+        return Ok(Vec::new());
+    }
+    if !root.join("pnpm-lock.yaml").exists() {
+        println!("{}", "⚠️  No pnpm-lock.yaml, skipping SBOM".yellow());
+        return Ok(Vec::new());
+    }
 
-    let output_path_str = output_path
-        .to_str()
-        .context("Docker image output path contains non-UTF-8 characters")?;
+    let sbom = Sbom::load(root, project, toolchain)?;
+    sbom.write(bundle_dir, formats)?;
+    let names: Vec<String> = formats.iter().map(|f| f.file_name().to_string()).collect();
+    println!("✅ Generated: {} ({} packages)", names.join(", "), sbom.packages.len().to_string().dimmed());
+    Ok(names)
+}
+
+/// Provenance of the bundled image: source commit, lockfile and base image
+fn build_provenance(
+    root: &Path,
+    project: &str,
+    metadata: &BundleMetadata,
+    platforms: &[String],
+    cached: &CachedArtifact,
+    toolchain: &Toolchain,
+) -> Provenance {
+    let mut materials = Vec::new();
+    if metadata.git_sha != "unknown" {
+        let remote = Command::new("git")
+            .args(["remote", "get-url", "origin"])
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+            .unwrap_or_else(|| root.display().to_string());
+        materials.push(ResourceDescriptor::material(
+            format!("git+{}@{}", remote, metadata.git_branch),
+            "gitCommit",
+            &metadata.git_sha,
+        ));
+    }
+    if let Ok(digest) = provenance::sha256_file(&root.join("pnpm-lock.yaml")) {
+        materials.push(ResourceDescriptor::material("pnpm-lock.yaml", "sha256", digest));
+    }
+    let image = format!("pkg:docker/{}", toolchain.image);
+    materials.push(match toolchain.digest.as_deref().and_then(|d| d.strip_prefix("sha256:")) {
+        Some(digest) => ResourceDescriptor::material(image, "sha256", digest),
+        None => ResourceDescriptor { name: None, uri: Some(image), digest: BTreeMap::new() },
+    });
+
+    let mut command = format!("airis build --docker {} --channel {}", project, metadata.runner_channel);
+    if !platforms.is_empty() {
+        command.push_str(&format!(" --platform {}", platforms.join(",")));
+    }
+
+    Provenance {
+        target: project.to_string(),
+        channel: metadata.runner_channel.clone(),
+        platforms: platforms.to_vec(),
+        content_hash: metadata.content_hash.clone(),
+        image_ref: cached.image_ref.clone(),
+        command,
+        builder_id: provenance::builder_id(),
+        built_at: cached.built_at.clone(),
+        materials,
+    }
+}
+
+/// Check a bundle directory (`airis bundle verify`)
+///
+/// Every file must match the provenance, bundle.json must agree with it,
+/// and the image layout's blobs must match their digests.
+pub fn verify(bundle_dir: &Path, key: Option<&Path>, allow_unsigned: bool) -> Result<BundleMetadata> {
+    use colored::Colorize;
+
+    let key = provenance::load_key(key)?;
+    let verified = provenance::verify(bundle_dir, key.as_deref(), allow_unsigned)?;
+    let (metadata, problems) = check_metadata(bundle_dir, &verified.statement)?;
+    if !problems.is_empty() {
+        bail!("bundle is inconsistent:\n  {}", problems.join("\n  "));
+    }
+
+    println!("✅ {} files match {}", verified.files, provenance::FILE_NAME);
+    println!("✅ bundle.json matches the provenance and image");
+    println!("   Target:  {}", metadata.name.cyan());
+    println!("   Hash:    {}", metadata.content_hash);
+    println!("   Builder: {}", verified.statement.predicate["runDetails"]["builder"]["id"].as_str().unwrap_or("?"));
+    if verified.signed {
+        println!("   {}", "Signature verified".green());
+    } else {
+        println!(
+            "   {}",
+            format!("Signature not checked (pass --key or set {})", provenance::DEFAULT_KEY_ENV).yellow()
+        );
+    }
+    Ok(metadata)
+}
+
+/// bundle.json, and where it disagrees with the provenance or the image layout
+fn check_metadata(bundle_dir: &Path, statement: &Statement) -> Result<(BundleMetadata, Vec<String>)> {
+    let path = bundle_dir.join("bundle.json");
+    let content = fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let metadata: BundleMetadata = serde_json::from_str(&content).context("Invalid bundle.json")?;
+    let mut problems = Vec::new();
+
+    let params = &statement.predicate["buildDefinition"];
+    for (field, actual, expected) in [
+        ("name", Some(&metadata.name), &params["externalParameters"]["target"]),
+        ("runner_channel", Some(&metadata.runner_channel), &params["externalParameters"]["channel"]),
+        ("content_hash", Some(&metadata.content_hash), &params["internalParameters"]["contentHash"]),
+        ("image_ref", metadata.image_ref.as_ref(), &params["internalParameters"]["imageRef"]),
+    ] {
+        if actual.map(String::as_str) != expected.as_str() {
+            problems.push(format!("bundle.json {} is {:?}, provenance says {}", field, actual, expected));
+        }
+    }
+    for name in &metadata.sbom {
+        if !bundle_dir.join(name).is_file() {
+            problems.push(format!("missing SBOM {}", name));
+        }
+    }
+
+    let layout = bundle_dir.join("image");
+    if layout.join("index.json").is_file() {
+        problems.extend(oci::check_blobs(&layout)?.1);
+        if !metadata.platforms.is_empty() {
+            let (index_digest, platforms) = oci::platform_digests(&layout)?;
+            if platforms != metadata.platforms {
+                problems.push("image/ platform digests differ from bundle.json".to_string());
+            }
+            if metadata.index_digest.is_some() && index_digest != metadata.index_digest {
+                problems.push("image/ index digest differs from bundle.json".to_string());
+            }
+        }
+    }
+
+    Ok((metadata, problems))
+}
+
+/// `airis bundle load` options
+#[derive(Debug, Clone)]
+pub struct LoadOptions {
+    /// Tag for the loaded image (default: bundle.json image_ref)
+    pub tag: Option<String>,
+    /// Platform to load from a multi-platform image (`docker load --platform`)
+    pub platform: Option<String>,
+    /// Provenance signing key file
+    pub key: Option<PathBuf>,
+    /// Accept a signed bundle without checking its signature (no key given)
+    pub allow_unsigned: bool,
+    pub skip_verify: bool,
+    /// Run a container and probe it before returning
+    pub smoke: bool,
+    /// HTTP path of the smoke probe
+    pub health: String,
+    /// Container port the app listens on
+    pub port: u16,
+    /// Seconds to wait for the probe to pass
+    pub timeout: u64,
+}
+
+/// Verify a bundle, `docker load` its image and tag it (`airis bundle load`)
+///
+/// Returns the tag. Loading an OCI layout needs Docker 25+.
+pub fn load(bundle_dir: &Path, options: &LoadOptions) -> Result<String> {
+    use colored::Colorize;
+
+    let metadata = if options.skip_verify {
+        println!("{}", "⚠️  Skipping bundle verification".yellow());
+        let content = fs::read_to_string(bundle_dir.join("bundle.json"))
+            .with_context(|| format!("Failed to read {}/bundle.json", bundle_dir.display()))?;
+        serde_json::from_str(&content).context("Invalid bundle.json")?
+    } else {
+        verify(bundle_dir, options.key.as_deref(), options.allow_unsigned)?
+    };
+
+    let layout = bundle_dir.join("image");
+    if !layout.join("index.json").is_file() {
+        bail!("{} has no image/ OCI layout", bundle_dir.display());
+    }
+    let Some(tag) = options.tag.clone().or_else(|| metadata.image_ref.clone()) else {
+        bail!("bundle.json has no image_ref; pass --tag");
+    };
+
+    println!("📥 Loading image...");
+    let temp = tempfile::tempdir()?;
+    let archive = temp.path().join("image.tar");
+    oci::write_archive(&layout, &archive)?;
+    let mut cmd = Command::new("docker");
+    cmd.arg("load").arg("-i").arg(&archive);
+    if let Some(platform) = &options.platform {
+        cmd.args(["--platform", platform]);
+    }
+    let output = cmd.output().context("Failed to run docker load")?;
+    if !output.status.success() {
+        bail!("docker load failed (OCI layouts need Docker 25+): {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    let Some(loaded) = loaded_image(&String::from_utf8_lossy(&output.stdout)) else {
+        bail!("docker load did not report the loaded image");
+    };
 
+    if loaded != tag {
+        let status = Command::new("docker")
+            .args(["tag", &loaded, &tag])
+            .status()
+            .context("Failed to run docker tag")?;
+        if !status.success() {
+            bail!("docker tag {} {} failed", loaded, tag);
+        }
+    }
+    println!("✅ Loaded: {}", tag.green());
+
+    if options.smoke {
+        smoke_test(&tag, options)?;
+    }
+    Ok(tag)
+}
+
+/// Image reference from `docker load` output ("Loaded image: web:1.0" or
+/// "Loaded image ID: sha256:...")
+fn loaded_image(output: &str) -> Option<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            line.strip_prefix("Loaded image: ")
+                .or_else(|| line.strip_prefix("Loaded image ID: "))
+        })
+        .map(|image| image.trim().to_string())
+        .next_back()
+}
+
+/// Run `image` and probe `options.health` until it answers, then remove it
+fn smoke_test(image: &str, options: &LoadOptions) -> Result<()> {
+    println!("🔥 Smoke test: {} (GET {} on port {})", image, options.health, options.port);
     let output = Command::new("docker")
-        .args(["save", "-o", output_path_str, image_ref])
+        .args(["run", "-d", "-p", &format!("127.0.0.1::{}", options.port), image])
+        .output()
+        .context("Failed to run docker run")?;
+    if !output.status.success() {
+        bail!("docker run failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    let container = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    let result = probe(&container, options);
+    if result.is_err() {
+        let _ = Command::new("docker").args(["logs", "--tail", "50", &container]).status();
+    }
+    let _ = Command::new("docker").args(["rm", "-f", &container]).output();
+    result?;
+
+    println!("✅ Smoke test passed");
+    Ok(())
+}
+
+fn probe(container: &str, options: &LoadOptions) -> Result<()> {
+    let docker = |args: &[&str]| -> Option<String> {
+        Command::new("docker")
+            .args(args)
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+    };
+
+    let port = format!("{}/tcp", options.port);
+    let Some(address) = docker(&["port", container, &port]).and_then(|a| a.lines().next().map(String::from)) else {
+        bail!("container does not publish port {}", options.port);
+    };
+    let url = format!("http://{}{}", address, options.health);
+    let agent = ureq::AgentBuilder::new().timeout(std::time::Duration::from_secs(2)).build();
+
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(options.timeout);
+    loop {
+        if docker(&["inspect", "-f", "{{.State.Running}}", container]).as_deref() != Some("true") {
+            bail!("container exited during the smoke test");
+        }
+        match agent.get(&url).call() {
+            Ok(_) => return Ok(()),
+            Err(ureq::Error::Status(code, _)) if std::time::Instant::now() >= deadline => {
+                bail!("{} answered {} after {}s", url, code, options.timeout)
+            }
+            Err(e) if std::time::Instant::now() >= deadline => bail!("{} failed after {}s: {}", url, options.timeout, e),
+            Err(_) => std::thread::sleep(std::time::Duration::from_secs(1)),
+        }
+    }
+}
+
+/// Export the image as an OCI layout directory
+///
+/// Builds for explicit platforms left one in the cache; other images are
+/// `docker save`d, which writes an OCI layout since Docker 25.
+fn export_image_layout(artifact: &CachedArtifact, layout: &Path) -> Result<Option<PathBuf>> {
+    use colored::Colorize;
+
+    println!("📤 Exporting image...");
+
+    if layout.exists() {
+        fs::remove_dir_all(layout)
+            .with_context(|| format!("Failed to remove {}", layout.display()))?;
+    }
+
+    if let Some(cached) = artifact.oci_layout.as_deref().filter(|p| p.join("index.json").is_file()) {
+        copy_dir_recursive(cached, layout)
+            .with_context(|| format!("Failed to copy OCI layout from {}", cached.display()))?;
+        return Ok(Some(layout.to_path_buf()));
+    }
+
+    fs::create_dir_all(layout)?;
+    let tarball = layout.with_extension("tar");
+    let output = Command::new("docker")
+        .arg("save")
+        .arg("-o")
+        .arg(&tarball)
+        .arg(&artifact.image_ref)
         .output()
         .context("Failed to run docker save")?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
         eprintln!("{}", format!("⚠️  docker save failed: {}", stderr).yellow());
+        let _ = fs::remove_dir_all(layout);
         return Ok(None);
     }
 
-    Ok(Some(output_path.to_path_buf()))
+    let unpacked = Command::new("tar")
+        .arg("-xf")
+        .arg(&tarball)
+        .arg("-C")
+        .arg(layout)
+        .output()
+        .context("Failed to unpack docker save output")?;
+    let _ = fs::remove_file(&tarball);
+
+    if !unpacked.status.success() || !layout.join("index.json").is_file() {
+        eprintln!(
+            "{}",
+            "⚠️  docker save did not produce an OCI layout (requires Docker 25+)".yellow()
+        );
+        let _ = fs::remove_dir_all(layout);
+        return Ok(None);
+    }
+
+    Ok(Some(layout.to_path_buf()))
 }
 
 /// Package build artifacts to tar.gz
@@ -337,31 +788,26 @@ fn get_runner_channel(project: &str) -> Option<String> {
 
     let project_name = project.rsplit('/').next().unwrap_or(project);
 
-    manifest
-        .get("projects")?
-        .get(project_name)?
-        .get("runner")?
-        .get("channel")?
+    let runner = manifest.get("projects")?.get(project_name)?.get("runner")?;
+    // Like `airis build --docker`: the channel, else the pinned version
+    runner
+        .get("channel")
+        .or_else(|| runner.get("version"))?
         .as_str()
         .map(|s| s.to_string())
 }
 
+/// Workspace dependencies of the project, by package name
 fn get_project_dependencies(project: &str) -> Option<Vec<String>> {
-    // Read package.json dependencies
-    let package_json_path = PathBuf::from(project).join("package.json");
-    let content = fs::read_to_string(&package_json_path).ok()?;
-    let pkg: serde_json::Value = serde_json::from_str(&content).ok()?;
-
-    let mut deps = Vec::new();
-
-    if let Some(dependencies) = pkg.get("dependencies").and_then(|d| d.as_object()) {
-        for (name, _) in dependencies {
-            if name.starts_with('@') && name.contains('/') {
-                // Workspace dependency
-                deps.push(name.clone());
-            }
-        }
-    }
+    let dag = crate::workspace_graph::load(Path::new(".")).ok()?;
+    let node = dag.get(project)?;
+
+    let mut deps: Vec<String> = node
+        .deps
+        .iter()
+        .map(|id| dag.get(id).map(|d| d.name.clone()).unwrap_or_else(|| id.clone()))
+        .collect();
+    deps.sort();
 
     Some(deps)
 }
@@ -508,6 +954,7 @@ spec:
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::manifest::RemoteCacheVerify;
 
     #[test]
     fn test_format_size() {
@@ -548,12 +995,115 @@ mod tests {
             created_at: "2025-01-01T00:00:00Z".to_string(),
             image_ref: Some("app:latest".to_string()),
             cache_hit: true,
+            platforms: BTreeMap::from([("linux/arm64".to_string(), "sha256:abc".to_string())]),
+            index_digest: None,
+            sbom: Vec::new(),
         };
 
         let json = serde_json::to_string(&metadata).unwrap();
         assert!(json.contains("apps/web"));
         assert!(json.contains("1.0.0"));
         assert!(json.contains("abc123"));
+        assert!(json.contains("linux/arm64"));
+        assert!(!json.contains("index_digest"));
+    }
+
+    #[test]
+    fn test_export_image_layout_copies_cached_layout() {
+        let temp = tempfile::tempdir().unwrap();
+        let cached = temp.path().join("cache/oci");
+        fs::create_dir_all(cached.join("blobs/sha256")).unwrap();
+        fs::write(cached.join("oci-layout"), r#"{"imageLayoutVersion":"1.0.0"}"#).unwrap();
+        fs::write(cached.join("index.json"), r#"{"schemaVersion":2,"manifests":[]}"#).unwrap();
+
+        let artifact = CachedArtifact {
+            image_ref: "web:airis-abc".to_string(),
+            hash: "abc".to_string(),
+            built_at: "2025-01-01T00:00:00Z".to_string(),
+            target: "apps/web".to_string(),
+            platforms: BTreeMap::new(),
+            index_digest: None,
+            oci_layout: Some(cached),
+        };
+        let layout = temp.path().join("dist/web/image");
+        fs::create_dir_all(&layout).unwrap();
+        fs::write(layout.join("stale"), "").unwrap();
+
+        assert_eq!(export_image_layout(&artifact, &layout).unwrap(), Some(layout.clone()));
+        assert!(layout.join("oci-layout").is_file());
+        assert!(layout.join("blobs/sha256").is_dir());
+        assert!(!layout.join("stale").exists());
+    }
+
+    #[test]
+    fn test_loaded_image() {
+        assert_eq!(loaded_image("Loaded image: web:airis-abc\n"), Some("web:airis-abc".to_string()));
+        assert_eq!(loaded_image("Loaded image ID: sha256:123\n"), Some("sha256:123".to_string()));
+        assert_eq!(loaded_image("nothing"), None);
+    }
+
+    #[test]
+    fn test_verify_checks_metadata_against_provenance() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let mut metadata = BundleMetadata {
+            name: "apps/web".to_string(),
+            version: "1.0.0".to_string(),
+            git_sha: "abc123".to_string(),
+            git_branch: "main".to_string(),
+            content_hash: "hash123".to_string(),
+            runner_channel: "lts".to_string(),
+            dependencies: Vec::new(),
+            created_at: "2025-01-01T00:00:00Z".to_string(),
+            image_ref: Some("web:airis-hash123".to_string()),
+            cache_hit: true,
+            platforms: BTreeMap::new(),
+            index_digest: None,
+            sbom: vec!["sbom.cdx.json".to_string()],
+        };
+        let provenance = Provenance {
+            target: "apps/web".to_string(),
+            channel: "lts".to_string(),
+            platforms: Vec::new(),
+            content_hash: "hash123".to_string(),
+            image_ref: "web:airis-hash123".to_string(),
+            command: "airis build --docker apps/web --channel lts".to_string(),
+            builder_id: "local://test".to_string(),
+            built_at: "2025-01-01T00:00:00Z".to_string(),
+            materials: Vec::new(),
+        };
+        let write = |metadata: &BundleMetadata| {
+            fs::write(dir.join("bundle.json"), serde_json::to_string(metadata).unwrap()).unwrap();
+            let envelope = provenance::seal(&provenance.statement(dir).unwrap(), None).unwrap();
+            fs::write(dir.join(provenance::FILE_NAME), serde_json::to_string(&envelope).unwrap()).unwrap();
+        };
+
+        fs::write(dir.join("sbom.cdx.json"), "{}").unwrap();
+        write(&metadata);
+        assert_eq!(verify(dir, None, false).unwrap().content_hash, "hash123");
+
+        // Re-signed after editing bundle.json: files match, metadata doesn't
+        metadata.content_hash = "other".to_string();
+        metadata.sbom.push("sbom.spdx.json".to_string());
+        write(&metadata);
+        let err = verify(dir, None, false).unwrap_err().to_string();
+        assert!(err.contains("content_hash"));
+        assert!(err.contains("missing SBOM sbom.spdx.json"));
+    }
+
+    #[test]
+    fn test_find_build_remote_miss() {
+        let remote_dir = tempfile::tempdir().unwrap();
+        let on_miss = OnCacheMiss::Remote {
+            remote: Remote::File { dir: remote_dir.path().to_path_buf() },
+            signing: Signing::new(Some(b"secret".to_vec()), RemoteCacheVerify::Strict),
+        };
+        let keys = ["no-such-hash-1".to_string(), "no-such-hash-2".to_string()];
+        let err = find_build(remote_dir.path(), "apps/bundle-test", &keys, "lts", &[], &on_miss).unwrap_err();
+        assert!(err.to_string().contains("No build for apps/bundle-test in the remote cache"));
+
+        let err = find_build(remote_dir.path(), "apps/bundle-test", &keys, "lts", &[], &OnCacheMiss::Fail).unwrap_err();
+        assert!(err.to_string().contains("--from-remote-cache"));
     }
 
     #[test]
@@ -163,6 +163,65 @@ pub fn run(dry_run: bool) -> Result<()> {
     Ok(())
 }
 
+/// Clean build artifacts inside the given package directories only
+///
+/// Applies `[workspace.clean]` dirs and recursive patterns relative to each
+/// package (e.g., `apps/web/.next`, `apps/web/node_modules`).
+pub fn run_packages(dry_run: bool, packages: &[String]) -> Result<()> {
+    let manifest = Manifest::load(MANIFEST_FILE)
+        .with_context(|| "Failed to load manifest.toml. Run 'airis init' first.")?;
+
+    let safe_fs = SafeFS::current(dry_run)?;
+
+    if dry_run {
+        println!("{}", "🔍 Dry-run mode: showing what would be cleaned...".bright_blue());
+    } else {
+        println!("{}", "🧹 Cleaning package build artifacts...".bright_blue());
+    }
+    println!();
+
+    let clean = &manifest.workspace.clean;
+    let mut cleaned = 0;
+    let mut skipped = 0;
+    let mut errors = 0;
+
+    for package in packages {
+        println!("{}", format!("📦 {}", package).cyan());
+        // Only paths inside the package
+        let targets = clean.dirs.iter().chain(&clean.recursive).filter(|p| !p.contains("..") && !p.starts_with('/'));
+        for name in targets {
+            let path = Path::new(package).join(name);
+            if !path.exists() {
+                continue;
+            }
+            match safe_fs.clean_artifact(&path) {
+                Ok(result) => {
+                    print_result(&result.action, &path.to_string_lossy(), &mut cleaned, &mut skipped);
+                }
+                Err(e) => {
+                    println!("   {} {} - {}", "✗".red(), path.display(), e);
+                    errors += 1;
+                }
+            }
+        }
+    }
+
+    println!();
+    if dry_run {
+        println!("{} Would clean {} item(s), {} skipped", "📋".cyan(), cleaned, skipped);
+    } else {
+        println!(
+            "{} Cleaned {} item(s), {} skipped, {} errors",
+            if errors == 0 { "✅".green() } else { "⚠️".yellow() },
+            cleaned,
+            skipped,
+            errors
+        );
+    }
+
+    Ok(())
+}
+
 fn print_result(action: &SafeAction, path: &str, cleaned: &mut usize, skipped: &mut usize) {
     match action {
         SafeAction::Deleted => {
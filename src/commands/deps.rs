@@ -2,14 +2,14 @@
 //!
 //! Provides commands to visualize and analyze the workspace dependency graph.
 
-use anyhow::{Context, Result};
+use anyhow::Result;
 use colored::Colorize;
 use serde::Serialize;
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
-use crate::dag::{build_dag, Dag, DagNode};
-use crate::pnpm::{build_workspace_map, PnpmLock};
+use crate::dag::{Dag, DagNode};
+use crate::workspace_graph;
 
 /// Dependency graph output for JSON serialization
 #[derive(Serialize)]
@@ -193,6 +193,21 @@ pub fn show(pkg: &str) -> Result<()> {
     Ok(())
 }
 
+/// Show dependencies for every package matching filter expressions
+pub fn show_filtered(filters: &[String]) -> Result<()> {
+    let dag = load_dag()?;
+    let packages = crate::filter::resolve(&dag, filters)?;
+
+    for (i, package) in packages.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        show(package)?;
+    }
+
+    Ok(())
+}
+
 /// Check for circular dependencies
 pub fn check() -> Result<()> {
     let dag = load_dag()?;
@@ -242,21 +257,9 @@ pub fn check() -> Result<()> {
 // Helper functions
 // ============================================================================
 
-/// Load DAG from pnpm-lock.yaml
+/// Load the workspace graph from the current directory
 fn load_dag() -> Result<Dag> {
-    let lock_path = Path::new("pnpm-lock.yaml");
-
-    if !lock_path.exists() {
-        anyhow::bail!(
-            "pnpm-lock.yaml not found. Run 'pnpm install' first or ensure you're in the workspace root."
-        );
-    }
-
-    let lock = PnpmLock::load(lock_path).context("Failed to parse pnpm-lock.yaml")?;
-    let workspace_map = build_workspace_map(&lock);
-    let dag = build_dag(&workspace_map);
-
-    Ok(dag)
+    workspace_graph::load(Path::new("."))
 }
 
 /// Build a map of package -> packages that depend on it
@@ -321,6 +324,11 @@ fn find_package<'a>(dag: &'a Dag, query: &str) -> Result<&'a DagNode> {
         return Ok(node);
     }
 
+    // Exact match by package name (e.g., "@agiletec/ui")
+    if let Some(node) = dag.find_by_name(query) {
+        return Ok(node);
+    }
+
     // Partial match
     let matches: Vec<&DagNode> = dag
         .nodes
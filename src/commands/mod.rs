@@ -21,3 +21,4 @@ pub mod shim;
 pub mod sync_deps;
 pub mod validate_cmd;
 pub mod verify;
+pub mod worker;
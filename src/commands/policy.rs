@@ -488,12 +488,14 @@ mod tests {
         let forbidden_file = temp.path().join(".env.local");
         std::fs::write(&forbidden_file, "SECRET=123").unwrap();
 
-        let mut result = PolicyResult::default();
-        result.passed = true;
+        let mut result = PolicyResult {
+            passed: true,
+            ..Default::default()
+        };
 
         // Use absolute path to avoid thread-safety issues with set_current_dir
         let abs_path = forbidden_file.to_string_lossy().to_string();
-        check_forbidden_files(&[abs_path.clone()], &mut result).unwrap();
+        check_forbidden_files(std::slice::from_ref(&abs_path), &mut result).unwrap();
 
         assert!(!result.violations.is_empty());
         assert!(result.violations[0].message.contains(".env.local"));
@@ -501,8 +503,10 @@ mod tests {
 
     #[test]
     fn test_required_env_missing() {
-        let mut result = PolicyResult::default();
-        result.passed = true;
+        let mut result = PolicyResult {
+            passed: true,
+            ..Default::default()
+        };
 
         check_required_env(&["DEFINITELY_NOT_SET_12345".to_string()], &mut result);
 
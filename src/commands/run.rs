@@ -4,13 +4,59 @@ use colored::Colorize;
 use glob::glob;
 use indexmap::IndexMap;
 use serde_json::Value;
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::sync::Arc;
 
+use crate::executor::{
+    self, BoxFuture, EventSink, ExecBackend, FailurePolicy, Job, JobOutput, LogSink, ParallelExecutor, Stream,
+    TaskEvent, TaskResult,
+};
 use crate::manifest::Manifest;
+use crate::pipeline;
+use crate::task_cache;
+use crate::task_history;
+use crate::task_logs::RunLog;
+use crate::task_ui::{TaskUi, UiCommand, UiMode};
+
+/// Options for `airis run` task pipelines
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    /// Number of parallel workers (default: CPU count)
+    pub parallel: Option<usize>,
+    /// Ignore cached task results (outputs are still stored)
+    pub no_cache: bool,
+    /// Only run in packages affected between (base, head)
+    pub affected: Option<(String, String)>,
+    /// Package filter expressions (see `filter`)
+    pub filter: Vec<String>,
+    /// `airis worker` endpoints to dispatch tasks to (empty: run locally)
+    pub workers: Vec<String>,
+    /// What to do with remaining tasks after a failure
+    pub failure_policy: FailurePolicy,
+    /// Retries for tasks that don't set `retries` in [tasks]
+    pub retries: u32,
+    /// Write a Chrome trace of the run here
+    pub profile: Option<PathBuf>,
+    /// Keep running: re-run the task for changed packages and their dependents
+    pub watch: bool,
+    /// How task output is shown
+    pub ui: UiMode,
+}
+
+/// Resolved execution details for one pipeline task
+struct PreparedTask {
+    package: String,
+    /// Command to run in the package directory (None when there is nothing to run)
+    command: Option<String>,
+    /// Cache key, set when the task declares `inputs`
+    cache_hash: Option<String>,
+    outputs: Vec<String>,
+}
 
 /// Extract package manager command from manifest (e.g., "pnpm@10.22.0" -> "pnpm")
-fn get_package_manager(manifest: &Manifest) -> &str {
+pub fn get_package_manager(manifest: &Manifest) -> &str {
     let pm = &manifest.workspace.package_manager;
     if pm.starts_with("pnpm") {
         "pnpm"
@@ -530,7 +576,7 @@ fn display_service_urls(manifest: &Manifest) -> Result<()> {
     // Combine and deduplicate routers
     let mut seen_urls = std::collections::HashSet::new();
 
-    for (router_name, host, path) in docker_routers.into_iter().chain(static_routers.into_iter()) {
+    for (router_name, host, path) in docker_routers.into_iter().chain(static_routers) {
         let url = format!("http://{}:{}{}", host, traefik_port, if path == "/" { "".to_string() } else { path.clone() });
 
         if seen_urls.contains(&url) {
@@ -952,11 +998,7 @@ fn default_commands(manifest: &Manifest) -> Result<IndexMap<String, String>> {
 
     let mut cmds = IndexMap::new();
 
-    // Detect project type: Rust or Node
-    let is_rust_project = !manifest.project.rust_edition.is_empty()
-        || !manifest.project.binary_name.is_empty();
-
-    if is_rust_project {
+    if is_rust_project(manifest) {
         // Rust project: use cargo commands (no docker compose required)
         cmds.insert("install".to_string(), "cargo install --path .".to_string());
         cmds.insert("build".to_string(), "cargo build --release".to_string());
@@ -988,6 +1030,11 @@ fn default_commands(manifest: &Manifest) -> Result<IndexMap<String, String>> {
     Ok(cmds)
 }
 
+/// Detect project type: Rust (host cargo commands) or Node (docker compose)
+fn is_rust_project(manifest: &Manifest) -> bool {
+    !manifest.project.rust_edition.is_empty() || !manifest.project.binary_name.is_empty()
+}
+
 /// Check if orchestration is configured in manifest
 fn has_orchestration(manifest: &Manifest) -> bool {
     let dev = &manifest.dev;
@@ -999,6 +1046,11 @@ fn has_orchestration(manifest: &Manifest) -> bool {
 
 /// Execute a command defined in manifest.toml [commands] section
 pub fn run(task: &str) -> Result<()> {
+    run_with_options(task, &RunOptions::default())
+}
+
+/// Execute a task: `[tasks.<name>]` pipeline if defined, otherwise `[commands]`
+pub fn run_with_options(task: &str, options: &RunOptions) -> Result<()> {
     let manifest_path = Path::new("manifest.toml");
 
     // Allow up/down without manifest.toml if docker-compose.yml exists
@@ -1052,6 +1104,17 @@ pub fn run(task: &str) -> Result<()> {
         }
     }
 
+    // Per-package task pipeline takes precedence over flat commands
+    // (--affected/--filter/--worker always run per package, using package.json scripts if needed)
+    if manifest.tasks.contains_key(task)
+        || options.affected.is_some()
+        || !options.filter.is_empty()
+        || !options.workers.is_empty()
+        || options.watch
+    {
+        return run_pipeline(&manifest, task, options);
+    }
+
     // Merge: defaults + manifest overrides (manifest wins)
     let mut commands = default_commands(&manifest)?;
     for (key, value) in manifest.commands.iter() {
@@ -1096,6 +1159,612 @@ pub fn run(task: &str) -> Result<()> {
     Ok(())
 }
 
+/// Wrap a package task command for execution from the workspace root
+///
+/// Docker-first: runs inside the workspace container via `docker compose exec`
+/// unless we are already in a container or this is a Rust project.
+fn task_shell_command(manifest: &Manifest, package: &str, cmd: &str, in_container: bool) -> Result<String> {
+    let inner = package_command(package, cmd);
+
+    if in_container || is_rust_project(manifest) {
+        return Ok(inner);
+    }
+
+    build_compose_command(
+        manifest,
+        &format!(
+            "exec -T {} sh -c '{}'",
+            manifest.workspace.service,
+            inner.replace('\'', "'\\''")
+        ),
+    )
+}
+
+/// `cd` into the package, then run `cmd`
+fn package_command(package: &str, cmd: &str) -> String {
+    format!("cd '{}' && {}", package.replace('\'', "'\\''"), cmd)
+}
+
+/// Runs task commands on this machine
+///
+/// With a manifest, commands go through `docker compose exec` like
+/// `task_shell_command`; without one (bare `airis worker` hosts) they run directly.
+pub struct LocalBackend {
+    root: PathBuf,
+    manifest: Option<Manifest>,
+    in_container: bool,
+    slots: usize,
+}
+
+impl LocalBackend {
+    pub fn new(root: PathBuf, manifest: Option<Manifest>, slots: usize) -> Self {
+        Self {
+            root,
+            manifest,
+            in_container: Path::new("/.dockerenv").exists(),
+            slots,
+        }
+    }
+}
+
+impl ExecBackend for LocalBackend {
+    fn describe(&self) -> String {
+        "local".to_string()
+    }
+
+    fn slots(&self) -> usize {
+        self.slots
+    }
+
+    fn run(&self, job: Job, logs: LogSink) -> BoxFuture<'_, Result<JobOutput>> {
+        Box::pin(async move {
+            let cmd = match &self.manifest {
+                Some(manifest) => task_shell_command(manifest, &job.package, &job.command, self.in_container)?,
+                None => package_command(&job.package, &job.command),
+            };
+
+            if !job.capture && logs.is_none() {
+                let mut child = async_shell(&cmd)
+                    .current_dir(&self.root)
+                    .spawn()
+                    .with_context(|| format!("Failed to execute: {}", cmd))?;
+                let guard = ProcessGroupGuard::new(&child);
+                let status = child.wait().await?;
+                guard.disarm();
+                return Ok(JobOutput {
+                    success: status.success(),
+                    exit_code: status.code(),
+                    ..Default::default()
+                });
+            }
+
+            let (status, stdout, stderr) = run_captured(&cmd, &self.root, logs).await?;
+            Ok(JobOutput {
+                success: status.success(),
+                exit_code: status.code(),
+                error: None,
+                stdout,
+                stderr,
+            })
+        })
+    }
+}
+
+/// Run a shell command asynchronously (for parallel task execution)
+///
+/// The child gets its own process group so a cancelled task (Ctrl-C, `--bail`)
+/// can kill everything it started; see `ProcessGroupGuard`.
+fn async_shell(cmd: &str) -> tokio::process::Command {
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = tokio::process::Command::new("cmd");
+        command.args(["/C", cmd]);
+        command
+    } else {
+        let mut command = tokio::process::Command::new("sh");
+        command.arg("-c").arg(cmd);
+        command
+    };
+    command.kill_on_drop(true);
+    #[cfg(unix)]
+    command.process_group(0);
+    command
+}
+
+/// Kills a task's process group if the task is dropped before the child exits
+///
+/// `kill_on_drop` only reaches the shell; this also gets its children
+/// (e.g., `pnpm` → `node`).
+struct ProcessGroupGuard(Option<u32>);
+
+impl ProcessGroupGuard {
+    fn new(child: &tokio::process::Child) -> Self {
+        Self(child.id())
+    }
+
+    fn disarm(mut self) {
+        self.0 = None;
+    }
+}
+
+impl Drop for ProcessGroupGuard {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(pid) = self.0 {
+            // SAFETY: plain syscall; a group that already exited just returns ESRCH
+            unsafe {
+                libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+            }
+        }
+    }
+}
+
+/// Echo a child stream to `writer` while capturing it
+async fn tee<R, W>(mut reader: R, mut writer: W) -> std::io::Result<Vec<u8>>
+where
+    R: tokio::io::AsyncRead + Unpin,
+    W: tokio::io::AsyncWrite + Unpin,
+{
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut captured = Vec::new();
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = reader.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        writer.write_all(&buf[..n]).await?;
+        captured.extend_from_slice(&buf[..n]);
+    }
+    writer.flush().await?;
+
+    Ok(captured)
+}
+
+/// Send a child stream to a log sink while capturing it
+async fn forward<R>(mut reader: R, stream: Stream, sink: tokio::sync::mpsc::UnboundedSender<(Stream, Vec<u8>)>) -> std::io::Result<Vec<u8>>
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    use tokio::io::AsyncReadExt;
+
+    let mut captured = Vec::new();
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = reader.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        let _ = sink.send((stream, buf[..n].to_vec()));
+        captured.extend_from_slice(&buf[..n]);
+    }
+
+    Ok(captured)
+}
+
+/// Run a command in `dir`, capturing stdout/stderr (for cache log replay)
+///
+/// Output is echoed to the terminal, or sent to `logs` when given.
+async fn run_captured(cmd: &str, dir: &Path, logs: LogSink) -> Result<(std::process::ExitStatus, Vec<u8>, Vec<u8>)> {
+    let mut command = async_shell(cmd);
+    if logs.is_some() {
+        // Output is relayed (log file, task display); keep keystrokes away from the task
+        command.stdin(Stdio::null());
+    }
+    let mut child = command
+        .current_dir(dir)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to execute: {}", cmd))?;
+
+    let guard = ProcessGroupGuard::new(&child);
+    let stdout = child.stdout.take().context("Failed to capture stdout")?;
+    let stderr = child.stderr.take().context("Failed to capture stderr")?;
+
+    let streams = async {
+        match logs {
+            Some(sink) => tokio::join!(
+                forward(stdout, Stream::Stdout, sink.clone()),
+                forward(stderr, Stream::Stderr, sink)
+            ),
+            None => tokio::join!(tee(stdout, tokio::io::stdout()), tee(stderr, tokio::io::stderr())),
+        }
+    };
+    let ((out, err), status) = tokio::join!(streams, child.wait());
+    guard.disarm();
+
+    Ok((status?, out?, err?))
+}
+
+/// Execute a `[tasks.<name>]` pipeline across all workspace packages
+fn run_pipeline(manifest: &Manifest, task: &str, options: &RunOptions) -> Result<()> {
+    let root = std::env::current_dir()?;
+    let dag = crate::workspace_graph::load(&root)?;
+
+    let mut packages = match &options.affected {
+        Some((base, head)) => {
+            let affected = crate::commands::affected::run(base, head)?;
+            println!();
+            affected
+        }
+        None => dag.ids(),
+    };
+    if !options.filter.is_empty() {
+        let filtered = crate::filter::resolve(&dag, &options.filter)?;
+        packages.retain(|p| filtered.contains(p));
+    }
+    if packages.is_empty() {
+        println!("{}", format!("✅ No matching packages to {}", task).green());
+        return Ok(());
+    }
+
+    if options.watch {
+        return watch_pipeline(manifest, task, &root, &dag, &packages, options);
+    }
+    execute_pipeline(manifest, task, &root, &dag, &packages, None, options)
+}
+
+/// Run the pipeline, then re-run it for changed packages until interrupted
+///
+/// Only tasks of changed packages and their dependents run again; everything
+/// upstream is left as the previous run produced it.
+fn watch_pipeline(
+    manifest: &Manifest,
+    task: &str,
+    root: &Path,
+    dag: &crate::dag::Dag,
+    packages: &[String],
+    options: &RunOptions,
+) -> Result<()> {
+    // Watch every package the task runs in, including upstream ones pulled in by `^deps`
+    let plan = pipeline::plan(dag, &manifest.tasks, task, packages)?;
+    let mut watched: Vec<String> = plan.iter().map(|t| t.package.clone()).collect();
+    watched.sort();
+    watched.dedup();
+    let outputs: Vec<String> = manifest.tasks.values().flat_map(|c| c.outputs.clone()).collect();
+
+    // Start watching before the first run so edits made during it are picked up
+    let watcher = crate::watch::Watcher::new(root, &watched, &outputs)?;
+
+    if let Err(e) = execute_pipeline(manifest, task, root, dag, packages, None, options) {
+        println!("{}", format!("❌ {}", e).red());
+    }
+
+    loop {
+        println!("{}", format!("\n👀 Watching {} packages for changes (Ctrl-C to stop)", watched.len()).cyan());
+        let changed = watcher.next_change(dag)?;
+        let rerun: std::collections::HashSet<String> = dag
+            .with_dependents(&changed)
+            .into_iter()
+            .filter(|p| watched.contains(p))
+            .collect();
+
+        println!(
+            "{}",
+            format!("🔄 Changed: {} → re-running {} in {} package(s)", changed.join(", "), task, rerun.len()).cyan()
+        );
+        if let Err(e) = execute_pipeline(manifest, task, root, dag, packages, Some(&rerun), options) {
+            println!("{}", format!("❌ {}", e).red());
+        }
+    }
+}
+
+/// Plan and execute `task` for `packages`
+///
+/// With `only`, tasks outside those packages are left out (their cache keys
+/// still feed into the ones that run).
+fn execute_pipeline(
+    manifest: &Manifest,
+    task: &str,
+    root: &Path,
+    dag: &crate::dag::Dag,
+    packages: &[String],
+    only: Option<&std::collections::HashSet<String>>,
+    options: &RunOptions,
+) -> Result<()> {
+    let root = root.to_path_buf();
+    let plan = pipeline::plan(dag, &manifest.tasks, task, packages)?;
+
+    // Resolve commands and cache keys up front, in dependency order
+    // (packages without the script are no-ops)
+    let pm = get_package_manager(manifest);
+    let mut hashes: HashMap<String, String> = HashMap::new();
+    let mut prepared: HashMap<String, PreparedTask> = HashMap::new();
+    let mut file_cache = crate::hash_cache::HashCache::load(&root);
+    for t in &plan {
+        let config = manifest.tasks.get(&t.task);
+        let command = pipeline::resolve_command(&root, &t.package, &t.task, config, pm);
+
+        let cacheable = config.is_some_and(|c| !c.inputs.is_empty());
+        let dep_hashes: Vec<String> = t
+            .dependencies
+            .iter()
+            .filter_map(|d| hashes.get(d).cloned())
+            .collect();
+        let hash = task_cache::compute_task_hash(
+            &root,
+            &t.package,
+            &t.task,
+            command.as_deref().unwrap_or_default(),
+            config,
+            &dep_hashes,
+            &mut file_cache,
+        )?;
+        hashes.insert(t.id.clone(), hash.clone());
+
+        prepared.insert(
+            t.id.clone(),
+            PreparedTask {
+                package: t.package.clone(),
+                command,
+                cache_hash: cacheable.then_some(hash),
+                outputs: config.map(|c| c.outputs.clone()).unwrap_or_default(),
+            },
+        );
+    }
+    if let Err(e) = file_cache.save() {
+        eprintln!("⚠️  Warning: Failed to save hash cache: {}", e);
+    }
+
+    let rt = tokio::runtime::Runtime::new()?;
+    let backend: Arc<dyn ExecBackend> = if options.workers.is_empty() {
+        let slots = options.parallel.unwrap_or_else(executor::default_parallelism);
+        Arc::new(LocalBackend::new(root.clone(), Some(manifest.clone()), slots))
+    } else {
+        let backend = rt.block_on(crate::worker::WorkerBackend::connect(&options.workers, root.clone()))?;
+        println!("{}", format!("🛰️  Dispatching to {} ({} slots)", backend.describe(), backend.slots()).cyan());
+        Arc::new(backend)
+    };
+
+    let worker_count = options.parallel.unwrap_or_else(|| backend.slots());
+    let mut history = task_history::TaskHistory::load(&root);
+    let mut exec = ParallelExecutor::new(worker_count)
+        .failure_policy(options.failure_policy)
+        .estimates(history.estimates());
+    let mut tasks = Vec::new();
+    for t in &plan {
+        if only.is_some_and(|only| !only.contains(&t.package)) {
+            continue;
+        }
+        let config = manifest.tasks.get(&t.task);
+        let mut build_task = t.to_build_task();
+        // Tasks left out already ran
+        build_task.dependencies.retain(|d| {
+            pipeline::split_task_id(d).is_none_or(|(package, _)| only.is_none_or(|only| only.contains(package)))
+        });
+        build_task.retries = config.and_then(|c| c.retries).unwrap_or(options.retries);
+        build_task.weight = config.and_then(|c| c.weight).unwrap_or(1);
+        tasks.push(build_task.clone());
+        exec.add_task(build_task);
+    }
+
+    // The TUI would fight with the watch loop's output
+    let mode = match options.ui.resolve() {
+        UiMode::Tui if options.watch => UiMode::Grouped,
+        mode => mode,
+    };
+    let task_ids: Vec<String> = tasks.iter().map(|t| t.id.clone()).collect();
+    let ui = TaskUi::start(mode, &format!("airis run {}", task), &task_ids, exec.canceller())?;
+    let events = ui.as_ref().map(|ui| ui.events());
+    if let Some(events) = &events {
+        exec = exec.events(events.clone());
+    }
+
+    let run_log = Arc::new(RunLog::start(&root, &format!("airis run {}", task))?);
+    let prepared = Arc::new(prepared);
+    let no_cache = options.no_cache;
+    let history_root = root.clone();
+    let task_logs = Arc::clone(&run_log);
+    let task_fn = move |build_task: executor::BuildTask| {
+        let prepared = Arc::clone(&prepared);
+        let backend = Arc::clone(&backend);
+        let run_log = Arc::clone(&task_logs);
+        let root = root.clone();
+        let events = events.clone();
+        async move {
+            let start = std::time::Instant::now();
+            let task = &prepared[&build_task.id];
+
+            let Some(cmd) = task.command.as_deref() else {
+                return Ok(TaskResult {
+                    task_id: build_task.id,
+                    success: true,
+                    duration_ms: 0,
+                    error: None,
+                    cached: false,
+                    skipped: false,
+                });
+            };
+
+            let mut out = TaskOutput {
+                task_id: build_task.id.clone(),
+                // Not being able to keep a log shouldn't fail the task
+                log: run_log.create(&build_task.id).ok(),
+                events: events.clone(),
+            };
+
+            // Cache hit: restore outputs and replay logs
+            if let Some(hash) = &task.cache_hash
+                && !no_cache
+                && let Some(entry) = task_cache::cache_hit(&task.package, hash) {
+                    task_cache::cache_restore(&root, &task.package, &entry)?;
+                    if events.is_none() {
+                        println!("{}", format!("  ⚡ {}: cache hit ({}), replaying logs", build_task.id, hash).green());
+                    }
+                    out.log(format!("⚡ cache hit ({})\n", hash).as_bytes());
+                    let (stdout, stderr) = task_cache::read_logs(&task.package, hash);
+                    out.write(Stream::Stdout, stdout);
+                    out.write(Stream::Stderr, stderr);
+                    return Ok(TaskResult {
+                        task_id: build_task.id,
+                        success: true,
+                        duration_ms: start.elapsed().as_millis() as u64,
+                        error: None,
+                        cached: true,
+                        skipped: false,
+                    });
+                }
+
+            let started = format!("▶ {}\n", cmd).into_bytes();
+            if events.is_some() {
+                out.write(Stream::Stdout, started);
+            } else {
+                println!("{}", format!("  ▶ {}: {}", build_task.id, cmd).dimmed());
+                out.log(&started);
+            }
+            let job = Job {
+                id: build_task.id.clone(),
+                package: task.package.clone(),
+                command: cmd.to_string(),
+                outputs: task.outputs.clone(),
+                capture: task.cache_hash.is_some(),
+            };
+
+            // Forward output to the log file and the display
+            let (sink, mut logs) = tokio::sync::mpsc::unbounded_channel();
+            let forwarder = tokio::spawn(async move {
+                while let Some((stream, data)) = logs.recv().await {
+                    out.write(stream, data);
+                }
+            });
+            let output = backend.run(job, Some(sink)).await;
+            // All output is in before the task is reported finished
+            let _ = forwarder.await;
+            let output = output?;
+            if output.success
+                && let Some(hash) = &task.cache_hash {
+                    task_cache::cache_store(&root, &task.package, &build_task.id, hash, &task.outputs, &output.stdout, &output.stderr)?;
+                }
+
+            Ok(TaskResult {
+                task_id: build_task.id,
+                success: output.success,
+                duration_ms: start.elapsed().as_millis() as u64,
+                error: (!output.success)
+                    .then(|| output.error.unwrap_or_else(|| format!("exit code {:?}", output.exit_code))),
+                cached: false,
+                skipped: false,
+            })
+        }
+    };
+    let mut results = rt.block_on(exec.execute(task_fn.clone()))?;
+
+    // TUI: re-run failed tasks on request until the user quits
+    if let Some(ui) = &ui {
+        while let Some(UiCommand::Rerun(task_id)) = ui.next_command() {
+            let mut rerun = ParallelExecutor::new(worker_count)
+                .failure_policy(options.failure_policy)
+                .events(ui.events());
+            for build_task in rerun_tasks(&tasks, &results, &task_id) {
+                rerun.add_task(build_task);
+            }
+            for result in rt.block_on(rerun.execute(task_fn.clone()))? {
+                match results.iter_mut().find(|r| r.task_id == result.task_id) {
+                    Some(previous) => *previous = result,
+                    None => results.push(result),
+                }
+            }
+        }
+    }
+    if let Some(ui) = ui {
+        let tui = ui.is_tui();
+        ui.finish()?;
+        if tui {
+            executor::print_summary(&results, results.len());
+        }
+    }
+
+    history.record(&results);
+    history.save(&history_root)?;
+    run_log.finish(&results)?;
+
+    if let Some(path) = &options.profile {
+        crate::profile::write(path, &format!("airis run {}", task), &exec, &results)?;
+    }
+
+    let failed = results.iter().filter(|r| !r.success && !r.skipped).count();
+    let skipped = results.iter().filter(|r| r.skipped).count();
+    if failed + skipped > 0 {
+        if let Some(first) = results.iter().find(|r| !r.success && !r.skipped) {
+            println!(
+                "{}",
+                format!("📜 Logs: airis logs --task {} --run {}", first.task_id, run_log.id()).dimmed()
+            );
+        }
+        bail!("{} task(s) failed, {} skipped", failed, skipped);
+    }
+
+    Ok(())
+}
+
+/// Where a pipeline task's output goes: its log file, then the display
+struct TaskOutput {
+    task_id: String,
+    log: Option<std::fs::File>,
+    /// None: straight to the terminal
+    events: Option<EventSink>,
+}
+
+impl TaskOutput {
+    fn log(&mut self, data: &[u8]) {
+        use std::io::Write;
+        if let Some(log) = &mut self.log {
+            let _ = log.write_all(data);
+        }
+    }
+
+    fn write(&mut self, stream: Stream, data: Vec<u8>) {
+        use std::io::Write;
+        self.log(&data);
+        match &self.events {
+            Some(events) => {
+                let _ = events.send(TaskEvent::Output { task_id: self.task_id.clone(), stream, data });
+            }
+            None => {
+                let _ = match stream {
+                    Stream::Stdout => std::io::stdout().write_all(&data),
+                    Stream::Stderr => std::io::stderr().write_all(&data),
+                };
+            }
+        }
+    }
+}
+
+/// A failed task plus the tasks skipped because of it, for a re-run
+///
+/// Dependencies on tasks outside that set are dropped (they already succeeded).
+pub(crate) fn rerun_tasks(tasks: &[executor::BuildTask], results: &[TaskResult], failed: &str) -> Vec<executor::BuildTask> {
+    let skipped: std::collections::HashSet<&str> = results
+        .iter()
+        .filter(|r| r.skipped)
+        .map(|r| r.task_id.as_str())
+        .collect();
+
+    let mut selected: std::collections::HashSet<String> = std::collections::HashSet::from([failed.to_string()]);
+    loop {
+        let before = selected.len();
+        for t in tasks {
+            if skipped.contains(t.id.as_str()) && t.dependencies.iter().any(|d| selected.contains(d)) {
+                selected.insert(t.id.clone());
+            }
+        }
+        if selected.len() == before {
+            break;
+        }
+    }
+
+    tasks
+        .iter()
+        .filter(|t| selected.contains(&t.id))
+        .map(|t| {
+            let mut t = t.clone();
+            t.dependencies.retain(|d| selected.contains(d));
+            t
+        })
+        .collect()
+}
+
 /// Execute logs command with options
 pub fn run_logs(service: Option<&str>, follow: bool, tail: Option<u32>) -> Result<()> {
     let manifest_path = Path::new("manifest.toml");
@@ -1846,6 +2515,34 @@ name = "test"
         result.unwrap();
     }
 
+    #[test]
+    fn test_task_shell_command_in_container() {
+        let manifest: Manifest = toml::from_str("[workspace]\nname = \"test\"").unwrap();
+        let cmd = task_shell_command(&manifest, "libs/ui", "pnpm run build", true).unwrap();
+        assert_eq!(cmd, "cd 'libs/ui' && pnpm run build");
+    }
+
+    #[test]
+    fn test_task_shell_command_wraps_compose_exec() {
+        let _guard = DIR_LOCK.lock().unwrap();
+        let dir = tempdir().unwrap();
+        let original_dir = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&dir).unwrap();
+        std::fs::write("docker-compose.yml", "services: {}").unwrap();
+
+        let result = std::panic::catch_unwind(|| {
+            let manifest: Manifest =
+                toml::from_str("[workspace]\nname = \"test\"\nservice = \"workspace\"").unwrap();
+            let cmd = task_shell_command(&manifest, "apps/web", "pnpm run build", false).unwrap();
+            assert!(cmd.starts_with("docker compose -f docker-compose.yml exec -T workspace sh -c"));
+            assert!(cmd.contains("apps/web"));
+            assert!(cmd.contains("pnpm run build"));
+        });
+
+        std::env::set_current_dir(original_dir).unwrap();
+        result.unwrap();
+    }
+
     #[test]
     fn test_build_compose_command_with_orchestration_succeeds() {
         let _guard = DIR_LOCK.lock().unwrap();
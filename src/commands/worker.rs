@@ -0,0 +1,74 @@
+//! `airis worker`: run pipeline tasks for remote `airis run --worker ...` clients
+//!
+//! Speaks the `worker` protocol on stdio (default, e.g. behind `ssh`) or on a
+//! TCP listener. Anyone who can connect can run commands in this checkout, so
+//! listening beyond loopback requires `AIRIS_WORKER_TOKEN`.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::commands::run::LocalBackend;
+use crate::executor;
+use crate::manifest::{Manifest, MANIFEST_FILE};
+use crate::worker::{self, Worker};
+
+pub fn run(listen: Option<&str>, jobs: Option<usize>) -> Result<()> {
+    let root = std::env::current_dir()?;
+    let manifest = if Path::new(MANIFEST_FILE).exists() {
+        Some(Manifest::load(MANIFEST_FILE)?)
+    } else {
+        None
+    };
+
+    let slots = jobs.unwrap_or_else(executor::default_parallelism);
+    let backend = Arc::new(LocalBackend::new(root.clone(), manifest, slots));
+    let token = worker::token_from_env();
+    let worker = Arc::new(Worker::new(backend, root.clone(), token.clone()));
+
+    // stdout carries the protocol, so progress goes to stderr
+    let rt = tokio::runtime::Runtime::new()?;
+    rt.block_on(async move {
+        let Some(addr) = listen else {
+            eprintln!("{}", format!("🛠️  airis worker ready on stdio ({} slots, {})", slots, root.display()).cyan());
+            return worker.serve(tokio::io::stdin(), tokio::io::stdout()).await;
+        };
+
+        if token.is_none() {
+            let addrs: Vec<_> = tokio::net::lookup_host(addr)
+                .await
+                .with_context(|| format!("Failed to resolve {}", addr))?
+                .collect();
+            if addrs.iter().any(|a| !a.ip().is_loopback()) {
+                bail!(
+                    "Refusing to listen on {} without {}: anyone who connects could run commands. \
+                     Set a shared token or listen on 127.0.0.1.",
+                    addr,
+                    worker::TOKEN_ENV
+                );
+            }
+        }
+
+        let listener = tokio::net::TcpListener::bind(addr)
+            .await
+            .with_context(|| format!("Failed to listen on {}", addr))?;
+        eprintln!(
+            "{}",
+            format!("🛠️  airis worker listening on {} ({} slots, {})", listener.local_addr()?, slots, root.display()).cyan()
+        );
+
+        loop {
+            let (stream, peer) = listener.accept().await?;
+            eprintln!("  🔌 {} connected", peer);
+            let worker = Arc::clone(&worker);
+            tokio::spawn(async move {
+                let (reader, writer) = stream.into_split();
+                match worker.serve(reader, writer).await {
+                    Ok(()) => eprintln!("  👋 {} disconnected", peer),
+                    Err(e) => eprintln!("  {} {}: {:#}", "⚠️  Worker connection failed:".yellow(), peer, e),
+                }
+            });
+        }
+    })
+}
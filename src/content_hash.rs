@@ -0,0 +1,257 @@
+//! Source hashing for Docker build caching (`airis build --docker`, `airis bundle`)
+//!
+//! A project's hash covers:
+//! - the workspace root files every image build copies (lockfile, tsconfig, ...)
+//! - the project's files: everything not ignored by `.gitignore` / `.airisignore`
+//!   (in the package or any directory up to the workspace root), or only the
+//!   files matching its `inputs` globs when `[[app]] inputs = [...]` is set
+//! - the hashes of its workspace dependencies, transitively via the DAG, so a
+//!   lib change invalidates every app image built on it
+//!
+//! File contents enter as digests from [`HashCache`].
+
+use anyhow::{Context, Result};
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::dag::{Dag, DagNode};
+use crate::hash_cache::HashCache;
+use crate::manifest::ProjectDefinition;
+
+/// Workspace root files that go into every project hash
+pub const ROOT_FILES: &[&str] = &[
+    "package.json",
+    "pnpm-lock.yaml",
+    "pnpm-workspace.yaml",
+    ".npmrc",
+    "tsconfig.base.json",
+    "tsconfig.json",
+];
+
+/// Ignore file for build inputs only (same syntax as `.gitignore`)
+pub const IGNORE_FILE: &str = ".airisignore";
+
+/// Never build inputs, whatever the ignore files say
+const ALWAYS_IGNORED: &[&str] = &[".git", "node_modules", ".airis"];
+
+/// Hash `target` and the workspace packages it depends on
+///
+/// `apps` supplies per-project `inputs` globs (`[[app]]` in manifest.toml).
+pub fn project_hash(
+    root: &Path,
+    dag: &Dag,
+    apps: &[ProjectDefinition],
+    target: &str,
+    cache: &mut HashCache,
+) -> Result<String> {
+    let mut hasher = blake3::Hasher::new();
+    let root_files: Vec<&str> = ROOT_FILES.iter().copied().filter(|f| root.join(f).is_file()).collect();
+    let paths: Vec<PathBuf> = root_files.iter().map(|f| root.join(f)).collect();
+    for (file, digest) in root_files.iter().zip(cache.digests(&paths)?) {
+        hasher.update(file.as_bytes());
+        hasher.update(digest.as_bytes());
+    }
+
+    // Dependency-first, so each package can fold in its dependencies' hashes
+    let mut hashes: HashMap<&str, String> = HashMap::new();
+    for node in dag.topo_order(target)? {
+        let deps: Vec<(&str, &str)> = node
+            .deps
+            .iter()
+            .filter_map(|d| hashes.get(d.as_str()).map(|h| (d.as_str(), h.as_str())))
+            .collect();
+        let hash = package_hash(root, &node.path, inputs_for(apps, node), &deps, cache)?;
+        hashes.insert(&node.id, hash);
+    }
+
+    // Projects outside the workspace graph are hashed on their own
+    let own = match hashes.get(target) {
+        Some(hash) => hash.clone(),
+        None => package_hash(root, target, &[], &[], cache)?,
+    };
+    hasher.update(own.as_bytes());
+
+    Ok(hasher.finalize().to_hex()[..12].to_string())
+}
+
+/// `inputs` of the `[[app]]` entry for a package (matched by path or name)
+fn inputs_for<'a>(apps: &'a [ProjectDefinition], node: &DagNode) -> &'a [String] {
+    let dir_name = node.path.rsplit('/').next().unwrap_or(&node.path);
+    apps.iter()
+        .find(|app| app.path.as_deref() == Some(node.path.as_str()) || app.name == node.name || app.name == dir_name)
+        .map(|app| app.inputs.as_slice())
+        .unwrap_or_default()
+}
+
+/// Hash of a package's files plus its direct dependencies' hashes
+fn package_hash(
+    root: &Path,
+    package: &str,
+    inputs: &[String],
+    deps: &[(&str, &str)],
+    cache: &mut HashCache,
+) -> Result<String> {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(package.as_bytes());
+
+    for (dep, hash) in deps {
+        hasher.update(dep.as_bytes());
+        hasher.update(hash.as_bytes());
+    }
+
+    let files = package_files(root, package, inputs)?;
+    for (path, digest) in files.iter().zip(cache.digests(&files)?) {
+        let rel = path.strip_prefix(root).unwrap_or(path);
+        hasher.update(rel.to_string_lossy().replace('\\', "/").as_bytes());
+        hasher.update(digest.as_bytes());
+    }
+
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+/// Files of `package` that feed its hash, sorted
+///
+/// With `inputs`, only files matching those globs (relative to the package)
+/// count; a glob naming a directory (`src`) selects everything below it.
+pub fn package_files(root: &Path, package: &str, inputs: &[String]) -> Result<Vec<PathBuf>> {
+    let dir = root.join(package);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut walker = WalkBuilder::new(&dir);
+    walker
+        .hidden(false)
+        .parents(false)
+        .git_global(false)
+        .git_exclude(false)
+        .require_git(false)
+        .add_custom_ignore_filename(IGNORE_FILE)
+        .filter_entry(|entry| !ALWAYS_IGNORED.iter().any(|name| entry.file_name() == *name));
+
+    // Ignore files between the workspace root and the package
+    // (`parents(true)` would keep going above the workspace)
+    for ancestor in dir.ancestors().skip(1).take_while(|a| a.starts_with(root)) {
+        for name in [".gitignore", IGNORE_FILE] {
+            let file = ancestor.join(name);
+            if file.is_file()
+                && let Some(err) = walker.add_ignore(&file)
+            {
+                return Err(err).with_context(|| format!("Invalid ignore file {}", file.display()));
+            }
+        }
+    }
+
+    if !inputs.is_empty() {
+        let mut overrides = OverrideBuilder::new(&dir);
+        for pattern in inputs {
+            overrides
+                .add(pattern)
+                .with_context(|| format!("Invalid inputs glob: {}", pattern))?;
+            if !pattern.ends_with("/**") {
+                overrides.add(&format!("{}/**", pattern))?;
+            }
+        }
+        walker.overrides(overrides.build()?);
+    }
+
+    let mut files = Vec::new();
+    for entry in walker.build() {
+        let entry = entry?;
+        if entry.file_type().is_some_and(|t| t.is_file()) {
+            files.push(entry.into_path());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn write(root: &Path, file: &str, content: &str) {
+        let path = root.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    /// apps/web depends on libs/ui
+    fn workspace() -> (tempfile::TempDir, Dag) {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, "pnpm-workspace.yaml", "packages:\n  - apps/*\n  - libs/*\n");
+        write(root, ".gitignore", "dist/\n");
+        write(root, "libs/ui/package.json", r#"{"name": "ui"}"#);
+        write(root, "libs/ui/src/index.ts", "export {}");
+        write(root, "apps/web/package.json", r#"{"name": "web", "dependencies": {"ui": "workspace:*"}}"#);
+        write(root, "apps/web/src/distance.ts", "export const d = 1");
+        let dag = crate::workspace_graph::load(root).unwrap();
+        (dir, dag)
+    }
+
+    fn files(root: &Path, package: &str, inputs: &[&str]) -> Vec<String> {
+        let inputs: Vec<String> = inputs.iter().map(|s| s.to_string()).collect();
+        package_files(root, package, &inputs)
+            .unwrap()
+            .iter()
+            .map(|p| p.strip_prefix(root.join(package)).unwrap().to_string_lossy().replace('\\', "/"))
+            .collect()
+    }
+
+    #[test]
+    fn test_package_files_respect_ignore_files() {
+        let (dir, _) = workspace();
+        let root = dir.path();
+        write(root, "apps/web/dist/index.js", "");
+        write(root, "apps/web/node_modules/x/index.js", "");
+        write(root, "apps/web/.env.example", "");
+        write(root, "apps/web/notes.md", "");
+        write(root, "apps/web/.airisignore", "*.md\n");
+
+        assert_eq!(
+            files(root, "apps/web", &[]),
+            vec![".airisignore", ".env.example", "package.json", "src/distance.ts"]
+        );
+        assert_eq!(files(root, "apps/web", &["src"]), vec!["src/distance.ts"]);
+        assert_eq!(files(root, "apps/web", &["src/**/*.ts", "package.json"]), vec!["package.json", "src/distance.ts"]);
+    }
+
+    #[test]
+    fn test_project_hash_covers_dependencies() {
+        let (dir, dag) = workspace();
+        let root = dir.path();
+        let hash = || project_hash(root, &dag, &[], "apps/web", &mut HashCache::load(root)).unwrap();
+
+        let before = hash();
+        write(root, "apps/web/dist/index.js", "built");
+        assert_eq!(hash(), before);
+
+        write(root, "libs/ui/src/index.ts", "export const x = 1");
+        let after_lib = hash();
+        assert_ne!(after_lib, before);
+
+        write(root, "apps/web/src/distance.ts", "export const d = 2");
+        assert_ne!(hash(), after_lib);
+    }
+
+    #[test]
+    fn test_project_hash_uses_app_inputs() {
+        let (dir, dag) = workspace();
+        let root = dir.path();
+        let apps: Vec<ProjectDefinition> =
+            toml::from_str::<crate::manifest::Manifest>("version = 1\n[[app]]\nname = \"web\"\ninputs = [\"src/**\"]\n")
+                .unwrap()
+                .app;
+        let hash = || project_hash(root, &dag, &apps, "apps/web", &mut HashCache::load(root)).unwrap();
+
+        let before = hash();
+        write(root, "apps/web/README.md", "docs");
+        assert_eq!(hash(), before);
+        write(root, "apps/web/src/distance.ts", "export const d = 2");
+        assert_ne!(hash(), before);
+    }
+}
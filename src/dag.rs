@@ -9,7 +9,6 @@ use std::collections::{HashMap, HashSet};
 #[derive(Debug, Clone)]
 pub struct DagNode {
     pub id: String,        // e.g., "apps/focustoday-api"
-    #[allow(dead_code)]
     pub name: String,      // e.g., "focustoday-api" or "@agiletec/focustoday-api"
     pub path: String,      // relative path from root
     pub deps: Vec<String>, // IDs of dependencies
@@ -34,11 +33,66 @@ impl Dag {
     }
 
     /// Get node by ID
-    #[allow(dead_code)]
     pub fn get(&self, id: &str) -> Option<&DagNode> {
         self.nodes.get(id)
     }
 
+    /// Find a node by package name (e.g., "@agiletec/ui")
+    pub fn find_by_name(&self, name: &str) -> Option<&DagNode> {
+        self.nodes.values().find(|n| n.name == name)
+    }
+
+    /// Find the package that owns a repo-relative file (deepest matching path)
+    pub fn owner_of(&self, file: &str) -> Option<&DagNode> {
+        self.nodes
+            .values()
+            .filter(|n| file.strip_prefix(n.path.as_str()).is_some_and(|rest| rest.starts_with('/')))
+            .max_by_key(|n| n.path.len())
+    }
+
+    /// All node IDs that depend on any of `ids`, directly or transitively
+    /// (the starting IDs are included)
+    pub fn with_dependents(&self, ids: &[String]) -> HashSet<String> {
+        let mut result: HashSet<String> = ids.iter().cloned().collect();
+        let mut queue: Vec<String> = ids.to_vec();
+
+        while let Some(id) = queue.pop() {
+            for node in self.nodes.values() {
+                if node.deps.contains(&id) && result.insert(node.id.clone()) {
+                    queue.push(node.id.clone());
+                }
+            }
+        }
+
+        result
+    }
+
+    /// All node IDs that any of `ids` depends on, directly or transitively
+    /// (the starting IDs are included)
+    pub fn with_dependencies(&self, ids: &[String]) -> HashSet<String> {
+        let mut result: HashSet<String> = ids.iter().cloned().collect();
+        let mut queue: Vec<String> = ids.to_vec();
+
+        while let Some(id) = queue.pop() {
+            if let Some(node) = self.nodes.get(&id) {
+                for dep in &node.deps {
+                    if self.nodes.contains_key(dep) && result.insert(dep.clone()) {
+                        queue.push(dep.clone());
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
+    /// All node IDs, sorted
+    pub fn ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.nodes.keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
     /// Get topological order starting from target
     /// Returns nodes in dependency-first order
     pub fn topo_order(&self, target: &str) -> Result<Vec<&DagNode>> {
@@ -140,4 +194,25 @@ mod tests {
         assert!(ids.iter().position(|&x| x == "c") < ids.iter().position(|&x| x == "b"));
         assert!(ids.iter().position(|&x| x == "b") < ids.iter().position(|&x| x == "a"));
     }
+
+    #[test]
+    fn test_owner_and_dependents() {
+        let mut dag = Dag::new();
+        for (id, deps) in [("apps/web", vec!["libs/ui"]), ("libs/ui", vec![]), ("libs/ui-kit", vec![])] {
+            dag.add_node(DagNode {
+                id: id.to_string(),
+                name: id.to_string(),
+                path: id.to_string(),
+                deps: deps.into_iter().map(String::from).collect(),
+            });
+        }
+
+        assert_eq!(dag.owner_of("libs/ui/src/a.ts").unwrap().id, "libs/ui");
+        assert_eq!(dag.owner_of("libs/ui-kit/a.ts").unwrap().id, "libs/ui-kit");
+        assert!(dag.owner_of("README.md").is_none());
+
+        let affected = dag.with_dependents(&["libs/ui".to_string()]);
+        assert!(affected.contains("apps/web") && affected.contains("libs/ui"));
+        assert!(!affected.contains("libs/ui-kit"));
+    }
 }
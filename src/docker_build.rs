@@ -10,7 +10,13 @@ use std::process::Command;
 
 use crate::channel::{resolve_channel, RuntimeChannel, RuntimeFamily, Toolchain};
 use crate::dag::Dag;
-use crate::pnpm::{build_workspace_map, PnpmLock};
+use crate::manifest::ProjectDefinition;
+use crate::pnpm::PnpmLock;
+use crate::sbom::{Sbom, SbomFormat};
+use crate::task_logs::Console;
+
+/// Worker slots a Docker build occupies in `airis build --docker` runs
+pub const DOCKER_BUILD_WEIGHT: usize = 2;
 
 /// Build configuration
 #[derive(Debug, Clone)]
@@ -23,6 +29,39 @@ pub struct BuildConfig {
     pub context_out: Option<PathBuf>,
     /// Runtime channel (lts, current, edge, bun, deno, or pinned version)
     pub channel: String,
+    /// Also write `docker buildx` output to this file (see `task_logs`)
+    pub log: Option<PathBuf>,
+    /// Image platforms (e.g., "linux/arm64"); empty: `[[app]] platforms`, else the host
+    pub platforms: Vec<String>,
+    pub builder: Builder,
+    /// Base image OCI layout for the native builder (default: pulled with skopeo)
+    pub base_layout: Option<PathBuf>,
+    /// Native builder: also write the image here (layout dir, or OCI archive if *.tar)
+    pub oci_out: Option<PathBuf>,
+    /// Also write these SBOMs to the build's cache entry
+    pub sbom: Vec<SbomFormat>,
+    /// Progress and build output (a run's display in parallel builds)
+    pub console: Console,
+}
+
+/// How `airis build --docker` produces the image
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Builder {
+    /// `docker buildx build` with the generated Dockerfile
+    #[default]
+    Docker,
+    /// OCI layers assembled without a daemon (see `native_build`)
+    Native,
+}
+
+impl Builder {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "docker" => Ok(Builder::Docker),
+            "native" => Ok(Builder::Native),
+            _ => bail!("Unknown builder '{}' (expected docker or native)", s),
+        }
+    }
 }
 
 impl Default for BuildConfig {
@@ -35,6 +74,13 @@ impl Default for BuildConfig {
             build_args: BTreeMap::new(),
             context_out: None,
             channel: "lts".to_string(),
+            log: None,
+            platforms: Vec::new(),
+            builder: Builder::Docker,
+            base_layout: None,
+            oci_out: None,
+            sbom: Vec::new(),
+            console: Console::default(),
         }
     }
 }
@@ -45,6 +91,15 @@ pub struct BuildResult {
     pub image_ref: String,
     pub hash: String,
     pub duration_secs: u64,
+    /// Image manifest digest per platform (builds with platforms only)
+    #[serde(default)]
+    pub platforms: BTreeMap<String, String>,
+    /// Digest of the multi-platform image index (manifest list)
+    #[serde(default)]
+    pub index_digest: Option<String>,
+    /// OCI layout the image was exported to
+    #[serde(default)]
+    pub oci_layout: Option<PathBuf>,
 }
 
 /// Cached artifact metadata
@@ -54,6 +109,30 @@ pub struct CachedArtifact {
     pub hash: String,
     pub built_at: String,
     pub target: String,
+    /// Image manifest digest per platform ("linux/arm64" -> "sha256:...")
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub platforms: BTreeMap<String, String>,
+    /// Digest of the multi-platform image index (manifest list)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index_digest: Option<String>,
+    /// Local OCI layout of the image (only valid on the machine that built it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oci_layout: Option<PathBuf>,
+}
+
+impl CachedArtifact {
+    /// Cache entry for a finished build, stored under `hash`
+    pub fn new(target: &str, hash: &str, result: &BuildResult) -> Self {
+        Self {
+            image_ref: result.image_ref.clone(),
+            hash: hash.to_string(),
+            built_at: chrono::Utc::now().to_rfc3339(),
+            target: target.to_string(),
+            platforms: result.platforms.clone(),
+            index_digest: result.index_digest.clone(),
+            oci_layout: result.oci_layout.clone(),
+        }
+    }
 }
 
 // =============================================================================
@@ -61,7 +140,7 @@ pub struct CachedArtifact {
 // =============================================================================
 
 /// Get cache directory path: ~/.airis/.cache/<project>/<hash>/
-fn cache_dir(project: &str, hash: &str) -> PathBuf {
+pub fn cache_dir(project: &str, hash: &str) -> PathBuf {
     let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
     let project_safe = project.replace('/', "_");
     PathBuf::from(home)
@@ -98,11 +177,62 @@ pub fn cache_store(project: &str, hash: &str, artifact: &CachedArtifact) -> Resu
     Ok(())
 }
 
+/// Platforms to build `target` for: `cli` (`--platform`) if given, else
+/// `[[app]] platforms`; sorted, empty for the host platform
+pub fn resolve_platforms(root: &Path, target: &str, cli: &[String]) -> Result<Vec<String>> {
+    let mut platforms = if cli.is_empty() {
+        let manifest_path = root.join("manifest.toml");
+        if manifest_path.exists() {
+            let manifest = crate::manifest::Manifest::load(&manifest_path)?;
+            manifest.find_app(target).map(|app| app.platforms.clone()).unwrap_or_default()
+        } else {
+            Vec::new()
+        }
+    } else {
+        cli.to_vec()
+    };
+
+    for platform in &platforms {
+        crate::oci::Platform::parse(platform)?;
+    }
+    platforms.sort();
+    platforms.dedup();
+    Ok(platforms)
+}
+
+/// Cache key of a build of `hash` for `platforms` (host builds keep `hash`)
+pub fn platform_hash(hash: &str, platforms: &[String]) -> String {
+    if platforms.is_empty() {
+        return hash.to_string();
+    }
+    let key = format!("{}-{}", hash, platforms.join(","));
+    blake3::hash(key.as_bytes()).to_hex()[..12].to_string()
+}
+
+/// Cache key of a single-project `airis build --docker` for `channel`
+pub fn channel_hash(hash: &str, channel: &str) -> String {
+    let key = format!("{}-{}", hash, channel);
+    blake3::hash(key.as_bytes()).to_hex()[..12].to_string()
+}
+
 /// Context builder - creates minimal Docker build context
+///
+/// Layout (like `turbo prune --docker`):
+///
+/// ```text
+/// pnpm-lock.yaml        # pruned to the target's importers and their packages
+/// pnpm-workspace.yaml   # lists exactly those packages
+/// json/                 # root + package manifests only: the install stage
+///                       # (package.json, bun.lock[b], Cargo.toml/.lock, requirements*.txt, pyproject.toml;
+///                       # with bun.lock[b], every workspace package's manifests)
+/// full/                 # sources of every package: the build stage
+/// ```
+///
+/// Install layers only depend on `json/` and the pruned lockfile, so they stay
+/// cached across source edits and unrelated dependency changes.
 pub struct ContextBuilder<'a> {
     root: &'a Path,
     dag: &'a Dag,
-    #[allow(dead_code)]
     lock: &'a PnpmLock,
     target: &'a str,
 }
@@ -138,33 +268,76 @@ impl<'a> ContextBuilder<'a> {
             }
         };
 
-        println!("📦 Building context for {} ({} packages)", self.target, dep_paths.len());
+        // 1. Pruned lockfile and workspace
+        self.write_pruned_workspace(&ctx_dir, &dep_paths)?;
 
-        // 1. Copy root files
-        self.copy_root_files(&ctx_dir)?;
+        // 2. Copy root files
+        let json_dir = ctx_dir.join("json");
+        let full_dir = ctx_dir.join("full");
+        fs::create_dir_all(&json_dir)?;
+        self.copy_root_files(&full_dir)?;
+        self.copy_root_manifests(&json_dir)?;
 
-        // 2. Copy each dependency in order
+        // 3. Copy each dependency in order
         for dep_path in &dep_paths {
-            self.copy_package(&ctx_dir, dep_path)?;
+            self.copy_package(&full_dir, dep_path)?;
+            copy_manifests(&self.root.join(dep_path), &json_dir.join(dep_path))?;
         }
 
-        // 3. Generate inputs manifest for hash verification
+        // bun.lock covers the whole workspace, so `bun install --frozen-lockfile`
+        // needs the manifests of the packages outside the target's closure too
+        if ["bun.lock", "bun.lockb"].iter().any(|f| self.root.join(f).exists()) {
+            for path in self.dag.nodes.keys().filter(|p| !dep_paths.contains(p)) {
+                if self.root.join(path).is_dir() {
+                    copy_manifests(&self.root.join(path), &json_dir.join(path))?;
+                }
+            }
+        }
+
+        // 4. Generate inputs manifest for hash verification
         self.write_inputs_manifest(&ctx_dir, &dep_paths)?;
 
         Ok(ctx_dir)
     }
 
+    fn write_pruned_workspace(&self, ctx: &Path, dep_paths: &[String]) -> Result<()> {
+        let mut importers = vec![".".to_string()];
+        importers.extend(dep_paths.iter().cloned());
+
+        let lock_content = fs::read_to_string(self.root.join("pnpm-lock.yaml"))
+            .with_context(|| "Failed to read pnpm-lock.yaml")?;
+        fs::write(ctx.join("pnpm-lock.yaml"), self.lock.prune(&lock_content, &importers)?)?;
+
+        let workspace = fs::read_to_string(self.root.join("pnpm-workspace.yaml")).ok();
+        fs::write(
+            ctx.join("pnpm-workspace.yaml"),
+            crate::pnpm::prune_workspace(workspace.as_deref(), dep_paths)?,
+        )?;
+
+        Ok(())
+    }
+
+    fn copy_root_manifests(&self, json: &Path) -> Result<()> {
+        // Bun lockfiles can't be pruned, so they are copied whole
+        for file in ["package.json", ".npmrc", "bun.lock", "bun.lockb"] {
+            copy_file_into(self.root, json, file)?;
+        }
+        Ok(())
+    }
+
     fn copy_root_files(&self, ctx: &Path) -> Result<()> {
         // Essential root files for pnpm workspace
+        // (the pruned lockfile and workspace sit at the context root)
         let root_files = [
             "package.json",
-            "pnpm-lock.yaml",
-            "pnpm-workspace.yaml",
             ".npmrc",
+            "bun.lock",
+            "bun.lockb",
             "tsconfig.base.json",
             "tsconfig.json",
         ];
 
+        fs::create_dir_all(ctx)?;
         for file in &root_files {
             let src = self.root.join(file);
             if src.exists() {
@@ -186,9 +359,10 @@ impl<'a> ContextBuilder<'a> {
         }
 
         fs::create_dir_all(&dst_dir)?;
+        copy_manifests(&src_dir, &dst_dir)?;
 
         // Files to copy for each package
-        let essential_files = ["package.json", "tsconfig.json", "tsconfig.build.json", ".npmrc"];
+        let essential_files = ["tsconfig.json", "tsconfig.build.json", ".npmrc", "build.rs"];
 
         for file in &essential_files {
             let src = src_dir.join(file);
@@ -210,6 +384,7 @@ impl<'a> ContextBuilder<'a> {
         }
 
         // Copy config files (next.config.*, tailwind.config.*, tsup.config.*, postcss.config.*)
+        // and top-level Python modules
         for entry in fs::read_dir(&src_dir)? {
             let entry = entry?;
             let name = entry.file_name();
@@ -218,6 +393,7 @@ impl<'a> ContextBuilder<'a> {
                 || name_str.starts_with("tailwind.config")
                 || name_str.starts_with("tsup.config")
                 || name_str.starts_with("postcss.config")
+                || (name_str.ends_with(".py") && entry.path().is_file())
             {
                 fs::copy(entry.path(), dst_dir.join(&name))?;
             }
@@ -244,8 +420,33 @@ impl<'a> ContextBuilder<'a> {
     }
 }
 
+/// Copy the install manifests of a package (whichever exist) from `dir` to `dst_dir`
+fn copy_manifests(dir: &Path, dst_dir: &Path) -> Result<()> {
+    for file in ["package.json", "Cargo.toml", "Cargo.lock", "pyproject.toml"] {
+        copy_file_into(dir, dst_dir, file)?;
+    }
+    for entry in fs::read_dir(dir)? {
+        let name = entry?.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with("requirements") && name.ends_with(".txt") {
+            copy_file_into(dir, dst_dir, &name)?;
+        }
+    }
+    Ok(())
+}
+
+/// Copy `dir/file` to `dst_dir/file` if it exists
+fn copy_file_into(dir: &Path, dst_dir: &Path, file: &str) -> Result<()> {
+    let src = dir.join(file);
+    if src.exists() {
+        fs::create_dir_all(dst_dir)?;
+        fs::copy(&src, dst_dir.join(file)).with_context(|| format!("Failed to copy {}", src.display()))?;
+    }
+    Ok(())
+}
+
 /// Detect if a Node.js project uses Next.js by checking package.json
-fn detect_nextjs(target: &str) -> bool {
+pub fn detect_nextjs(target: &str) -> bool {
     let pkg_json_path = Path::new(target).join("package.json");
     if !pkg_json_path.exists() {
         return false;
@@ -272,6 +473,103 @@ fn detect_nextjs(target: &str) -> bool {
     has_next_dep || has_next_dev_dep
 }
 
+/// Variables available to Dockerfile templates
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DockerfileVars {
+    /// Project path (e.g., "apps/web")
+    pub target: String,
+    /// Last path segment (e.g., "web")
+    pub name: String,
+    /// Runtime family: node, edge, bun, deno, rust or python
+    pub family: String,
+    /// Node project using Next.js
+    pub nextjs: bool,
+    /// Toolchain image (e.g., "node:22-alpine")
+    pub image: String,
+    /// Toolchain version (e.g., "22")
+    pub version: String,
+    /// Pinned image digest, if resolved (e.g., "sha256:...")
+    pub digest: Option<String>,
+    /// `image@digest` when pinned, else `image`
+    pub image_ref: String,
+    /// Workspace packages the target depends on, in build order
+    pub deps: Vec<String>,
+    /// `--build-arg`s passed to the build (declare them with `ARG`)
+    pub build_args: Vec<BuildArg>,
+    /// `[[app]] port` (default 3000)
+    pub port: u16,
+    /// The Dockerfile airis would generate, to extend rather than replace
+    pub builtin: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BuildArg {
+    pub name: String,
+    pub value: String,
+}
+
+/// Dockerfile for `target`: a project template if there is one, else the built-in one
+///
+/// Handlebars templates are looked up in order:
+/// 1. `[[app]] dockerfile_template = "<path>"` (relative to the workspace root)
+/// 2. `templates/docker/nextjs.hbs` (Next.js apps), then `templates/docker/<family>.hbs`
+///
+/// See [`DockerfileVars`] for the variables.
+pub fn render_dockerfile(
+    root: &Path,
+    app: Option<&ProjectDefinition>,
+    target: &str,
+    toolchain: &Toolchain,
+    build_args: &BTreeMap<String, String>,
+    deps: &[String],
+    console: &Console,
+) -> Result<String> {
+    let nextjs = toolchain.family == RuntimeFamily::Node && detect_nextjs(target);
+    let builtin = generate_dockerfile_for_toolchain(target, toolchain, build_args);
+
+    let template = match app.and_then(|a| a.dockerfile_template.as_deref()) {
+        Some(path) => Some(root.join(path)),
+        None => {
+            let dir = root.join("templates/docker");
+            let mut candidates = Vec::new();
+            if nextjs {
+                candidates.push(dir.join("nextjs.hbs"));
+            }
+            candidates.push(dir.join(format!("{}.hbs", toolchain.family.as_str())));
+            candidates.into_iter().find(|p| p.exists())
+        }
+    };
+    let Some(template) = template else {
+        return Ok(builtin);
+    };
+
+    let content = fs::read_to_string(&template)
+        .with_context(|| format!("Failed to read Dockerfile template {}", template.display()))?;
+    let vars = DockerfileVars {
+        target: target.to_string(),
+        name: target.rsplit('/').next().unwrap_or(target).to_string(),
+        family: toolchain.family.as_str().to_string(),
+        nextjs,
+        image: toolchain.image.clone(),
+        version: toolchain.version.clone(),
+        digest: toolchain.digest.clone(),
+        image_ref: match &toolchain.digest {
+            Some(digest) => format!("{}@{}", toolchain.image, digest),
+            None => toolchain.image.clone(),
+        },
+        deps: deps.to_vec(),
+        build_args: build_args
+            .iter()
+            .map(|(name, value)| BuildArg { name: name.clone(), value: value.clone() })
+            .collect(),
+        port: app.and_then(|a| a.port).unwrap_or(3000),
+        builtin,
+    };
+
+    console.say(format!("📝 Dockerfile template: {}", template.strip_prefix(root).unwrap_or(&template).display()));
+    crate::templates::TemplateEngine::new()?.render_string(&template.display().to_string(), &content, &vars)
+}
+
 /// Generate Dockerfile based on runtime family
 pub fn generate_dockerfile_for_toolchain(
     target: &str,
@@ -336,18 +634,15 @@ RUN corepack enable pnpm
 # ---- deps ----
 FROM base AS deps
 WORKDIR /app
-COPY pnpm-lock.yaml package.json pnpm-workspace.yaml .npmrc* ./
-COPY apps/ apps/
-COPY libs/ libs/
+COPY json/ ./
+COPY pnpm-lock.yaml pnpm-workspace.yaml ./
 RUN pnpm install --frozen-lockfile
 
 # ---- build ----
 FROM base AS build
 WORKDIR /app
-COPY --from=deps /app/node_modules ./node_modules
-COPY --from=deps /app/apps ./apps
-COPY --from=deps /app/libs ./libs
-COPY . .
+COPY --from=deps /app/ ./
+COPY full/ ./
 {extra_args}ARG NODE_ENV=production
 ENV NODE_ENV=${{NODE_ENV}}
 ENV NEXT_TELEMETRY_DISABLED=1
@@ -403,21 +698,20 @@ RUN corepack enable pnpm
 # ---- deps ----
 FROM base AS deps
 WORKDIR /app
-COPY pnpm-lock.yaml package.json pnpm-workspace.yaml ./
-COPY apps/ apps/
-COPY libs/ libs/
+COPY pnpm-lock.yaml pnpm-workspace.yaml ./
 RUN pnpm fetch
 
 # ---- build ----
 FROM base AS build
 WORKDIR /app
 COPY --from=deps /root/.local/share/pnpm/store /root/.local/share/pnpm/store
-COPY . .
+COPY json/ ./
+COPY pnpm-lock.yaml pnpm-workspace.yaml ./
+RUN pnpm install --offline --frozen-lockfile
+COPY full/ ./
 {extra_args}ARG NODE_ENV=production
 ENV NODE_ENV=${{NODE_ENV}}
-RUN corepack enable pnpm \
- && pnpm install --offline --frozen-lockfile \
- && pnpm -r --filter='./{target}...' build
+RUN pnpm -r --filter='./{target}...' build
 
 # ---- runtime ----
 FROM node:{node_version}-alpine AS runtime
@@ -463,22 +757,24 @@ fn generate_bun_dockerfile(
 
 FROM {image} AS deps
 WORKDIR /app
-COPY package.json bun.lockb ./
+# Manifests of the whole workspace, so bun.lock installs frozen
+COPY json/ ./
 RUN bun install --frozen-lockfile
 
 FROM {image} AS build
 WORKDIR /app
-COPY --from=deps /app/node_modules ./node_modules
-COPY . .
+COPY --from=deps /app/ ./
+COPY full/ ./
 {extra_args}ARG NODE_ENV=production
 ENV NODE_ENV=${{NODE_ENV}}
+WORKDIR /app/{target}
 RUN bun run build
 
 FROM {image} AS runtime
 WORKDIR /app
 ENV NODE_ENV=production
-COPY --from=build /app/dist ./dist
-COPY --from=build /app/package.json ./
+COPY --from=build /app/ ./
+WORKDIR /app/{target}
 EXPOSE 3000
 CMD ["bun", "run", "dist/index.js"]
 "#,
@@ -505,12 +801,13 @@ fn generate_deno_dockerfile(
 
 FROM {image} AS build
 WORKDIR /app
-COPY . .
+COPY full/ ./
+WORKDIR /app/{target}
 {extra_args}RUN deno cache src/main.ts
 RUN deno compile --allow-net --allow-env --output=app src/main.ts
 
 FROM gcr.io/distroless/cc AS runtime
-COPY --from=build /app/app /app
+COPY --from=build /app/{target}/app /app
 EXPOSE 3000
 CMD ["/app"]
 "#,
@@ -539,12 +836,13 @@ fn generate_edge_dockerfile(
 
 FROM {image} AS build
 WORKDIR /app
-COPY . .
+COPY full/ ./
+WORKDIR /app/{target}
 {extra_args}RUN deno task build
 
 FROM {image} AS runtime
 WORKDIR /app
-COPY --from=build /app/dist ./dist
+COPY --from=build /app/{target}/dist ./dist
 EXPOSE 8787
 CMD ["deno", "run", "--allow-net", "--allow-env", "dist/index.js"]
 "#,
@@ -572,14 +870,15 @@ fn generate_rust_dockerfile(
 
 FROM rust:1.83-slim AS deps
 WORKDIR /app
-COPY Cargo.toml Cargo.lock ./
+COPY json/{target}/ ./
 # Create dummy src to cache dependencies
 RUN mkdir src && echo "fn main() {{}}" > src/main.rs
 RUN cargo fetch
 
 FROM rust:1.83-slim AS build
 WORKDIR /app
-{extra_args}COPY . .
+COPY --from=deps /usr/local/cargo/registry /usr/local/cargo/registry
+{extra_args}COPY full/{target}/ ./
 RUN cargo build --release
 
 FROM gcr.io/distroless/cc AS runtime
@@ -609,13 +908,13 @@ fn generate_python_dockerfile(
 
 FROM python:3.12-slim AS deps
 WORKDIR /app
-COPY requirements*.txt ./
+COPY json/{target}/ ./
 RUN pip install --no-cache-dir -r requirements.txt
 
 FROM python:3.12-slim AS runtime
 WORKDIR /app
 {extra_args}COPY --from=deps /usr/local /usr/local
-COPY . .
+COPY full/{target}/ ./
 EXPOSE 8000
 CMD ["python", "-m", "uvicorn", "main:app", "--host", "0.0.0.0", "--port", "8000"]
 "#,
@@ -626,68 +925,28 @@ CMD ["python", "-m", "uvicorn", "main:app", "--host", "0.0.0.0", "--port", "8000
 
 /// Compute content hash directly from source files (fast path for cache lookup)
 /// This avoids building the full context directory when checking for cache hits
+///
+/// See `content_hash` for what goes in: ignore files, `[[app]] inputs` and
+/// the target's workspace dependencies.
 pub fn compute_content_hash(root: &Path, target: &str) -> Result<String> {
-    use std::io::Read;
-    use walkdir::WalkDir;
-
-    let mut hasher = blake3::Hasher::new();
-
-    // Hash essential root files
-    let root_files = [
-        "package.json",
-        "pnpm-lock.yaml",
-        "pnpm-workspace.yaml",
-        "tsconfig.base.json",
-        "tsconfig.json",
-    ];
-
-    for file in &root_files {
-        let path = root.join(file);
-        if path.exists() {
-            hasher.update(file.as_bytes());
-            let content = fs::read(&path)?;
-            hasher.update(&content);
-        }
-    }
+    let manifest_path = root.join("manifest.toml");
+    let apps = if manifest_path.exists() {
+        crate::manifest::Manifest::load(&manifest_path)?.app
+    } else {
+        Vec::new()
+    };
+    let dag = crate::workspace_graph::load(root)?;
 
-    // Hash target directory
-    let target_dir = root.join(target);
-    if target_dir.exists() {
-        let mut files: Vec<_> = WalkDir::new(&target_dir)
-            .into_iter()
-            .filter_map(|e| e.ok())
-            .filter(|e| e.file_type().is_file())
-            .filter(|e| {
-                let name = e.file_name().to_string_lossy();
-                // Skip build artifacts and dependencies
-                !name.contains("node_modules")
-                    && !name.contains(".next")
-                    && !name.contains("dist")
-                    && !name.contains(".turbo")
-            })
-            .map(|e| e.path().to_path_buf())
-            .collect();
-
-        files.sort();
-
-        for path in files {
-            let rel = path.strip_prefix(root).unwrap_or(&path);
-            hasher.update(rel.to_string_lossy().as_bytes());
-
-            let mut file = fs::File::open(&path)?;
-            let mut buf = Vec::new();
-            file.read_to_end(&mut buf)?;
-            hasher.update(&buf);
-        }
+    let mut cache = crate::hash_cache::HashCache::load(root);
+    let hash = crate::content_hash::project_hash(root, &dag, &apps, target, &mut cache)?;
+    if let Err(e) = cache.save() {
+        eprintln!("⚠️  Warning: Failed to save hash cache: {}", e);
     }
-
-    let hash = hasher.finalize();
-    Ok(hash.to_hex()[..12].to_string())
+    Ok(hash)
 }
 
 /// Compute BLAKE3 hash of inputs
 pub fn compute_hash(ctx_dir: &Path) -> Result<String> {
-    use std::io::Read;
     use walkdir::WalkDir;
 
     let mut hasher = blake3::Hasher::new();
@@ -702,22 +961,30 @@ pub fn compute_hash(ctx_dir: &Path) -> Result<String> {
 
     files.sort();
 
-    for path in files {
+    // Context dirs are fresh copies, so there's no stat cache to reuse
+    let digests = crate::hash_cache::digest_files(&files)?;
+    for (path, digest) in files.iter().zip(digests) {
         // Hash relative path
-        let rel = path.strip_prefix(ctx_dir).unwrap_or(&path);
+        let rel = path.strip_prefix(ctx_dir).unwrap_or(path);
         hasher.update(rel.to_string_lossy().as_bytes());
 
         // Hash content
-        let mut file = fs::File::open(&path)?;
-        let mut buf = Vec::new();
-        file.read_to_end(&mut buf)?;
-        hasher.update(&buf);
+        hasher.update(digest.as_bytes());
     }
 
     let hash = hasher.finalize();
     Ok(hash.to_hex()[..12].to_string())
 }
 
+/// Image name: `--image`, else `<app>:airis-<hash>`
+pub fn image_name(config: &BuildConfig, hash: &str) -> String {
+    let app_name = config.target.rsplit('/').next().unwrap_or(&config.target);
+    config
+        .image_name
+        .clone()
+        .unwrap_or_else(|| format!("{}:airis-{}", app_name, hash))
+}
+
 /// Run BuildKit build
 pub fn run_buildkit(
     ctx_dir: &Path,
@@ -733,15 +1000,10 @@ pub fn run_buildkit(
     let dockerfile_path = ctx_dir.join("Dockerfile");
     fs::write(&dockerfile_path, dockerfile_content)?;
 
-    // Determine image name
-    let app_name = config.target.rsplit('/').next().unwrap_or(&config.target);
-    let image_name = config
-        .image_name
-        .clone()
-        .unwrap_or_else(|| format!("{}:airis-{}", app_name, hash));
+    let image_name = image_name(config, hash);
 
-    println!("🐳 Building image: {}", image_name);
-    println!("   Context: {}", ctx_dir.display());
+    config.console.say(format!("🐳 Building image: {}", image_name));
+    config.console.say(format!("   Context: {}", ctx_dir.display()));
 
     // Build command
     let mut cmd = Command::new("docker");
@@ -762,16 +1024,40 @@ pub fn run_buildkit(
         cmd.arg("--no-cache");
     }
 
+    // Builds for explicit platforms also export an OCI layout, which records
+    // the per-platform digests and is what `airis bundle` ships. Several
+    // platforms can't be loaded into the local image store: they're pushed as
+    // a manifest list, or only kept in the layout.
+    let oci_layout = (!config.platforms.is_empty()).then(|| cache_dir(&config.target, hash).join("oci"));
+    if let Some(layout) = &oci_layout {
+        if layout.exists() {
+            fs::remove_dir_all(layout)?;
+        }
+        cmd.arg("--platform").arg(config.platforms.join(","));
+        cmd.arg("--output")
+            .arg(format!("type=oci,dest={},tar=false,name={}", layout.display(), image_name));
+        config.console.say(format!("   Platforms: {}", config.platforms.join(", ")));
+    }
+
     if config.push {
         cmd.arg("--push");
-    } else {
+    } else if config.platforms.len() <= 1 {
         cmd.arg("--load");
     }
 
     cmd.arg(ctx_dir);
 
     // Execute
-    let status = cmd.status().context("Failed to run docker buildx")?;
+    let log = match &config.log {
+        Some(path) => {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            Some(fs::File::create(path).with_context(|| format!("Failed to create {}", path.display()))?)
+        }
+        None => None,
+    };
+    let status = crate::task_logs::tee_command(&mut cmd, log, &config.console).context("Failed to run docker buildx")?;
 
     let duration = start.elapsed().as_secs();
 
@@ -779,104 +1065,155 @@ pub fn run_buildkit(
         bail!("Docker build failed with exit code: {:?}", status.code());
     }
 
+    let (index_digest, platforms) = match &oci_layout {
+        Some(layout) => crate::oci::platform_digests(layout)?,
+        None => (None, BTreeMap::new()),
+    };
+
     Ok(BuildResult {
         image_ref: image_name,
         hash: hash.to_string(),
         duration_secs: duration,
+        platforms,
+        index_digest,
+        oci_layout,
     })
 }
 
 /// Main entry point for `airis build --docker`
 pub fn docker_build(root: &Path, config: BuildConfig) -> Result<BuildResult> {
-    use colored::Colorize;
-
-    // 1. Resolve runtime channel to toolchain
-    let channel = RuntimeChannel::parse(&config.channel)?;
-    let toolchain = resolve_channel(&channel)?;
-
-    println!("{}", "==================================".bright_blue());
-    println!("{}", "airis build --docker".bright_blue().bold());
-    println!("Target:  {}", config.target.cyan());
-    println!("Channel: {} → {} ({})", config.channel.yellow(), toolchain.image.green(), format!("{:?}", toolchain.family).dimmed());
-    println!("{}", "==================================".bright_blue());
-
-    // 2. Load pnpm-lock.yaml
-    let lock_path = root.join("pnpm-lock.yaml");
-    let lock = PnpmLock::load(&lock_path)?;
-
-    // 3. Build workspace map and DAG
-    let workspace_map = build_workspace_map(&lock);
-    let dag = crate::dag::build_dag(&workspace_map);
+    let (lock, dag) = load_workspace(root, &config.target)?;
+    docker_build_in(root, config, &lock, &dag)
+}
 
-    // 4. Verify target exists
-    if !dag.nodes.contains_key(&config.target) {
+/// Load pnpm-lock.yaml and the workspace DAG, checking that `target` is in it
+pub fn load_workspace(root: &Path, target: &str) -> Result<(PnpmLock, Dag)> {
+    let lock = PnpmLock::load(&root.join("pnpm-lock.yaml"))?;
+    let dag = crate::workspace_graph::from_lockfile(root, &lock);
+    if !dag.nodes.contains_key(target) {
         bail!(
             "Target '{}' not found in workspace. Available:\n{}",
-            config.target,
+            target,
             dag.nodes.keys().map(|k| format!("  - {}", k)).collect::<Vec<_>>().join("\n")
         );
     }
+    Ok((lock, dag))
+}
+
+/// `docker_build` for a workspace already loaded with `load_workspace`
+pub fn docker_build_in(root: &Path, mut config: BuildConfig, lock: &PnpmLock, dag: &Dag) -> Result<BuildResult> {
+    use colored::Colorize;
+    let console = config.console.clone();
+
+    // 1. Resolve runtime channel to toolchain, and the image platforms
+    let channel = RuntimeChannel::parse(&config.channel)?;
+    let toolchain = resolve_channel(&channel)?;
+    config.platforms = resolve_platforms(root, &config.target, &config.platforms)?;
+    if config.oci_out.is_some() && config.builder != Builder::Native {
+        bail!("--oci-out requires --builder native");
+    }
+
+    console.say("==================================".bright_blue());
+    console.say("airis build --docker".bright_blue().bold());
+    console.say(format!("Target:  {}", config.target.cyan()));
+    console.say(format!("Channel: {} → {} ({})", config.channel.yellow(), toolchain.image.green(), format!("{:?}", toolchain.family).dimmed()));
+    if !config.platforms.is_empty() {
+        console.say(format!("Platforms: {}", config.platforms.join(", ").yellow()));
+    }
+    console.say("==================================".bright_blue());
 
-    // 5. Build context
-    let ctx_builder = ContextBuilder::new(root, &dag, &lock, &config.target);
+    // 2. Build context (the workspace was loaded and the target checked by `load_workspace`)
+    let dep_paths = dag.get_dep_paths(&config.target)?;
+    console.say(format!("📦 Building context for {} ({} packages)", config.target, dep_paths.len()));
+    let ctx_builder = ContextBuilder::new(root, dag, lock, &config.target);
     let ctx_dir = ctx_builder.build(config.context_out.as_deref())?;
 
-    // 6. Compute hash (includes toolchain info for cache invalidation)
+    // 3. Compute hash (includes toolchain info for cache invalidation)
     let hash = compute_hash(&ctx_dir)?;
-    // Append channel to hash for cache invalidation on channel change
-    let full_hash = format!("{}-{}", hash, config.channel);
-    let final_hash = blake3::hash(full_hash.as_bytes()).to_hex()[..12].to_string();
-    println!("📋 Input hash: {}", final_hash.yellow());
+    // Key by channel too, for cache invalidation on channel change
+    let final_hash = platform_hash(&channel_hash(&hash, &config.channel), &config.platforms);
+    console.say(format!("📋 Input hash: {}", final_hash.yellow()));
+
+    // SBOMs depend only on the lockfile and toolchain, so cache hits get them too
+    if !config.sbom.is_empty() {
+        let sbom = Sbom::collect(root, lock, dag, &config.target, &toolchain)?;
+        for path in sbom.write(&cache_dir(&config.target, &final_hash), &config.sbom)? {
+            console.say(format!("📄 SBOM: {}", path.display()));
+        }
+    }
 
-    // 7. Check cache (skip if --no-cache)
+    // 4. Check cache (skip if --no-cache)
     if !config.no_cache
         && let Some(cached) = cache_hit(&config.target, &final_hash) {
-            println!();
-            println!("{}", "==================================".bright_blue());
-            println!("{}", "⚡ Cache hit! Skipping build.".green().bold());
-            println!("   Image: {}", cached.image_ref);
-            println!("   Hash:  {}", cached.hash);
-            println!("   Built: {}", cached.built_at);
-            println!("{}", "==================================".bright_blue());
+            console.say("");
+            console.say("==================================".bright_blue());
+            console.say("⚡ Cache hit! Skipping build.".green().bold());
+            console.say(format!("   Image: {}", cached.image_ref));
+            console.say(format!("   Hash:  {}", cached.hash));
+            console.say(format!("   Built: {}", cached.built_at));
+            console.say("==================================".bright_blue());
 
             return Ok(BuildResult {
                 image_ref: cached.image_ref,
                 hash: cached.hash,
                 duration_secs: 0,
+                platforms: cached.platforms,
+                index_digest: cached.index_digest,
+                oci_layout: cached.oci_layout,
             });
         }
 
-    // 8. Generate Dockerfile based on runtime family
-    let dockerfile = generate_dockerfile_for_toolchain(&config.target, &toolchain, &config.build_args);
+    let manifest_path = root.join("manifest.toml");
+    let manifest = if manifest_path.exists() {
+        Some(crate::manifest::Manifest::load(&manifest_path)?)
+    } else {
+        None
+    };
+    let app = manifest.as_ref().and_then(|m| m.find_app(&config.target));
 
-    // 9. Run BuildKit
-    let result = run_buildkit(&ctx_dir, &dockerfile, &config, &final_hash)?;
+    let result = match config.builder {
+        Builder::Docker => {
+            // 5. Generate Dockerfile (project template or built-in, by runtime family)
+            let deps: Vec<String> = dep_paths.into_iter().filter(|p| *p != config.target).collect();
+            let dockerfile =
+                render_dockerfile(root, app, &config.target, &toolchain, &config.build_args, &deps, &console)?;
 
-    // 10. Store in cache
-    let artifact = CachedArtifact {
-        image_ref: result.image_ref.clone(),
-        hash: result.hash.clone(),
-        built_at: chrono::Utc::now().to_rfc3339(),
-        target: config.target.clone(),
+            // 6. Run BuildKit
+            run_buildkit(&ctx_dir, &dockerfile, &config, &final_hash)?
+        }
+        // 5-6. Install/build on the host and assemble the image
+        Builder::Native => {
+            let port = app.and_then(|a| a.port).unwrap_or(3000);
+            crate::native_build::build(&ctx_dir, &config, &toolchain, &final_hash, port)?
+        }
     };
+
+    // 7. Store in cache
+    let artifact = CachedArtifact::new(&config.target, &result.hash, &result);
     if let Err(e) = cache_store(&config.target, &final_hash, &artifact) {
-        eprintln!("⚠️  Warning: Failed to store cache: {}", e);
+        console.warn(format!("⚠️  Warning: Failed to store cache: {}", e));
     }
 
-    // 10. Print summary
-    println!();
-    println!("{}", "==================================".bright_blue());
-    println!("{}", "✅ Build successful!".green());
-    println!("   Image: {}", result.image_ref);
-    println!("   Hash:  {}", result.hash);
-    println!("   Time:  {}s", result.duration_secs);
-    println!("{}", "==================================".bright_blue());
+    // 8. Print summary
+    console.say("");
+    console.say("==================================".bright_blue());
+    console.say("✅ Build successful!".green());
+    console.say(format!("   Image: {}", result.image_ref));
+    console.say(format!("   Hash:  {}", result.hash));
+    console.say(format!("   Time:  {}s", result.duration_secs));
+    if let Some(digest) = &result.index_digest {
+        console.say(format!("   Index: {}", digest));
+    }
+    for (platform, digest) in &result.platforms {
+        console.say(format!("   {}: {}", platform, digest));
+    }
+    console.say("==================================".bright_blue());
 
     Ok(result)
 }
 
 /// Recursively copy directory
-fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
+pub fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
     fs::create_dir_all(dst)?;
 
     for entry in fs::read_dir(src)? {
@@ -924,6 +1261,28 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_resolve_platforms() {
+        let temp = tempdir().unwrap();
+        let root = temp.path();
+        fs::write(
+            root.join("manifest.toml"),
+            "version = 1\n[[app]]\nname = \"web\"\nplatforms = [\"linux/arm64\", \"linux/amd64\"]\n",
+        )
+        .unwrap();
+
+        let platforms = resolve_platforms(root, "apps/web", &[]).unwrap();
+        assert_eq!(platforms, vec!["linux/amd64", "linux/arm64"]);
+        // --platform wins over the manifest
+        assert_eq!(resolve_platforms(root, "apps/web", &["linux/arm64".to_string()]).unwrap(), vec!["linux/arm64"]);
+        assert!(resolve_platforms(root, "apps/api", &[]).unwrap().is_empty());
+        assert!(resolve_platforms(root, "apps/web", &["arm64".to_string()]).is_err());
+
+        // Host builds keep their cache key; each platform set gets its own
+        assert_eq!(platform_hash("abc123", &[]), "abc123");
+        assert_ne!(platform_hash("abc123", &platforms), platform_hash("abc123", &platforms[..1]));
+    }
+
     #[test]
     fn test_cache_store_and_hit() {
         let project = "test_project_cache";
@@ -934,6 +1293,9 @@ mod tests {
             hash: hash.to_string(),
             built_at: "2025-01-01T00:00:00Z".to_string(),
             target: project.to_string(),
+            platforms: BTreeMap::from([("linux/arm64".to_string(), "sha256:abc".to_string())]),
+            index_digest: Some("sha256:def".to_string()),
+            oci_layout: None,
         };
 
         // Store
@@ -946,6 +1308,8 @@ mod tests {
         let cached = cached.unwrap();
         assert_eq!(cached.image_ref, "test:latest");
         assert_eq!(cached.hash, hash);
+        assert_eq!(cached.platforms["linux/arm64"], "sha256:abc");
+        assert_eq!(cached.index_digest.as_deref(), Some("sha256:def"));
 
         // Cleanup
         let dir = cache_dir(project, hash);
@@ -979,6 +1343,157 @@ mod tests {
         assert_ne!(hash1, hash2);
     }
 
+    #[test]
+    fn test_context_builder_prunes_workspace() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let write = |file: &str, content: &str| {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+        write("package.json", r#"{"name": "root"}"#);
+        write("pnpm-workspace.yaml", "packages:\n  - apps/*\n  - libs/*\n");
+        write("apps/web/package.json", r#"{"name": "web"}"#);
+        write("apps/web/src/index.ts", "");
+        write("libs/ui/package.json", r#"{"name": "ui"}"#);
+        write("libs/ui/src/index.ts", "");
+        write("libs/other/package.json", r#"{"name": "other"}"#);
+        write(
+            "pnpm-lock.yaml",
+            r#"lockfileVersion: '9.0'
+importers:
+  .: {}
+  apps/web:
+    dependencies:
+      ui:
+        specifier: workspace:*
+        version: link:../../libs/ui
+  libs/ui:
+    dependencies:
+      clsx:
+        specifier: ^2.0.0
+        version: 2.1.0
+  libs/other:
+    dependencies:
+      lodash:
+        specifier: ^4.0.0
+        version: 4.17.21
+packages:
+  clsx@2.1.0: {}
+  lodash@4.17.21: {}
+snapshots:
+  clsx@2.1.0: {}
+  lodash@4.17.21: {}
+"#,
+        );
+
+        let lock = PnpmLock::load(&root.join("pnpm-lock.yaml")).unwrap();
+        let dag = crate::workspace_graph::from_lockfile(root, &lock);
+        let ctx = root.join("ctx");
+        ContextBuilder::new(root, &dag, &lock, "apps/web").build(Some(&ctx)).unwrap();
+
+        let pruned = fs::read_to_string(ctx.join("pnpm-lock.yaml")).unwrap();
+        assert!(pruned.contains("clsx@2.1.0"));
+        assert!(!pruned.contains("lodash") && !pruned.contains("libs/other"));
+        let workspace = fs::read_to_string(ctx.join("pnpm-workspace.yaml")).unwrap();
+        assert!(workspace.contains("libs/ui") && !workspace.contains("libs/*"));
+
+        assert!(ctx.join("json/package.json").exists());
+        assert!(ctx.join("json/apps/web/package.json").exists());
+        assert!(!ctx.join("json/apps/web/src").exists());
+        assert!(ctx.join("full/apps/web/src/index.ts").exists());
+        assert!(ctx.join("full/libs/ui/src/index.ts").exists());
+        assert!(!ctx.join("full/libs/other").exists());
+    }
+
+    #[test]
+    fn test_builtin_dockerfiles_copy_from_context() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let write = |file: &str, content: &str| {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+        write("package.json", r#"{"name": "root"}"#);
+        write("bun.lock", "{}");
+        write("pnpm-workspace.yaml", "packages:\n  - apps/*\n");
+        write("pnpm-lock.yaml", "lockfileVersion: '9.0'\nimporters:\n  .: {}\n  apps/api: {}\n  apps/other: {}\n");
+        write("apps/other/package.json", "{}");
+        write("apps/other/src/index.ts", "");
+        for file in ["package.json", "Cargo.toml", "Cargo.lock", "requirements.txt", "main.py", "src/main.ts"] {
+            write(&format!("apps/api/{}", file), "{}");
+        }
+
+        let lock = PnpmLock::load(&root.join("pnpm-lock.yaml")).unwrap();
+        let dag = crate::workspace_graph::from_lockfile(root, &lock);
+        let ctx = root.join("ctx");
+        ContextBuilder::new(root, &dag, &lock, "apps/api").build(Some(&ctx)).unwrap();
+
+        let args = BTreeMap::new();
+        for dockerfile in [
+            generate_node_dockerfile("apps/api", "22", &args),
+            generate_nextjs_dockerfile("apps/api", "22", &args),
+            generate_bun_dockerfile("apps/api", "oven/bun:1", &args),
+            generate_deno_dockerfile("apps/api", "denoland/deno", &args),
+            generate_edge_dockerfile("apps/api", "denoland/deno", &args),
+            generate_rust_dockerfile("apps/api", &args),
+            generate_python_dockerfile("apps/api", &args),
+        ] {
+            for line in dockerfile.lines().filter(|l| l.starts_with("COPY ") && !l.contains("--from=")) {
+                let words: Vec<&str> = line.split_whitespace().collect();
+                for src in &words[1..words.len() - 1] {
+                    assert!(ctx.join(src).exists(), "{} is not in the context:\n{}", src, dockerfile);
+                }
+            }
+        }
+        assert!(ctx.join("json/bun.lock").exists() && ctx.join("full/bun.lock").exists());
+        // bun.lock lists every workspace package, built or not
+        assert!(ctx.join("json/apps/other/package.json").exists());
+        assert!(!ctx.join("full/apps/other").exists());
+        assert!(ctx.join("json/apps/api/requirements.txt").exists());
+        assert!(ctx.join("full/apps/api/main.py").exists());
+    }
+
+    #[test]
+    fn test_render_dockerfile_templates() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let toolchain = Toolchain {
+            image: "node:22-alpine".to_string(),
+            digest: Some("sha256:abc".to_string()),
+            family: RuntimeFamily::Node,
+            version: "22".to_string(),
+        };
+        let build_args = BTreeMap::from([("API_URL".to_string(), "https://api".to_string())]);
+        let deps = vec!["libs/ui".to_string()];
+        let render = |app: Option<&ProjectDefinition>| {
+            render_dockerfile(root, app, "apps/web", &toolchain, &build_args, &deps, &Console::default()).unwrap()
+        };
+
+        // No template: built-in
+        assert_eq!(render(None), generate_dockerfile_for_toolchain("apps/web", &toolchain, &build_args));
+
+        fs::create_dir_all(root.join("templates/docker")).unwrap();
+        fs::write(
+            root.join("templates/docker/node.hbs"),
+            "FROM {{image_ref}}\n{{#each build_args}}ARG {{name}}\n{{/each}}# {{name}} {{port}}{{#each deps}} {{this}}{{/each}}\n",
+        )
+        .unwrap();
+        assert_eq!(render(None), "FROM node:22-alpine@sha256:abc\nARG API_URL\n# web 3000 libs/ui\n");
+
+        // [[app]] dockerfile_template wins
+        fs::write(root.join("web.hbs"), "{{builtin}}HEALTHCHECK CMD wget -q localhost:{{port}}\n").unwrap();
+        let manifest: crate::manifest::Manifest = toml::from_str(
+            "version = 1\n[[app]]\nname = \"web\"\nport = 8080\ndockerfile_template = \"web.hbs\"\n",
+        )
+        .unwrap();
+        let rendered = render(manifest.find_app("apps/web"));
+        assert!(rendered.starts_with("# syntax=docker/dockerfile:1.7"));
+        assert!(rendered.ends_with("HEALTHCHECK CMD wget -q localhost:8080\n"));
+    }
+
     #[test]
     fn test_generate_nextjs_dockerfile() {
         let dockerfile = generate_nextjs_dockerfile(
@@ -1002,7 +1517,7 @@ mod tests {
         );
 
         assert!(dockerfile.contains("FROM oven/bun:1.1-alpine"));
-        assert!(dockerfile.contains("bun install"));
+        assert!(dockerfile.contains("bun install --frozen-lockfile"));
         assert!(dockerfile.contains("bun run"));
     }
 
@@ -2,11 +2,26 @@
 //!
 //! Executes build tasks in parallel respecting dependency order.
 //! Uses tokio for async execution with configurable worker pool.
+//!
+//! Ready tasks are started longest-remaining-path first: each task's priority
+//! is its estimated duration (from `task_history`) plus the longest chain of
+//! dependents behind it, so long chains don't start late. Tasks occupy
+//! `BuildTask::weight` worker slots while running.
+//!
+//! Where a task's command actually runs is pluggable via [`ExecBackend`]:
+//! in-process (`commands::run::LocalBackend`) or on `airis worker`
+//! processes (`worker::WorkerBackend`).
 
 use anyhow::Result;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::sync::{mpsc, Mutex, Semaphore};
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex, Notify, Semaphore};
+use tokio::task::JoinSet;
 
 /// Task state in the execution graph
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -21,6 +36,8 @@ pub enum TaskState {
     Completed,
     /// Failed with error
     Failed(String),
+    /// Not run (failed dependency, earlier failure or cancellation)
+    Skipped(String),
 }
 
 /// A build task in the DAG
@@ -30,6 +47,10 @@ pub struct BuildTask {
     pub target: String,
     pub channel: String,
     pub dependencies: Vec<String>,
+    /// Extra attempts after a failure
+    pub retries: u32,
+    /// Worker slots the task occupies while running (e.g., 2 for Docker builds)
+    pub weight: usize,
 }
 
 /// Task execution result
@@ -39,6 +60,98 @@ pub struct TaskResult {
     pub success: bool,
     pub duration_ms: u64,
     pub error: Option<String>,
+    /// Result came from a cache (no work done)
+    pub cached: bool,
+    /// Never ran (see `TaskState::Skipped`)
+    pub skipped: bool,
+}
+
+/// Output stream of a task log chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// Receives task output as it is produced (None: echo to the terminal)
+pub type LogSink = Option<mpsc::UnboundedSender<(Stream, Vec<u8>)>>;
+
+/// Progress of an `execute`, for `task_ui`
+#[derive(Debug, Clone)]
+pub enum TaskEvent {
+    /// Progress line (what is printed without an event sink)
+    Message(String),
+    /// A task moved to a new state
+    State { task_id: String, state: TaskState },
+    /// Output produced by a running task
+    Output { task_id: String, stream: Stream, data: Vec<u8> },
+    /// A task finished, failed or was skipped
+    Finished(TaskResult),
+}
+
+/// Receives executor progress instead of the terminal
+pub type EventSink = mpsc::UnboundedSender<TaskEvent>;
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// A shell command to run for one task
+#[derive(Debug, Clone)]
+pub struct Job {
+    pub id: String,
+    /// Package directory the command runs in, relative to the workspace root
+    pub package: String,
+    pub command: String,
+    /// Output globs (relative to the package) that must end up in the local tree
+    pub outputs: Vec<String>,
+    /// Capture stdout/stderr (for the task cache) instead of inheriting the terminal
+    pub capture: bool,
+}
+
+/// Result of running a [`Job`]
+#[derive(Debug, Clone, Default)]
+pub struct JobOutput {
+    pub success: bool,
+    pub exit_code: Option<i32>,
+    /// Set when the command could not be run at all
+    pub error: Option<String>,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+}
+
+/// Where task commands run
+pub trait ExecBackend: Send + Sync {
+    /// Short description for progress output (e.g., "local", "2 workers")
+    fn describe(&self) -> String;
+
+    /// How many jobs the backend can run at once
+    fn slots(&self) -> usize;
+
+    /// Run a job to completion
+    fn run(&self, job: Job, logs: LogSink) -> BoxFuture<'_, Result<JobOutput>>;
+}
+
+/// When and where a task ran (for `--profile`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskSpan {
+    pub task_id: String,
+    /// Worker slot the task ran in (0-based)
+    pub lane: usize,
+    /// Microseconds since the executor started
+    pub start_us: u64,
+    pub end_us: u64,
+}
+
+/// What to do with the rest of the graph when a task fails
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FailurePolicy {
+    /// Start no new tasks, let running ones finish (default)
+    #[default]
+    Stop,
+    /// Keep running everything that doesn't depend on the failed task (`--continue`)
+    Continue,
+    /// Cancel running tasks immediately (`--bail`)
+    Bail,
 }
 
 /// Parallel executor for DAG-based builds
@@ -51,6 +164,17 @@ pub struct ParallelExecutor {
     states: Arc<Mutex<HashMap<String, TaskState>>>,
     /// Reverse dependency map (task -> tasks that depend on it)
     dependents: HashMap<String, Vec<String>>,
+    policy: FailurePolicy,
+    /// Delay before the first retry, doubled for each further attempt
+    retry_backoff: Duration,
+    /// Cancels the run like Ctrl-C does
+    cancel: Arc<Notify>,
+    /// Spans of tasks that ran in the last `execute`
+    timeline: std::sync::Mutex<Vec<TaskSpan>>,
+    /// Expected duration per task ID in ms (from earlier runs)
+    estimates: HashMap<String, u64>,
+    /// Where progress goes (None: printed)
+    events: Option<EventSink>,
 }
 
 impl ParallelExecutor {
@@ -61,9 +185,57 @@ impl ParallelExecutor {
             tasks: HashMap::new(),
             states: Arc::new(Mutex::new(HashMap::new())),
             dependents: HashMap::new(),
+            policy: FailurePolicy::default(),
+            retry_backoff: Duration::from_secs(1),
+            cancel: Arc::new(Notify::new()),
+            timeline: std::sync::Mutex::new(Vec::new()),
+            estimates: HashMap::new(),
+            events: None,
         }
     }
 
+    /// Set the failure policy
+    pub fn failure_policy(mut self, policy: FailurePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Set the base delay between retries
+    #[cfg(test)]
+    pub fn retry_backoff(mut self, backoff: Duration) -> Self {
+        self.retry_backoff = backoff;
+        self
+    }
+
+    /// Set expected task durations in ms, used to prioritize long chains
+    pub fn estimates(mut self, estimates: HashMap<String, u64>) -> Self {
+        self.estimates = estimates;
+        self
+    }
+
+    /// Send progress to `events` instead of printing it
+    pub fn events(mut self, events: EventSink) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Handle that cancels a running `execute` (in addition to Ctrl-C)
+    pub fn canceller(&self) -> Arc<Notify> {
+        Arc::clone(&self.cancel)
+    }
+
+    /// Tasks in the graph
+    pub fn tasks(&self) -> impl Iterator<Item = &BuildTask> {
+        self.tasks.values()
+    }
+
+    /// Start/end and worker lane of each task that ran, sorted by start
+    pub fn timeline(&self) -> Vec<TaskSpan> {
+        let mut spans = self.timeline.lock().expect("timeline lock poisoned").clone();
+        spans.sort_by(|a, b| (a.start_us, &a.task_id).cmp(&(b.start_us, &b.task_id)));
+        spans
+    }
+
     /// Add a task to the executor
     pub fn add_task(&mut self, task: BuildTask) {
         let task_id = task.id.clone();
@@ -80,10 +252,15 @@ impl ParallelExecutor {
     }
 
     /// Execute all tasks in parallel respecting dependencies
+    ///
+    /// Failed tasks are retried up to `BuildTask::retries` times with exponential
+    /// backoff. Dependents of a failed task are skipped; what happens to the rest
+    /// depends on the [`FailurePolicy`]. Ctrl-C aborts running tasks (dropping
+    /// their child processes) and skips everything not yet started.
     pub async fn execute<F, Fut>(&self, task_fn: F) -> Result<Vec<TaskResult>>
     where
         F: Fn(BuildTask) -> Fut + Send + Sync + Clone + 'static,
-        Fut: std::future::Future<Output = Result<TaskResult>> + Send,
+        Fut: Future<Output = Result<TaskResult>> + Send + 'static,
     {
         use colored::Colorize;
 
@@ -100,217 +277,561 @@ impl ParallelExecutor {
                 } else {
                     TaskState::Pending
                 };
+                emit(&self.events, TaskEvent::State { task_id: id.clone(), state: state.clone() });
                 states.insert(id.clone(), state);
             }
         }
 
         let semaphore = Arc::new(Semaphore::new(self.max_parallel));
-        let (tx, mut rx) = mpsc::channel::<TaskResult>(self.tasks.len());
-        let states = Arc::clone(&self.states);
-        let tasks = self.tasks.clone();
-        let dependents = self.dependents.clone();
+        let gate = Arc::new(Gate::new(self.policy, self.max_parallel, self.events.clone()));
+        let mut running: JoinSet<TaskResult> = JoinSet::new();
+        let mut states = self.states.lock().await;
+        let priorities = self.priorities();
+        let mut free_slots = self.max_parallel;
 
         let mut results = Vec::new();
-        let mut completed_count = 0;
         let total_tasks = self.tasks.len();
 
-        println!(
-            "{}",
+        say(
+            &self.events,
             format!(
                 "🚀 Starting parallel build ({} tasks, {} workers)",
                 total_tasks, self.max_parallel
             )
-            .cyan()
+            .cyan(),
         );
 
         // Spawn initial ready tasks
-        let ready_tasks: Vec<_> = {
-            let states = states.lock().await;
-            states
-                .iter()
-                .filter(|(_, s)| **s == TaskState::Ready)
-                .map(|(id, _)| id.clone())
-                .collect()
+        let mut ready: Vec<String> = states
+            .iter()
+            .filter(|(_, s)| **s == TaskState::Ready)
+            .map(|(id, _)| id.clone())
+            .collect();
+        self.dispatch(&mut ready, &priorities, &mut free_slots, &mut states, |task, weight| {
+            spawn_task(&mut running, task, weight, &semaphore, &gate, &task_fn, self.retry_backoff);
+        });
+
+        // Without signal support, only the canceller can interrupt
+        let ctrl_c = async {
+            if tokio::signal::ctrl_c().await.is_err() {
+                std::future::pending::<()>().await;
+            }
         };
+        tokio::pin!(ctrl_c);
+        let mut stopping = false;
+        let mut aborted = false;
+        let mut interrupted = false;
 
-        for task_id in ready_tasks {
-            let task = match tasks.get(&task_id) {
-                Some(t) => t.clone(),
-                None => {
-                    // This should never happen as we just collected ready task IDs
-                    eprintln!("Internal error: task not found: {}", task_id);
-                    continue;
-                }
+        // Process completions and spawn newly ready tasks
+        while !running.is_empty() {
+            let joined = tokio::select! {
+                joined = running.join_next() => joined,
+                _ = &mut ctrl_c => None,
+                _ = self.cancel.notified() => None,
+            };
+            let Some(joined) = joined else {
+                say(&self.events, "\n🛑 Interrupted, cancelling running tasks...".yellow().bold());
+                interrupted = true;
+                aborted = true;
+                running.abort_all();
+                break;
             };
-            let semaphore = Arc::clone(&semaphore);
-            let tx = tx.clone();
-            let states = Arc::clone(&states);
-            let task_fn = task_fn.clone();
-
-            tokio::spawn(async move {
-                let _permit = semaphore.acquire().await.expect("semaphore closed unexpectedly");
-
-                // Mark as running
-                {
-                    let mut states = states.lock().await;
-                    states.insert(task.id.clone(), TaskState::Running);
+            let result = match joined {
+                Ok(result) => result,
+                Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+                Err(e) => return Err(e.into()),
+            };
+
+            let task_id = result.task_id.clone();
+            let failed = !result.success && !result.skipped;
+            let success = result.success;
+            free_slots += self.weight(&self.tasks[&task_id]);
+            self.record(&mut states, &mut results, result, total_tasks);
+
+            if failed {
+                self.skip_dependents(&task_id, &mut states, &mut results, total_tasks);
+                match self.policy {
+                    FailurePolicy::Continue => {}
+                    FailurePolicy::Stop => stopping = true,
+                    FailurePolicy::Bail => {
+                        say(&self.events, format!("🛑 {} failed, cancelling running tasks (--bail)", task_id).red().bold());
+                        aborted = true;
+                        running.abort_all();
+                        break;
+                    }
+                }
+            } else if success {
+                // Queue dependents whose dependencies are all done
+                for dep_id in self.dependents.get(&task_id).into_iter().flatten() {
+                    let Some(dep_task) = self.tasks.get(dep_id) else {
+                        continue;
+                    };
+                    let all_deps_done = dep_task
+                        .dependencies
+                        .iter()
+                        .all(|d| matches!(states.get(d), Some(TaskState::Completed)));
+                    if all_deps_done && matches!(states.get(dep_id), Some(TaskState::Pending)) {
+                        states.insert(dep_id.clone(), TaskState::Ready);
+                        emit(&self.events, TaskEvent::State { task_id: dep_id.clone(), state: TaskState::Ready });
+                        ready.push(dep_id.clone());
+                    }
                 }
+            }
 
-                let result = task_fn(task).await.unwrap_or_else(|e| TaskResult {
-                    task_id: String::new(),
-                    success: false,
-                    duration_ms: 0,
-                    error: Some(e.to_string()),
+            if !stopping {
+                self.dispatch(&mut ready, &priorities, &mut free_slots, &mut states, |task, weight| {
+                    spawn_task(&mut running, task, weight, &semaphore, &gate, &task_fn, self.retry_backoff);
                 });
-
-                let _ = tx.send(result).await;
-            });
+            }
         }
 
-        // Process completions and spawn newly ready tasks
-        while completed_count < total_tasks {
-            if let Some(result) = rx.recv().await {
-                completed_count += 1;
-
-                let task_id = result.task_id.clone();
-                let success = result.success;
-
-                // Update state
-                {
-                    let mut states_guard = states.lock().await;
-                    if success {
-                        states_guard.insert(task_id.clone(), TaskState::Completed);
-                    } else {
-                        states_guard.insert(
-                            task_id.clone(),
-                            TaskState::Failed(result.error.clone().unwrap_or_default()),
-                        );
-                    }
+        if aborted {
+            // Keep results of tasks that finished before the abort landed
+            while let Some(joined) = running.join_next().await {
+                if let Ok(result) = joined {
+                    self.record(&mut states, &mut results, result, total_tasks);
                 }
+            }
 
-                // Print progress
-                if success {
-                    println!(
-                        "{}",
-                        format!(
-                            "  ✅ [{}/{}] {} ({}ms)",
-                            completed_count, total_tasks, task_id, result.duration_ms
-                        )
-                        .green()
-                    );
-                } else {
-                    println!(
-                        "{}",
-                        format!(
-                            "  ❌ [{}/{}] {} - {}",
-                            completed_count,
-                            total_tasks,
-                            task_id,
-                            result.error.as_deref().unwrap_or("unknown error")
-                        )
-                        .red()
-                    );
+            let mut cancelled: Vec<String> = states
+                .iter()
+                .filter(|(_, s)| **s == TaskState::Running)
+                .map(|(id, _)| id.clone())
+                .collect();
+            cancelled.sort();
+            for task_id in cancelled {
+                // Still waiting for a worker slot: it never ran
+                if !gate.started(&task_id) {
+                    self.skip(&task_id, "cancelled", &mut states, &mut results, total_tasks);
+                    continue;
                 }
+                self.record(
+                    &mut states,
+                    &mut results,
+                    TaskResult {
+                        task_id,
+                        success: false,
+                        duration_ms: 0,
+                        error: Some("cancelled".to_string()),
+                        cached: false,
+                        skipped: false,
+                    },
+                    total_tasks,
+                );
+            }
+        }
 
-                results.push(result);
-
-                // If successful, check dependents
-                if success
-                    && let Some(deps) = dependents.get(&task_id) {
-                        for dep_id in deps {
-                            let should_run = {
-                                let states_guard = states.lock().await;
-
-                                // Check if all dependencies are completed
-                                let Some(dep_task) = tasks.get(dep_id) else {
-                                    // This should never happen as dep_id comes from dependents map
-                                    eprintln!("Internal error: dependent task not found during state check: {}", dep_id);
-                                    continue;
-                                };
-                                let all_deps_done = dep_task.dependencies.iter().all(|d| {
-                                    matches!(
-                                        states_guard.get(d),
-                                        Some(TaskState::Completed)
-                                    )
-                                });
-
-                                // Only run if pending and all deps done
-                                all_deps_done
-                                    && matches!(
-                                        states_guard.get(dep_id),
-                                        Some(TaskState::Pending)
-                                    )
-                            };
-
-                            if should_run {
-                                // Mark as ready then spawn
-                                {
-                                    let mut states_guard = states.lock().await;
-                                    states_guard.insert(dep_id.clone(), TaskState::Ready);
-                                }
-
-                                let task = match tasks.get(dep_id) {
-                                    Some(t) => t.clone(),
-                                    None => {
-                                        // This should never happen as dep_id comes from dependents map
-                                        eprintln!("Internal error: dependent task not found: {}", dep_id);
-                                        continue;
-                                    }
-                                };
-                                let semaphore = Arc::clone(&semaphore);
-                                let tx = tx.clone();
-                                let states = Arc::clone(&states);
-                                let task_fn = task_fn.clone();
-
-                                tokio::spawn(async move {
-                                    let _permit = semaphore.acquire().await.expect("semaphore closed unexpectedly");
-
-                                    {
-                                        let mut states = states.lock().await;
-                                        states.insert(task.id.clone(), TaskState::Running);
-                                    }
-
-                                    let result =
-                                        task_fn(task).await.unwrap_or_else(|e| TaskResult {
-                                            task_id: String::new(),
-                                            success: false,
-                                            duration_ms: 0,
-                                            error: Some(e.to_string()),
-                                        });
-
-                                    let _ = tx.send(result).await;
-                                });
-                            }
-                        }
-                    }
+        // Everything else never started
+        let reason = if interrupted { "cancelled" } else { "not started after an earlier failure" };
+        let mut not_started: Vec<String> = states
+            .iter()
+            .filter(|(_, s)| matches!(s, TaskState::Pending | TaskState::Ready))
+            .map(|(id, _)| id.clone())
+            .collect();
+        not_started.sort();
+        for task_id in not_started {
+            self.skip(&task_id, reason, &mut states, &mut results, total_tasks);
+        }
+
+        *self.timeline.lock().expect("timeline lock poisoned") =
+            std::mem::take(&mut *gate.spans.lock().expect("gate lock poisoned"));
+
+        for line in summary(&results, total_tasks) {
+            say(&self.events, line);
+        }
+
+        Ok(results)
+    }
+
+    /// Worker slots a task occupies (at least 1, at most the whole pool)
+    fn weight(&self, task: &BuildTask) -> usize {
+        task.weight.clamp(1, self.max_parallel.max(1))
+    }
+
+    /// Start ready tasks, highest priority first, while worker slots are free
+    ///
+    /// A task that doesn't fit in the free slots is passed over for smaller
+    /// ones behind it and retried when slots free up.
+    fn dispatch(
+        &self,
+        ready: &mut Vec<String>,
+        priorities: &HashMap<String, u64>,
+        free_slots: &mut usize,
+        states: &mut HashMap<String, TaskState>,
+        mut spawn: impl FnMut(BuildTask, usize),
+    ) {
+        ready.sort_by(|a, b| priorities[b].cmp(&priorities[a]).then_with(|| a.cmp(b)));
+        ready.retain(|task_id| {
+            let task = &self.tasks[task_id];
+            let weight = self.weight(task);
+            if weight > *free_slots {
+                return true;
             }
+            *free_slots -= weight;
+            states.insert(task_id.clone(), TaskState::Running);
+            spawn(task.clone(), weight);
+            false
+        });
+    }
+
+    /// Longest remaining path (in estimated ms) from each task to the end of the graph
+    ///
+    /// Tasks without history are assumed to take as long as the average known
+    /// task (1ms each when nothing is known, which ranks by chain length).
+    pub fn priorities(&self) -> HashMap<String, u64> {
+        let known: Vec<u64> = self
+            .tasks
+            .keys()
+            .filter_map(|id| self.estimates.get(id).copied())
+            .collect();
+        let fallback = if known.is_empty() {
+            1
+        } else {
+            (known.iter().sum::<u64>() / known.len() as u64).max(1)
+        };
+
+        let mut priorities = HashMap::new();
+        let mut ids: Vec<&String> = self.tasks.keys().collect();
+        ids.sort();
+        for id in ids {
+            self.priority(id, fallback, &mut priorities, &mut HashSet::new());
         }
+        priorities
+    }
+
+    fn priority(
+        &self,
+        task_id: &str,
+        fallback: u64,
+        memo: &mut HashMap<String, u64>,
+        visiting: &mut HashSet<String>,
+    ) -> u64 {
+        if let Some(&priority) = memo.get(task_id) {
+            return priority;
+        }
+        // Cycles are rejected when the graph is planned; don't recurse forever
+        if !visiting.insert(task_id.to_string()) {
+            return 0;
+        }
+
+        let own = self.estimates.get(task_id).copied().unwrap_or(fallback);
+        let tail = self
+            .dependents
+            .get(task_id)
+            .into_iter()
+            .flatten()
+            .filter(|d| self.tasks.contains_key(*d))
+            .map(|d| self.priority(d, fallback, memo, visiting))
+            .max()
+            .unwrap_or(0);
+
+        visiting.remove(task_id);
+        memo.insert(task_id.to_string(), own + tail);
+        own + tail
+    }
+
+    /// Record a finished task and print its progress line
+    fn record(
+        &self,
+        states: &mut HashMap<String, TaskState>,
+        results: &mut Vec<TaskResult>,
+        result: TaskResult,
+        total_tasks: usize,
+    ) {
+        use colored::Colorize;
 
-        // Summary
-        let failed: Vec<_> = results.iter().filter(|r| !r.success).collect();
-        let total_time: u64 = results.iter().map(|r| r.duration_ms).sum();
-
-        println!();
-        if failed.is_empty() {
-            println!(
-                "{}",
-                format!("✅ All {} tasks completed successfully ({}ms total)", total_tasks, total_time)
-                    .green()
-                    .bold()
-            );
+        let progress = format!("[{}/{}]", results.len() + 1, total_tasks);
+        emit(&self.events, TaskEvent::Finished(result.clone()));
+        if result.skipped {
+            let reason = result.error.as_deref().unwrap_or_default();
+            say(&self.events, format!("  ⏭️  {} {} - skipped: {}", progress, result.task_id, reason).yellow());
+            states.insert(result.task_id.clone(), TaskState::Skipped(reason.to_string()));
+        } else if result.success {
+            states.insert(result.task_id.clone(), TaskState::Completed);
+            let line = if result.cached {
+                format!("  ⚡ {} {} (cached, {}ms)", progress, result.task_id, result.duration_ms)
+            } else {
+                format!("  ✅ {} {} ({}ms)", progress, result.task_id, result.duration_ms)
+            };
+            say(&self.events, line.green());
         } else {
-            println!(
-                "{}",
-                format!("❌ {} of {} tasks failed", failed.len(), total_tasks)
-                    .red()
-                    .bold()
-            );
+            let error = result.error.clone().unwrap_or_else(|| "unknown error".to_string());
+            say(&self.events, format!("  ❌ {} {} - {}", progress, result.task_id, error).red());
+            states.insert(result.task_id.clone(), TaskState::Failed(error));
         }
 
-        Ok(results)
+        results.push(result);
+    }
+
+    /// Skip every pending task that (transitively) depends on a failed one
+    fn skip_dependents(
+        &self,
+        task_id: &str,
+        states: &mut HashMap<String, TaskState>,
+        results: &mut Vec<TaskResult>,
+        total_tasks: usize,
+    ) {
+        let mut blocked = collect_dependents(task_id, &self.dependents);
+        blocked.sort();
+        let reason = format!("dependency {} failed", task_id);
+        for blocked_id in blocked {
+            if matches!(states.get(&blocked_id), Some(TaskState::Pending)) {
+                self.skip(&blocked_id, &reason, states, results, total_tasks);
+            }
+        }
+    }
+
+    fn skip(
+        &self,
+        task_id: &str,
+        reason: &str,
+        states: &mut HashMap<String, TaskState>,
+        results: &mut Vec<TaskResult>,
+        total_tasks: usize,
+    ) {
+        use colored::Colorize;
+
+        states.insert(task_id.to_string(), TaskState::Skipped(reason.to_string()));
+        let result = TaskResult {
+            task_id: task_id.to_string(),
+            success: false,
+            duration_ms: 0,
+            error: Some(reason.to_string()),
+            cached: false,
+            skipped: true,
+        };
+        emit(&self.events, TaskEvent::Finished(result.clone()));
+        say(
+            &self.events,
+            format!("  ⏭️  [{}/{}] {} - skipped: {}", results.len() + 1, total_tasks, task_id, reason).yellow(),
+        );
+        results.push(result);
     }
 }
 
+/// State shared with spawned tasks
+///
+/// Checked after a task gets its worker slot, so queued tasks see a failure
+/// before they start.
+struct Gate {
+    policy: FailurePolicy,
+    stopped: AtomicBool,
+    started: std::sync::Mutex<HashSet<String>>,
+    epoch: std::time::Instant,
+    /// Busy flag per worker slot
+    lanes: std::sync::Mutex<Vec<bool>>,
+    spans: std::sync::Mutex<Vec<TaskSpan>>,
+    events: Option<EventSink>,
+}
+
+impl Gate {
+    fn new(policy: FailurePolicy, max_parallel: usize, events: Option<EventSink>) -> Self {
+        Self {
+            policy,
+            stopped: AtomicBool::new(false),
+            started: std::sync::Mutex::new(HashSet::new()),
+            epoch: std::time::Instant::now(),
+            lanes: std::sync::Mutex::new(vec![false; max_parallel.max(1)]),
+            spans: std::sync::Mutex::new(Vec::new()),
+            events,
+        }
+    }
+
+    fn started(&self, task_id: &str) -> bool {
+        self.started.lock().expect("gate lock poisoned").contains(task_id)
+    }
+
+    /// Claim the lowest free lane (one is always free while holding a permit)
+    fn claim_lane(&self) -> usize {
+        let mut lanes = self.lanes.lock().expect("gate lock poisoned");
+        let lane = lanes.iter().position(|busy| !busy).unwrap_or(lanes.len());
+        if lane == lanes.len() {
+            lanes.push(true);
+        } else {
+            lanes[lane] = true;
+        }
+        lane
+    }
+
+    fn finish(&self, task_id: String, lane: usize, start_us: u64) {
+        self.lanes.lock().expect("gate lock poisoned")[lane] = false;
+        self.spans.lock().expect("gate lock poisoned").push(TaskSpan {
+            task_id,
+            lane,
+            start_us,
+            end_us: self.now_us(),
+        });
+    }
+
+    fn now_us(&self) -> u64 {
+        self.epoch.elapsed().as_micros() as u64
+    }
+}
+
+/// Spawn a task (with retries) once a worker slot is free
+fn spawn_task<F, Fut>(
+    running: &mut JoinSet<TaskResult>,
+    task: BuildTask,
+    weight: usize,
+    semaphore: &Arc<Semaphore>,
+    gate: &Arc<Gate>,
+    task_fn: &F,
+    backoff: Duration,
+) where
+    F: Fn(BuildTask) -> Fut + Send + Sync + Clone + 'static,
+    Fut: Future<Output = Result<TaskResult>> + Send + 'static,
+{
+    let semaphore = Arc::clone(semaphore);
+    let gate = Arc::clone(gate);
+    let task_fn = task_fn.clone();
+
+    running.spawn(async move {
+        let _permit = semaphore.acquire_many_owned(weight as u32).await.expect("semaphore closed unexpectedly");
+        if gate.stopped.load(Ordering::SeqCst) {
+            return TaskResult {
+                task_id: task.id,
+                success: false,
+                duration_ms: 0,
+                error: Some("not started after an earlier failure".to_string()),
+                cached: false,
+                skipped: true,
+            };
+        }
+        gate.started.lock().expect("gate lock poisoned").insert(task.id.clone());
+        let lane = gate.claim_lane();
+        let start_us = gate.now_us();
+        emit(&gate.events, TaskEvent::State { task_id: task.id.clone(), state: TaskState::Running });
+
+        let result = run_with_retries(task, task_fn, backoff, &gate.events).await;
+        gate.finish(result.task_id.clone(), lane, start_us);
+        // Before the slot is released, so the next queued task sees it
+        if !result.success && gate.policy != FailurePolicy::Continue {
+            gate.stopped.store(true, Ordering::SeqCst);
+        }
+        result
+    });
+}
+
+/// Run a task, retrying failures with exponential backoff
+async fn run_with_retries<F, Fut>(task: BuildTask, task_fn: F, backoff: Duration, events: &Option<EventSink>) -> TaskResult
+where
+    F: Fn(BuildTask) -> Fut,
+    Fut: Future<Output = Result<TaskResult>>,
+{
+    use colored::Colorize;
+
+    let start = std::time::Instant::now();
+    let mut attempt = 0;
+    loop {
+        let task_id = task.id.clone();
+        let mut result = task_fn(task.clone()).await.unwrap_or_else(|e| TaskResult {
+            task_id,
+            success: false,
+            duration_ms: 0,
+            error: Some(e.to_string()),
+            cached: false,
+            skipped: false,
+        });
+        if result.success || attempt >= task.retries {
+            if attempt > 0 {
+                result.duration_ms = start.elapsed().as_millis() as u64;
+            }
+            return result;
+        }
+
+        attempt += 1;
+        let delay = backoff.saturating_mul(1 << (attempt - 1).min(10));
+        say(
+            events,
+            format!(
+                "  🔁 {} failed ({}), retry {}/{} in {}ms",
+                task.id,
+                result.error.as_deref().unwrap_or("unknown error"),
+                attempt,
+                task.retries,
+                delay.as_millis()
+            )
+            .yellow(),
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Print a progress line, or send it to the event sink
+fn say(events: &Option<EventSink>, line: impl std::fmt::Display) {
+    match events {
+        Some(events) => {
+            let _ = events.send(TaskEvent::Message(line.to_string()));
+        }
+        None => println!("{}", line),
+    }
+}
+
+fn emit(events: &Option<EventSink>, event: TaskEvent) {
+    if let Some(events) = events {
+        // The receiver going away (e.g., TUI closed) doesn't stop the run
+        let _ = events.send(event);
+    }
+}
+
+/// Print succeeded / cached / failed / skipped counts
+pub fn print_summary(results: &[TaskResult], total_tasks: usize) {
+    for line in summary(results, total_tasks) {
+        println!("{}", line);
+    }
+}
+
+/// Summary lines: succeeded / cached / failed / skipped counts
+fn summary(results: &[TaskResult], total_tasks: usize) -> Vec<String> {
+    use colored::Colorize;
+
+    let cached = results.iter().filter(|r| r.success && r.cached).count();
+    let succeeded = results.iter().filter(|r| r.success).count() - cached;
+    let skipped = results.iter().filter(|r| r.skipped).count();
+    let failed = results.len() - succeeded - cached - skipped;
+    let total_time: u64 = results.iter().map(|r| r.duration_ms).sum();
+
+    let headline = if failed == 0 && skipped == 0 {
+        format!("✅ All {} tasks completed successfully ({}ms total)", total_tasks, total_time)
+            .green()
+            .bold()
+    } else {
+        format!("❌ {} of {} tasks failed, {} skipped", failed, total_tasks, skipped)
+            .red()
+            .bold()
+    };
+    vec![
+        String::new(),
+        headline.to_string(),
+        format!(
+            "   {} succeeded, {} cached, {} failed, {} skipped",
+            succeeded, cached, failed, skipped
+        )
+        .dimmed()
+        .to_string(),
+    ]
+}
+
+/// Collect all transitive dependents of a task
+fn collect_dependents(task_id: &str, dependents: &HashMap<String, Vec<String>>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut stack = vec![task_id.to_string()];
+    let mut result = Vec::new();
+
+    while let Some(id) = stack.pop() {
+        if let Some(next) = dependents.get(&id) {
+            for dep in next {
+                if seen.insert(dep.clone()) {
+                    result.push(dep.clone());
+                    stack.push(dep.clone());
+                }
+            }
+        }
+    }
+
+    result
+}
+
 /// Get default parallelism (number of CPUs)
 pub fn default_parallelism() -> usize {
     std::thread::available_parallelism()
@@ -343,6 +864,8 @@ mod tests {
                 success: true,
                 duration_ms: 0,
                 error: None,
+                cached: false,
+                skipped: false,
             }) })
             .await
             .unwrap();
@@ -357,6 +880,8 @@ mod tests {
             target: "apps/web".to_string(),
             channel: "lts".to_string(),
             dependencies: vec![],
+            retries: 0,
+            weight: 1,
         });
 
         let results = executor
@@ -366,6 +891,8 @@ mod tests {
                     success: true,
                     duration_ms: 10,
                     error: None,
+                    cached: false,
+                    skipped: false,
                 })
             })
             .await
@@ -385,12 +912,16 @@ mod tests {
             target: "libs/core".to_string(),
             channel: "lts".to_string(),
             dependencies: vec![],
+            retries: 0,
+            weight: 1,
         });
         executor.add_task(BuildTask {
             id: "task2".to_string(),
             target: "apps/web".to_string(),
             channel: "lts".to_string(),
             dependencies: vec!["task1".to_string()],
+            retries: 0,
+            weight: 1,
         });
 
         let execution_order = Arc::new(Mutex::new(Vec::new()));
@@ -406,6 +937,8 @@ mod tests {
                         success: true,
                         duration_ms: 10,
                         error: None,
+                        cached: false,
+                        skipped: false,
                     })
                 }
             })
@@ -420,4 +953,288 @@ mod tests {
         let pos2 = order.iter().position(|x| x == "task2").unwrap();
         assert!(pos1 < pos2);
     }
+
+    #[tokio::test]
+    async fn test_executor_failure_skips_dependents() {
+        let mut executor = ParallelExecutor::new(4);
+
+        // task3 -> task2 -> task1 (task1 fails)
+        for (id, deps) in [("task1", vec![]), ("task2", vec!["task1"]), ("task3", vec!["task2"])] {
+            executor.add_task(BuildTask {
+                id: id.to_string(),
+                target: id.to_string(),
+                channel: "lts".to_string(),
+                dependencies: deps.into_iter().map(String::from).collect(),
+                retries: 0,
+                weight: 1,
+            });
+        }
+
+        let results = executor
+            .execute(|task| async move {
+                if task.id == "task1" {
+                    anyhow::bail!("boom");
+                }
+                Ok(TaskResult {
+                    task_id: task.id,
+                    success: true,
+                    duration_ms: 0,
+                    error: None,
+                    cached: false,
+                    skipped: false,
+                })
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|r| !r.success));
+        let task1 = results.iter().find(|r| r.task_id == "task1").unwrap();
+        assert_eq!(task1.error.as_deref(), Some("boom"));
+        assert!(!task1.skipped);
+        let task3 = results.iter().find(|r| r.task_id == "task3").unwrap();
+        assert!(task3.skipped);
+        assert_eq!(task3.error.as_deref(), Some("dependency task1 failed"));
+    }
+
+    /// "fail" fails after a moment, "slow" takes a while, others succeed
+    fn independent_tasks(ids: &[&str]) -> Vec<BuildTask> {
+        ids.iter()
+            .map(|id| BuildTask {
+                id: id.to_string(),
+                target: id.to_string(),
+                channel: String::new(),
+                dependencies: vec![],
+                retries: 0,
+                weight: 1,
+            })
+            .collect()
+    }
+
+    async fn run_independent(executor: &ParallelExecutor) -> Vec<TaskResult> {
+        executor
+            .execute(|task| async move {
+                match task.id.as_str() {
+                    "slow" => tokio::time::sleep(Duration::from_secs(5)).await,
+                    "fail" => tokio::time::sleep(Duration::from_millis(20)).await,
+                    _ => {}
+                }
+                Ok(TaskResult {
+                    success: task.id != "fail",
+                    task_id: task.id,
+                    duration_ms: 0,
+                    error: None,
+                    cached: false,
+                    skipped: false,
+                })
+            })
+            .await
+            .unwrap()
+    }
+
+    fn find<'a>(results: &'a [TaskResult], id: &str) -> &'a TaskResult {
+        results.iter().find(|r| r.task_id == id).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_failure_policies() {
+        // One worker: "fail" runs first, "ok" is still queued
+        let ids = ["fail", "ok"];
+
+        let mut stop = ParallelExecutor::new(1);
+        let mut cont = ParallelExecutor::new(1).failure_policy(FailurePolicy::Continue);
+        for task in independent_tasks(&ids) {
+            stop.add_task(task.clone());
+            cont.add_task(task);
+        }
+
+        let results = run_independent(&stop).await;
+        assert!(find(&results, "ok").skipped);
+
+        let results = run_independent(&cont).await;
+        assert!(find(&results, "ok").success);
+        assert!(!find(&results, "fail").success);
+    }
+
+    #[tokio::test]
+    async fn test_bail_cancels_running_tasks() {
+        let mut executor = ParallelExecutor::new(2).failure_policy(FailurePolicy::Bail);
+        for task in independent_tasks(&["fail", "slow"]) {
+            executor.add_task(task);
+        }
+
+        let start = std::time::Instant::now();
+        let results = run_independent(&executor).await;
+
+        assert!(start.elapsed() < Duration::from_secs(4));
+        let slow = find(&results, "slow");
+        assert!(!slow.success && !slow.skipped);
+        assert_eq!(slow.error.as_deref(), Some("cancelled"));
+    }
+
+    #[tokio::test]
+    async fn test_canceller_interrupts_run() {
+        let mut executor = ParallelExecutor::new(1);
+        for task in independent_tasks(&["first", "second"]) {
+            executor.add_task(task);
+        }
+
+        // Cancel once "first" is running, as Ctrl-C would
+        let cancel = executor.canceller();
+        let results = executor
+            .execute(move |task| {
+                let cancel = Arc::clone(&cancel);
+                async move {
+                    cancel.notify_one();
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    Ok(TaskResult {
+                        task_id: task.id,
+                        success: true,
+                        duration_ms: 0,
+                        error: None,
+                        cached: false,
+                        skipped: false,
+                    })
+                }
+            })
+            .await
+            .unwrap();
+
+        let first = find(&results, "first");
+        assert!(!first.success && !first.skipped);
+        assert_eq!(first.error.as_deref(), Some("cancelled"));
+        // Queued behind the single worker slot: never started
+        let second = find(&results, "second");
+        assert!(second.skipped);
+        assert_eq!(second.error.as_deref(), Some("cancelled"));
+    }
+
+    #[tokio::test]
+    async fn test_timeline_lanes() {
+        let mut executor = ParallelExecutor::new(2);
+        for task in independent_tasks(&["a", "b", "c"]) {
+            executor.add_task(task);
+        }
+        run_independent(&executor).await;
+
+        let timeline = executor.timeline();
+        assert_eq!(timeline.len(), 3);
+        assert!(timeline.iter().all(|s| s.lane < 2 && s.start_us <= s.end_us));
+    }
+
+    /// Run tasks on one worker and return the order they started in
+    async fn start_order(executor: &ParallelExecutor) -> Vec<String> {
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let order_clone = Arc::clone(&order);
+        executor
+            .execute(move |task| {
+                let order = Arc::clone(&order_clone);
+                async move {
+                    order.lock().await.push(task.id.clone());
+                    Ok(TaskResult {
+                        task_id: task.id,
+                        success: true,
+                        duration_ms: 0,
+                        error: None,
+                        cached: false,
+                        skipped: false,
+                    })
+                }
+            })
+            .await
+            .unwrap();
+        order.lock().await.clone()
+    }
+
+    #[tokio::test]
+    async fn test_longest_chain_starts_first() {
+        // "z1" -> "z2" -> "z3" is the long chain; "a" would win on name alone
+        let mut executor = ParallelExecutor::new(1);
+        let mut tasks = independent_tasks(&["a", "z1", "z2", "z3"]);
+        tasks[2].dependencies = vec!["z1".to_string()];
+        tasks[3].dependencies = vec!["z2".to_string()];
+        for task in tasks {
+            executor.add_task(task);
+        }
+
+        assert_eq!(executor.priorities()["z1"], 3);
+        assert_eq!(start_order(&executor).await[0], "z1");
+    }
+
+    #[tokio::test]
+    async fn test_estimates_weight_priorities() {
+        let estimates = HashMap::from([("a".to_string(), 10), ("b".to_string(), 5000)]);
+        let mut executor = ParallelExecutor::new(1).estimates(estimates);
+        for task in independent_tasks(&["a", "b", "c"]) {
+            executor.add_task(task);
+        }
+
+        // "c" has no history and is assumed average
+        let priorities = executor.priorities();
+        assert_eq!(priorities["c"], 2505);
+        assert_eq!(start_order(&executor).await, vec!["b", "c", "a"]);
+    }
+
+    #[tokio::test]
+    async fn test_weighted_tasks_take_multiple_slots() {
+        let mut executor = ParallelExecutor::new(2);
+        let mut tasks = independent_tasks(&["heavy", "light1", "light2"]);
+        tasks[0].weight = 2;
+        for task in tasks {
+            executor.add_task(task);
+        }
+        executor
+            .execute(|task| async move {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                Ok(TaskResult {
+                    task_id: task.id,
+                    success: true,
+                    duration_ms: 0,
+                    error: None,
+                    cached: false,
+                    skipped: false,
+                })
+            })
+            .await
+            .unwrap();
+
+        // Nothing runs alongside the task that takes both slots
+        let timeline = executor.timeline();
+        let heavy = timeline.iter().find(|s| s.task_id == "heavy").unwrap();
+        assert!(timeline
+            .iter()
+            .filter(|s| s.task_id != "heavy")
+            .all(|s| s.end_us <= heavy.start_us || s.start_us >= heavy.end_us));
+    }
+
+    #[tokio::test]
+    async fn test_retries_with_backoff() {
+        let mut executor = ParallelExecutor::new(1).retry_backoff(Duration::from_millis(1));
+        let mut tasks = independent_tasks(&["flaky"]);
+        tasks[0].retries = 2;
+        executor.add_task(tasks.remove(0));
+
+        let attempts = Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let counter = Arc::clone(&attempts);
+        let results = executor
+            .execute(move |task| {
+                let counter = Arc::clone(&counter);
+                async move {
+                    let attempt = counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+                    Ok(TaskResult {
+                        task_id: task.id,
+                        success: attempt == 3,
+                        duration_ms: 0,
+                        error: None,
+                        cached: false,
+                        skipped: false,
+                    })
+                }
+            })
+            .await
+            .unwrap();
+
+        assert!(results[0].success);
+        assert_eq!(attempts.load(std::sync::atomic::Ordering::SeqCst), 3);
+    }
 }
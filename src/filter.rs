@@ -0,0 +1,262 @@
+//! Package filter expressions (pnpm/turbo style)
+//!
+//! Resolved against the workspace graph into package IDs (paths).
+//!
+//! | Expression          | Selects                                        |
+//! |---------------------|------------------------------------------------|
+//! | `@scope/web`        | package by name (globs allowed: `@scope/*`)    |
+//! | `apps/*`, `./libs/ui` | packages by path (globs allowed)             |
+//! | `@scope/web...`     | the package and everything it depends on       |
+//! | `...libs/ui`        | the package and everything that depends on it  |
+//! | `@scope/web^...`    | only its dependencies                          |
+//! | `...^libs/ui`       | only its dependents                            |
+//! | `[origin/main]`     | packages changed since a git ref               |
+//! | `!apps/legacy`      | exclude matches from the other filters         |
+//!
+//! Multiple filters are combined: the union of all includes, minus all excludes.
+//! With only excludes, they apply to every package.
+
+use anyhow::{bail, Result};
+use std::collections::HashSet;
+
+use crate::dag::Dag;
+
+/// A parsed filter expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    pub exclude: bool,
+    pub selector: Selector,
+    /// `...x`: add dependents
+    pub dependents: bool,
+    /// `...^x`: dependents only
+    pub dependents_only: bool,
+    /// `x...`: add dependencies
+    pub dependencies: bool,
+    /// `x^...`: dependencies only
+    pub dependencies_only: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Selector {
+    /// Name or path pattern
+    Pattern(String),
+    /// Packages changed since a git ref (`[ref]`)
+    Changed(String),
+}
+
+impl Filter {
+    /// Parse a single filter expression
+    pub fn parse(expr: &str) -> Result<Self> {
+        let mut rest = expr.trim();
+
+        let exclude = rest.starts_with('!');
+        if exclude {
+            rest = &rest[1..];
+        }
+
+        let mut dependents = false;
+        let mut dependents_only = false;
+        if let Some(r) = rest.strip_prefix("...") {
+            dependents = true;
+            rest = r;
+            if let Some(r) = rest.strip_prefix('^') {
+                dependents_only = true;
+                rest = r;
+            }
+        }
+
+        let mut dependencies = false;
+        let mut dependencies_only = false;
+        if let Some(r) = rest.strip_suffix("...") {
+            dependencies = true;
+            rest = r;
+            if let Some(r) = rest.strip_suffix('^') {
+                dependencies_only = true;
+                rest = r;
+            }
+        }
+
+        let selector = if let Some(inner) = rest.strip_prefix('[').and_then(|r| r.strip_suffix(']')) {
+            if inner.is_empty() {
+                bail!("Invalid filter '{}': empty git ref", expr);
+            }
+            Selector::Changed(inner.to_string())
+        } else {
+            // pnpm-style "{./apps/web}" and "./apps/web" both mean a path
+            let pattern = rest
+                .strip_prefix('{')
+                .and_then(|r| r.strip_suffix('}'))
+                .unwrap_or(rest);
+            let pattern = pattern.strip_prefix("./").unwrap_or(pattern).trim_end_matches('/');
+            if pattern.is_empty() {
+                bail!("Invalid filter '{}': missing package selector", expr);
+            }
+            glob::Pattern::new(pattern)
+                .map_err(|e| anyhow::anyhow!("Invalid filter '{}': {}", expr, e))?;
+            Selector::Pattern(pattern.to_string())
+        };
+
+        Ok(Self {
+            exclude,
+            selector,
+            dependents,
+            dependents_only,
+            dependencies,
+            dependencies_only,
+        })
+    }
+
+    /// Package IDs selected by this filter (ignoring `exclude`)
+    fn select(&self, dag: &Dag) -> Result<HashSet<String>> {
+        let matched: Vec<String> = match &self.selector {
+            Selector::Pattern(pattern) => {
+                let glob = glob::Pattern::new(pattern)?;
+                // `*` stays within one path segment ("apps/*" doesn't match "apps/web/e2e")
+                let options = glob::MatchOptions {
+                    require_literal_separator: true,
+                    ..Default::default()
+                };
+                let mut ids: Vec<String> = dag
+                    .nodes
+                    .values()
+                    .filter(|n| glob.matches_with(&n.name, options) || glob.matches_with(&n.path, options))
+                    .map(|n| n.id.clone())
+                    .collect();
+                ids.sort();
+                ids
+            }
+            Selector::Changed(git_ref) => crate::commands::affected::changed_since(dag, git_ref)?,
+        };
+
+        let mut selected = HashSet::new();
+        if self.dependents {
+            selected.extend(dag.with_dependents(&matched));
+        }
+        if self.dependencies {
+            selected.extend(dag.with_dependencies(&matched));
+        }
+        if !self.dependents && !self.dependencies {
+            selected.extend(matched.iter().cloned());
+        }
+
+        // "^" drops the matched packages themselves
+        if self.dependents_only || self.dependencies_only {
+            for id in &matched {
+                selected.remove(id);
+            }
+        }
+
+        Ok(selected)
+    }
+}
+
+/// Resolve filter expressions to sorted package IDs
+///
+/// Fails if no package matches, so typos don't silently select nothing.
+pub fn resolve(dag: &Dag, exprs: &[String]) -> Result<Vec<String>> {
+    let filters: Vec<Filter> = exprs.iter().map(|e| Filter::parse(e)).collect::<Result<_>>()?;
+
+    let mut included: HashSet<String> = HashSet::new();
+    let mut excluded: HashSet<String> = HashSet::new();
+    let mut has_include = false;
+
+    for filter in &filters {
+        let selected = filter.select(dag)?;
+        if filter.exclude {
+            excluded.extend(selected);
+        } else {
+            has_include = true;
+            included.extend(selected);
+        }
+    }
+
+    if !has_include {
+        included = dag.nodes.keys().cloned().collect();
+    }
+
+    let mut result: Vec<String> = included.difference(&excluded).cloned().collect();
+    result.sort();
+
+    // "[ref]" legitimately selects nothing when there are no changes
+    let only_git = filters
+        .iter()
+        .all(|f| f.exclude || matches!(f.selector, Selector::Changed(_)));
+    if result.is_empty() && !only_git {
+        bail!("No packages match filter: {}", exprs.join(" "));
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dag::DagNode;
+
+    /// apps/web -> libs/ui -> libs/core, apps/admin -> libs/core
+    fn sample_dag() -> Dag {
+        let mut dag = Dag::new();
+        for (path, name, deps) in [
+            ("apps/web", "@acme/web", vec!["libs/ui"]),
+            ("apps/admin", "@acme/admin", vec!["libs/core"]),
+            ("libs/ui", "@acme/ui", vec!["libs/core"]),
+            ("libs/core", "@acme/core", vec![]),
+        ] {
+            dag.add_node(DagNode {
+                id: path.to_string(),
+                name: name.to_string(),
+                path: path.to_string(),
+                deps: deps.into_iter().map(String::from).collect(),
+            });
+        }
+        dag
+    }
+
+    fn select(exprs: &[&str]) -> Vec<String> {
+        let exprs: Vec<String> = exprs.iter().map(|e| e.to_string()).collect();
+        resolve(&sample_dag(), &exprs).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let f = Filter::parse("...^@acme/ui").unwrap();
+        assert!(f.dependents && f.dependents_only && !f.dependencies);
+        assert_eq!(f.selector, Selector::Pattern("@acme/ui".to_string()));
+
+        let f = Filter::parse("!{./apps/web}...").unwrap();
+        assert!(f.exclude && f.dependencies);
+        assert_eq!(f.selector, Selector::Pattern("apps/web".to_string()));
+
+        assert_eq!(
+            Filter::parse("...[origin/main]").unwrap().selector,
+            Selector::Changed("origin/main".to_string())
+        );
+        assert!(Filter::parse("[]").is_err());
+        assert!(Filter::parse("...").is_err());
+    }
+
+    #[test]
+    fn test_name_and_path_patterns() {
+        assert_eq!(select(&["@acme/web"]), vec!["apps/web"]);
+        assert_eq!(select(&["apps/*"]), vec!["apps/admin", "apps/web"]);
+        assert_eq!(select(&["./libs/ui"]), vec!["libs/ui"]);
+        // `*` doesn't cross `/`
+        assert_eq!(select(&["*/core"]), vec!["libs/core"]);
+        assert!(resolve(&sample_dag(), &["*".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_dependencies_and_dependents() {
+        assert_eq!(select(&["@acme/web..."]), vec!["apps/web", "libs/core", "libs/ui"]);
+        assert_eq!(select(&["@acme/web^..."]), vec!["libs/core", "libs/ui"]);
+        assert_eq!(select(&["...libs/ui"]), vec!["apps/web", "libs/ui"]);
+        assert_eq!(select(&["...^libs/core"]), vec!["apps/admin", "apps/web", "libs/ui"]);
+    }
+
+    #[test]
+    fn test_union_and_exclude() {
+        assert_eq!(select(&["apps/*", "!@acme/admin"]), vec!["apps/web"]);
+        assert_eq!(select(&["!libs/*"]), vec!["apps/admin", "apps/web"]);
+        assert!(resolve(&sample_dag(), &["@acme/missing".to_string()]).is_err());
+    }
+}
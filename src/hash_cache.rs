@@ -0,0 +1,278 @@
+//! File digests for build cache keys, hashed in parallel and cached by file stat
+//!
+//! `.airis/hash-cache` maps workspace-relative paths to the (mtime, size,
+//! inode) they had when hashed, plus their BLAKE3 digest. Files whose stat
+//! still matches aren't read again, so checking the cache for a large app
+//! costs a `stat` per file. Changed files are hashed on all cores, large ones
+//! through a memory map.
+
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+const CACHE_FILE: &str = ".airis/hash-cache";
+/// Held while merging and writing `CACHE_FILE`
+const LOCK_FILE: &str = ".airis/hash-cache.lock";
+
+/// Files at least this large are memory-mapped instead of read
+const MMAP_THRESHOLD: u64 = 64 * 1024;
+
+/// Files modified this recently aren't cached: a write within the same mtime
+/// tick could otherwise go unnoticed
+const RACY_WINDOW: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Entry {
+    mtime_ns: u64,
+    size: u64,
+    inode: u64,
+    digest: String,
+}
+
+impl Entry {
+    fn stat(meta: &fs::Metadata) -> (u64, u64, u64) {
+        let mtime_ns = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_nanos() as u64);
+        (mtime_ns, meta.len(), inode(meta))
+    }
+
+    fn matches(&self, (mtime_ns, size, inode): (u64, u64, u64)) -> bool {
+        self.mtime_ns == mtime_ns && self.size == size && self.inode == inode
+    }
+}
+
+#[cfg(unix)]
+fn inode(meta: &fs::Metadata) -> u64 {
+    std::os::unix::fs::MetadataExt::ino(meta)
+}
+
+#[cfg(not(unix))]
+fn inode(_meta: &fs::Metadata) -> u64 {
+    0
+}
+
+/// Stat-keyed digest cache of one workspace
+#[derive(Debug)]
+pub struct HashCache {
+    root: PathBuf,
+    entries: HashMap<String, Entry>,
+    dirty: bool,
+}
+
+impl HashCache {
+    /// Load `<root>/.airis/hash-cache` (empty if missing or unreadable)
+    pub fn load(root: &Path) -> Self {
+        let entries = fs::read(root.join(CACHE_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default();
+        Self {
+            root: root.to_path_buf(),
+            entries,
+            dirty: false,
+        }
+    }
+
+    /// BLAKE3 digests (hex) of `files`, in the same order
+    ///
+    /// Only files whose stat changed since they were cached are read.
+    pub fn digests(&mut self, files: &[PathBuf]) -> Result<Vec<String>> {
+        let now = SystemTime::now();
+        let hashed: Vec<(String, Entry, bool)> = files
+            .par_iter()
+            .map(|path| {
+                let meta = fs::metadata(path).with_context(|| format!("Failed to stat {}", path.display()))?;
+                let stat = Entry::stat(&meta);
+                let key = self.key(path);
+                if let Some(entry) = self.entries.get(&key)
+                    && entry.matches(stat)
+                {
+                    return Ok((key, entry.clone(), false));
+                }
+
+                let (mtime_ns, size, inode) = stat;
+                let entry = Entry {
+                    mtime_ns,
+                    size,
+                    inode,
+                    digest: digest_file(path, size)?,
+                };
+                let settled = meta.modified().is_ok_and(|t| t + RACY_WINDOW < now);
+                Ok((key, entry, settled))
+            })
+            .collect::<Result<_>>()?;
+
+        let mut digests = Vec::with_capacity(hashed.len());
+        for (key, entry, store) in hashed {
+            digests.push(entry.digest.clone());
+            if store {
+                self.entries.insert(key, entry);
+                self.dirty = true;
+            }
+        }
+        Ok(digests)
+    }
+
+    /// Write the cache back if anything changed, dropping deleted files
+    ///
+    /// Parallel builds save concurrently, so entries written by others since
+    /// `load` are merged in (ours win) instead of being overwritten.
+    pub fn save(&mut self) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        let path = self.root.join(CACHE_FILE);
+        let parent = path.parent().context("Hash cache path has no parent")?;
+        fs::create_dir_all(parent)?;
+        let lock = fs::File::create(self.root.join(LOCK_FILE))?;
+        lock.lock().context("Failed to lock the hash cache")?;
+
+        let on_disk: HashMap<String, Entry> = fs::read(&path)
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default();
+        for (key, entry) in on_disk {
+            self.entries.entry(key).or_insert(entry);
+        }
+        let root = self.root.clone();
+        self.entries.retain(|key, _| root.join(key).exists());
+
+        // Never leave a half-written file
+        let mut tmp = tempfile::NamedTempFile::new_in(parent)
+            .with_context(|| format!("Failed to create temp file in {}", parent.display()))?;
+        tmp.write_all(&serde_json::to_vec(&self.entries)?)
+            .with_context(|| format!("Failed to write {}", tmp.path().display()))?;
+        tmp.persist(&path).with_context(|| format!("Failed to write {}", path.display()))?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    fn key(&self, path: &Path) -> String {
+        let rel = path.strip_prefix(&self.root).unwrap_or(path);
+        rel.to_string_lossy().replace('\\', "/")
+    }
+}
+
+/// BLAKE3 digests (hex) of `files` in parallel, without a cache
+pub fn digest_files(files: &[PathBuf]) -> Result<Vec<String>> {
+    files
+        .par_iter()
+        .map(|path| {
+            let size = fs::metadata(path)
+                .with_context(|| format!("Failed to stat {}", path.display()))?
+                .len();
+            digest_file(path, size)
+        })
+        .collect()
+}
+
+fn digest_file(path: &Path, size: u64) -> Result<String> {
+    let mut hasher = blake3::Hasher::new();
+    if size >= MMAP_THRESHOLD {
+        let file = fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        // SAFETY: the map is read-only and dropped before returning. A file
+        // modified while mapped yields a digest of mixed content, which the
+        // stat check catches on the next run (its mtime changed).
+        let map = unsafe { memmap2::Mmap::map(&file) }
+            .with_context(|| format!("Failed to map {}", path.display()))?;
+        hasher.update(&map);
+    } else {
+        hasher.update(&fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?);
+    }
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set_mtime(path: &Path, ago: Duration) {
+        let file = fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() - ago).unwrap();
+    }
+
+    fn blake3_hex(content: &[u8]) -> String {
+        blake3::hash(content).to_hex().to_string()
+    }
+
+    #[test]
+    fn test_unchanged_stat_skips_reading() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.ts");
+        fs::write(&file, "aaaa").unwrap();
+        let hour = Duration::from_secs(3600);
+        set_mtime(&file, hour);
+
+        let mut cache = HashCache::load(dir.path());
+        assert_eq!(cache.digests(std::slice::from_ref(&file)).unwrap(), vec![blake3_hex(b"aaaa")]);
+        cache.save().unwrap();
+
+        // Same size and mtime: the cached digest is trusted
+        let stale = fs::metadata(&file).unwrap().modified().unwrap();
+        fs::write(&file, "bbbb").unwrap();
+        fs::File::options().write(true).open(&file).unwrap().set_modified(stale).unwrap();
+        let mut cache = HashCache::load(dir.path());
+        assert_eq!(cache.digests(std::slice::from_ref(&file)).unwrap(), vec![blake3_hex(b"aaaa")]);
+
+        set_mtime(&file, hour / 2);
+        assert_eq!(cache.digests(std::slice::from_ref(&file)).unwrap(), vec![blake3_hex(b"bbbb")]);
+    }
+
+    #[test]
+    fn test_recent_files_are_not_cached() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("fresh.ts");
+        fs::write(&file, "x").unwrap();
+
+        let mut cache = HashCache::load(dir.path());
+        cache.digests(std::slice::from_ref(&file)).unwrap();
+        assert!(cache.entries.is_empty());
+        assert!(!cache.dirty);
+    }
+
+    #[test]
+    fn test_concurrent_saves_keep_each_others_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let hour = Duration::from_secs(3600);
+        let files: Vec<PathBuf> = (0..8).map(|i| dir.path().join(format!("{}.ts", i))).collect();
+        for file in &files {
+            fs::write(file, "x").unwrap();
+            set_mtime(file, hour);
+        }
+
+        std::thread::scope(|s| {
+            for file in &files {
+                s.spawn(|| {
+                    let mut cache = HashCache::load(dir.path());
+                    cache.digests(std::slice::from_ref(file)).unwrap();
+                    cache.save().unwrap();
+                });
+            }
+        });
+
+        assert_eq!(HashCache::load(dir.path()).entries.len(), files.len());
+    }
+
+    #[test]
+    fn test_large_files_match_plain_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let small = dir.path().join("small.bin");
+        let large = dir.path().join("large.bin");
+        let content = vec![7u8; MMAP_THRESHOLD as usize * 2];
+        fs::write(&small, b"small").unwrap();
+        fs::write(&large, &content).unwrap();
+
+        assert_eq!(
+            digest_files(&[small, large]).unwrap(),
+            vec![blake3_hex(b"small"), blake3_hex(&content)]
+        );
+    }
+}
@@ -1,15 +1,31 @@
 mod channel;
 mod commands;
+mod content_hash;
 mod dag;
 mod docker_build;
 mod executor;
+mod filter;
 mod generators;
+mod hash_cache;
 mod manifest;
+mod native_build;
+mod oci;
 mod ownership;
+mod pipeline;
 mod pnpm;
+mod profile;
+mod provenance;
 mod remote_cache;
 mod safe_fs;
+mod sbom;
+mod task_cache;
+mod task_history;
+mod task_logs;
+mod task_ui;
 mod templates;
+mod watch;
+mod worker;
+mod workspace_graph;
 
 use anyhow::Result;
 use clap::{CommandFactory, Parser, Subcommand};
@@ -27,6 +43,25 @@ fn get_version() -> String {
     }
 }
 
+/// Map `--remote-cache-verify` to the manifest enum (None → use manifest.toml)
+fn parse_verify_mode(mode: Option<&str>) -> Option<manifest::RemoteCacheVerify> {
+    mode.map(|m| match m {
+        "warn" => manifest::RemoteCacheVerify::Warn,
+        _ => manifest::RemoteCacheVerify::Strict,
+    })
+}
+
+/// Map `--continue` / `--bail` to an executor failure policy
+fn failure_policy(continue_on_error: bool, bail: bool) -> executor::FailurePolicy {
+    if continue_on_error {
+        executor::FailurePolicy::Continue
+    } else if bail {
+        executor::FailurePolicy::Bail
+    } else {
+        executor::FailurePolicy::Stop
+    }
+}
+
 /// Resolve channel from CLI arg or manifest.toml
 /// Priority: CLI --channel > manifest.toml [projects.<name>.runner.channel] > "lts"
 fn resolve_channel_for_project(cli_channel: Option<String>, project_path: &str) -> String {
@@ -62,28 +97,6 @@ fn resolve_channel_for_project(cli_channel: Option<String>, project_path: &str)
     "lts".to_string()
 }
 
-/// Convert package name to project path
-/// e.g., "@workspace/web" -> "apps/web", "@agiletec/api" -> "apps/api"
-fn convert_package_to_path(package_name: &str) -> String {
-    // Remove @ prefix and scope
-    let name = package_name
-        .trim_start_matches('@')
-        .split('/')
-        .next_back()
-        .unwrap_or(package_name);
-
-    // Try to find the actual path by checking directories
-    for dir in &["apps", "libs", "packages"] {
-        let path = format!("{}/{}", dir, name);
-        if std::path::Path::new(&path).exists() {
-            return path;
-        }
-    }
-
-    // Default to apps/ if not found
-    format!("apps/{}", name)
-}
-
 #[derive(Parser)]
 #[command(name = "airis")]
 #[command(about = "Docker-first monorepo workspace manager", long_about = None)]
@@ -97,6 +110,8 @@ struct Cli {
 }
 
 #[derive(Subcommand)]
+// Parsed once per invocation; boxing Build's flags isn't worth it
+#[allow(clippy::large_enum_variant)]
 enum Commands {
     /// Initialize MANIFEST.toml + workspace metadata
     Init {
@@ -180,10 +195,59 @@ enum Commands {
         migrate: bool,
     },
 
-    /// Run a command defined in manifest.toml [commands]
+    /// Run a task from manifest.toml [tasks] (per-package pipeline) or [commands]
     Run {
-        /// Task name from [commands] section
+        /// Task name from [tasks] or [commands] section
         task: String,
+        /// Number of parallel task workers (default: CPU count)
+        #[arg(long, short = 'j')]
+        parallel: Option<usize>,
+        /// Ignore cached task results (re-run everything)
+        #[arg(long)]
+        no_cache: bool,
+        /// Run only in packages affected by git changes
+        #[arg(long)]
+        affected: bool,
+        /// Base branch/commit for --affected (default: origin/main)
+        #[arg(long, default_value = "origin/main")]
+        base: String,
+        /// Head branch/commit for --affected (default: HEAD)
+        #[arg(long, default_value = "HEAD")]
+        head: String,
+        /// Package filter, repeatable (e.g., "@scope/web...", "...libs/ui", "apps/*", "[origin/main]")
+        #[arg(long, short = 'F')]
+        filter: Vec<String>,
+        /// Dispatch tasks to `airis worker`s (host:port or stdio:<command>), repeatable
+        #[arg(long = "worker", env = "AIRIS_WORKERS", value_delimiter = ',')]
+        workers: Vec<String>,
+        /// Keep running tasks that don't depend on a failed one
+        #[arg(long = "continue", conflicts_with = "bail")]
+        continue_on_error: bool,
+        /// Cancel running tasks as soon as one fails
+        #[arg(long)]
+        bail: bool,
+        /// Retry failed tasks N times with exponential backoff (default for [tasks] without `retries`)
+        #[arg(long, default_value_t = 0)]
+        retries: u32,
+        /// Write a Chrome trace of the run (open in chrome://tracing or ui.perfetto.dev)
+        #[arg(long, value_name = "PATH")]
+        profile: Option<std::path::PathBuf>,
+        /// Watch package files and re-run for changed packages and their dependents
+        #[arg(long)]
+        watch: bool,
+        /// Task output: tui (default on a terminal), grouped (default in CI / when piped) or stream
+        #[arg(long, value_parser = ["auto", "tui", "grouped", "stream"], default_value = "auto")]
+        ui: String,
+    },
+
+    /// Run tasks for remote `airis run --worker` clients
+    Worker {
+        /// Listen on a TCP address (e.g., 0.0.0.0:7878) instead of stdio
+        #[arg(long)]
+        listen: Option<String>,
+        /// Concurrent tasks (default: CPU count)
+        #[arg(long, short = 'j')]
+        jobs: Option<usize>,
     },
 
     /// Start Docker services (alias for 'run up')
@@ -224,6 +288,9 @@ enum Commands {
         /// Head branch/commit for --affected (default: HEAD)
         #[arg(long, default_value = "HEAD")]
         head: String,
+        /// Package filter, repeatable (e.g., "@scope/web...", "apps/*", "[origin/main]")
+        #[arg(long, short = 'F')]
+        filter: Vec<String>,
         /// Build using Docker (hermetic build with auto-generated Dockerfile)
         #[arg(long)]
         docker: bool,
@@ -237,21 +304,58 @@ enum Commands {
         /// Number of parallel build workers (default: CPU count)
         #[arg(long, short = 'j')]
         parallel: Option<usize>,
+        /// Keep building projects that don't depend on a failed one
+        #[arg(long = "continue", conflicts_with = "bail")]
+        continue_on_error: bool,
+        /// Cancel running builds as soon as one fails
+        #[arg(long)]
+        bail: bool,
+        /// Retry failed builds N times with exponential backoff
+        #[arg(long, default_value_t = 0)]
+        retries: u32,
+        /// Write a Chrome trace of the build (open in chrome://tracing or ui.perfetto.dev)
+        #[arg(long, value_name = "PATH")]
+        profile: Option<std::path::PathBuf>,
+        /// Output of --affected/--filter builds: tui (default on a terminal), grouped (default in CI / when piped) or stream
+        #[arg(long, value_parser = ["auto", "tui", "grouped", "stream"], default_value = "auto")]
+        ui: String,
         /// Image name for Docker build (e.g., ghcr.io/org/app:tag)
         #[arg(long)]
         image: Option<String>,
         /// Push image to registry after build
         #[arg(long)]
         push: bool,
+        /// Image platforms, comma-separated (e.g., linux/amd64,linux/arm64)
+        /// If not specified, reads from manifest.toml [[app]] platforms (default: host)
+        #[arg(long, value_delimiter = ',')]
+        platform: Vec<String>,
+        /// Image builder: docker (buildx) or native (assemble OCI layers without a daemon;
+        /// node, bun and deno only)
+        #[arg(long, default_value = "docker", value_parser = ["docker", "native"])]
+        builder: String,
+        /// Base image OCI layout for --builder native (default: pulled with skopeo)
+        #[arg(long, value_name = "DIR")]
+        base_layout: Option<std::path::PathBuf>,
+        /// Also write the image to PATH for --builder native: an OCI layout, or an
+        /// OCI archive if PATH ends in .tar
+        #[arg(long, value_name = "PATH")]
+        oci_out: Option<std::path::PathBuf>,
+        /// Write SBOMs (cyclonedx, spdx; comma-separated) next to the cached image
+        #[arg(long, value_delimiter = ',', value_parser = ["cyclonedx", "spdx"])]
+        sbom: Vec<String>,
         /// Output directory for build context (for debugging)
         #[arg(long)]
         context_out: Option<std::path::PathBuf>,
         /// No cache for Docker build
         #[arg(long)]
         no_cache: bool,
-        /// Remote cache URL (s3://bucket/prefix or oci://registry/image)
+        /// Remote cache URL (s3://bucket/prefix, oci://registry/image, https://host or file:///dir)
         #[arg(long)]
         remote_cache: Option<String>,
+        /// Remote cache verification: strict (reject unverified artifacts) or warn
+        /// If not specified, reads from manifest.toml [remote_cache.verify]
+        #[arg(long, value_parser = ["strict", "warn"])]
+        remote_cache_verify: Option<String>,
         /// Build production Docker image (legacy)
         #[arg(long)]
         prod: bool,
@@ -265,18 +369,48 @@ enum Commands {
         /// Preview what would be deleted without actually deleting
         #[arg(long)]
         dry_run: bool,
+        /// Only clean inside matching packages (e.g., "apps/*", "...libs/ui")
+        #[arg(long, short = 'F')]
+        filter: Vec<String>,
     },
 
-    /// Generate deployment bundle (image.tar, artifact.tar.gz, bundle.json)
+    /// Generate deployment bundle (OCI image layout, artifact.tar.gz, bundle.json)
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Bundle {
+        #[command(subcommand)]
+        action: Option<BundleCommands>,
         /// Target project path (e.g., apps/web)
-        project: String,
+        #[arg(required_unless_present = "filter")]
+        project: Option<String>,
+        /// Bundle every matching package instead (e.g., "apps/*")
+        #[arg(long, short = 'F')]
+        filter: Vec<String>,
         /// Output directory (default: dist/)
         #[arg(short, long)]
         output: Option<std::path::PathBuf>,
         /// Generate Kubernetes manifests (deployment.yaml, service.yaml)
         #[arg(long)]
         k8s: bool,
+        /// Image platforms the build used (default: manifest.toml [[app]] platforms)
+        #[arg(long, value_delimiter = ',')]
+        platform: Vec<String>,
+        /// SBOM formats to include, comma-separated
+        #[arg(long, value_delimiter = ',', value_parser = ["cyclonedx", "spdx"], default_value = "cyclonedx,spdx")]
+        sbom: Vec<String>,
+        /// Sign the provenance with this key file (default: $AIRIS_PROVENANCE_KEY)
+        #[arg(long, value_name = "FILE")]
+        sign_key: Option<std::path::PathBuf>,
+        /// Run `airis build --docker` first if there is no cached build
+        #[arg(long, conflicts_with = "from_remote_cache")]
+        build: bool,
+        /// Fetch the build from this remote cache if there is no local one
+        /// (s3://bucket/prefix, oci://registry/image, https://host or file:///dir)
+        #[arg(long, value_name = "URL")]
+        from_remote_cache: Option<String>,
+        /// Remote cache verification: strict (reject unverified artifacts) or warn
+        /// If not specified, reads from manifest.toml [remote_cache.verify]
+        #[arg(long, value_parser = ["strict", "warn"], requires = "from_remote_cache")]
+        remote_cache_verify: Option<String>,
     },
 
     /// Run linting (alias for 'run lint')
@@ -291,10 +425,20 @@ enum Commands {
     /// Show Docker container status
     Ps,
 
-    /// View Docker logs
+    /// View Docker logs, or task logs recorded by `run`/`build` (--task, --runs)
     Logs {
         /// Service name (optional, defaults to all services)
+        #[arg(conflicts_with_all = ["task", "runs"])]
         service: Option<String>,
+        /// Replay a task's log (e.g., apps/web#build) from the latest run that has it
+        #[arg(long, conflicts_with = "runs")]
+        task: Option<String>,
+        /// Run id to replay from (see --runs)
+        #[arg(long, requires = "task")]
+        run: Option<String>,
+        /// List recorded runs
+        #[arg(long)]
+        runs: bool,
         /// Follow log output
         #[arg(short, long)]
         follow: bool,
@@ -353,6 +497,15 @@ enum Commands {
         /// Head branch/commit (default: HEAD)
         #[arg(long, default_value = "HEAD")]
         head: String,
+        /// Output as JSON (same as --format json)
+        #[arg(long)]
+        json: bool,
+        /// Output format: text, json, paths, names, github-matrix
+        #[arg(long, value_parser = ["text", "json", "paths", "names", "github-matrix"])]
+        format: Option<String>,
+        /// Only list packages that define this task (added to each entry)
+        #[arg(long)]
+        task: Option<String>,
     },
 
     /// Generate code and types from various sources
@@ -374,6 +527,53 @@ enum Commands {
     },
 }
 
+#[derive(Subcommand)]
+enum BundleCommands {
+    /// Check a bundle's files against its provenance
+    Verify {
+        /// Bundle directory (e.g., dist/api)
+        dir: std::path::PathBuf,
+        /// Signing key file (default: $AIRIS_PROVENANCE_KEY); required for signed bundles
+        #[arg(long, value_name = "FILE")]
+        key: Option<std::path::PathBuf>,
+        /// Accept a signed bundle without a key, leaving the signature unchecked
+        #[arg(long)]
+        allow_unsigned: bool,
+    },
+    /// Verify a bundle, docker load its image and tag it (Docker 25+)
+    Load {
+        /// Bundle directory (e.g., dist/api)
+        dir: std::path::PathBuf,
+        /// Tag for the loaded image (default: bundle.json image_ref)
+        #[arg(long)]
+        tag: Option<String>,
+        /// Platform to load from a multi-platform image
+        #[arg(long)]
+        platform: Option<String>,
+        /// Signing key file (default: $AIRIS_PROVENANCE_KEY)
+        #[arg(long, value_name = "FILE")]
+        key: Option<std::path::PathBuf>,
+        /// Accept a signed bundle without a key, leaving the signature unchecked
+        #[arg(long)]
+        allow_unsigned: bool,
+        /// Load without verifying the bundle first
+        #[arg(long)]
+        no_verify: bool,
+        /// Run a container and probe its health endpoint
+        #[arg(long)]
+        smoke: bool,
+        /// Health endpoint path for --smoke
+        #[arg(long, default_value = "/health")]
+        health: String,
+        /// Container port for --smoke
+        #[arg(long, default_value_t = 3000)]
+        port: u16,
+        /// Seconds to wait for the health probe
+        #[arg(long, default_value_t = 60)]
+        timeout: u64,
+    },
+}
+
 #[derive(Subcommand)]
 enum PolicyCommands {
     /// Initialize .airis/policies.toml
@@ -399,7 +599,11 @@ enum DepsCommands {
     /// Show dependencies for a specific package
     Show {
         /// Package path or name (e.g., apps/web, libs/ui)
-        package: String,
+        #[arg(required_unless_present = "filter")]
+        package: Option<String>,
+        /// Show every matching package instead (e.g., "...libs/ui")
+        #[arg(long, short = 'F')]
+        filter: Vec<String>,
     },
     /// Check for circular dependencies and architecture violations
     Check,
@@ -653,7 +857,22 @@ fn main() -> Result<()> {
                 commands::sync_deps::run()?;
             }
         }
-        Commands::Run { task } => commands::run::run(&task)?,
+        Commands::Run { task, parallel, no_cache, affected, base, head, filter, workers, continue_on_error, bail, retries, profile, watch, ui } => {
+            let options = commands::run::RunOptions {
+                parallel,
+                no_cache,
+                affected: affected.then_some((base, head)),
+                filter,
+                workers,
+                failure_policy: failure_policy(continue_on_error, bail),
+                retries,
+                profile,
+                watch,
+                ui: task_ui::UiMode::parse(&ui),
+            };
+            commands::run::run_with_options(&task, &options)?
+        }
+        Commands::Worker { listen, jobs } => commands::worker::run(listen.as_deref(), jobs)?,
         Commands::Up => commands::run::run("up")?,
         Commands::Down => commands::run::run("down")?,
         Commands::Shell => commands::run::run("shell")?,
@@ -666,131 +885,212 @@ fn main() -> Result<()> {
             }
         }
         Commands::Install => commands::run::run("install")?,
-        Commands::Build { project, affected, base, head, docker, channel, targets, parallel, image, push, context_out, no_cache, remote_cache, prod, quick } => {
-            if affected && docker {
-                // Parallel build for affected projects
+        Commands::Build { project, affected, base, head, filter, docker, channel, targets, parallel, continue_on_error, bail, retries, profile, ui, image, push, platform, builder, base_layout, oci_out, sbom, context_out, no_cache, remote_cache, remote_cache_verify, prod, quick } => {
+            let builder = docker_build::Builder::parse(&builder)?;
+            let sbom = sbom.iter().map(|s| sbom::SbomFormat::parse(s)).collect::<anyhow::Result<Vec<_>>>()?;
+            if (affected || !filter.is_empty()) && docker {
+                // Parallel build for affected / filtered projects
                 use colored::Colorize;
-                let affected_projects = commands::affected::run(&base, &head)?;
+                if oci_out.is_some() {
+                    anyhow::bail!("--oci-out writes one image; pass a single project");
+                }
+                let root = std::env::current_dir()?;
+                let dag = workspace_graph::load(&root)?;
+
+                let mut selected_projects = if affected {
+                    commands::affected::run(&base, &head)?
+                } else {
+                    dag.ids()
+                };
+                if !filter.is_empty() {
+                    let filtered = filter::resolve(&dag, &filter)?;
+                    selected_projects.retain(|p| filtered.contains(p));
+                }
 
-                if affected_projects.is_empty() {
-                    println!("{}", "✅ No affected projects to build".green());
+                if selected_projects.is_empty() {
+                    println!("{}", "✅ No projects to build".green());
                 } else {
                     let worker_count = parallel.unwrap_or_else(executor::default_parallelism);
-                    let root = std::env::current_dir()?;
                     let remote = remote_cache.as_ref().map(|url| remote_cache::Remote::parse(url)).transpose()?;
+                    let signing = remote_cache::Signing::load(&root, parse_verify_mode(remote_cache_verify.as_deref()))?;
+                    if remote.is_some() {
+                        signing.require_key()?;
+                    }
 
                     // Build task list
-                    let mut exec = executor::ParallelExecutor::new(worker_count);
-
-                    for proj in &affected_projects {
-                        let target = convert_package_to_path(proj);
-                        let resolved_channel = resolve_channel_for_project(channel.clone(), &target);
-
-                        // Get dependencies from DAG
-                        let deps: Vec<String> = {
-                            let lock_path = root.join("pnpm-lock.yaml");
-                            if let Ok(lock) = pnpm::PnpmLock::load(&lock_path) {
-                                let workspace_map = pnpm::build_workspace_map(&lock);
-                                let dag = dag::build_dag(&workspace_map);
-                                dag.nodes.get(&target)
-                                    .map(|n| n.deps.iter()
-                                        .filter(|d| affected_projects.iter().any(|ap| convert_package_to_path(ap) == **d))
-                                        .cloned()
-                                        .collect())
-                                    .unwrap_or_default()
-                            } else {
-                                vec![]
-                            }
-                        };
-
-                        exec.add_task(executor::BuildTask {
+                    let mut history = task_history::TaskHistory::load(&root);
+                    let run_log = std::sync::Arc::new(task_logs::RunLog::start(&root, "airis build --docker")?);
+                    let mut exec = executor::ParallelExecutor::new(worker_count)
+                        .failure_policy(failure_policy(continue_on_error, bail))
+                        .estimates(history.estimates());
+
+                    let mut tasks = Vec::new();
+                    for target in &selected_projects {
+                        let resolved_channel = resolve_channel_for_project(channel.clone(), target);
+
+                        // Only wait on dependencies that are being rebuilt too
+                        let deps: Vec<String> = dag.nodes.get(target)
+                            .map(|n| n.deps.iter()
+                                .filter(|d| selected_projects.contains(d))
+                                .cloned()
+                                .collect())
+                            .unwrap_or_default();
+
+                        let task = executor::BuildTask {
                             id: target.clone(),
                             target: target.clone(),
                             channel: resolved_channel,
                             dependencies: deps,
-                        });
+                            retries,
+                            weight: docker_build::DOCKER_BUILD_WEIGHT,
+                        };
+                        tasks.push(task.clone());
+                        exec.add_task(task);
+                    }
+
+                    let task_ids: Vec<String> = tasks.iter().map(|t| t.id.clone()).collect();
+                    let ui = task_ui::TaskUi::start(task_ui::UiMode::parse(&ui), "airis build --docker", &task_ids, exec.canceller())?;
+                    let events = ui.as_ref().map(|ui| ui.events());
+                    if let Some(events) = &events {
+                        exec = exec.events(events.clone());
                     }
 
                     // Execute in parallel
                     let root_clone = root.clone();
                     let image_clone = image.clone();
+                    let platform_clone = platform.clone();
+                    let base_layout_clone = base_layout.clone();
+                    let sbom_clone = sbom.clone();
                     let context_out_clone = context_out.clone();
                     let remote_clone = remote.clone();
+                    let signing_clone = signing.clone();
+                    let run_log_clone = std::sync::Arc::clone(&run_log);
 
                     let rt = tokio::runtime::Runtime::new()?;
-                    let results = rt.block_on(async {
-                        exec.execute(move |task| {
-                            let root = root_clone.clone();
-                            let image = image_clone.clone();
-                            let context_out = context_out_clone.clone();
-                            let remote = remote_clone.clone();
-
-                            async move {
-                                let start = std::time::Instant::now();
-
-                                // Check cache first
-                                let hash = docker_build::compute_content_hash(&root, &task.target)?;
+                    let build_fn = move |task: executor::BuildTask| {
+                        let root = root_clone.clone();
+                        let image = image_clone.clone();
+                        let platform = platform_clone.clone();
+                        let base_layout = base_layout_clone.clone();
+                        let sbom = sbom_clone.clone();
+                        let context_out = context_out_clone.clone();
+                        let remote = remote_clone.clone();
+                        let signing = signing_clone.clone();
+                        let run_log = std::sync::Arc::clone(&run_log_clone);
+                        let console = events
+                            .clone()
+                            .map(|events| task_logs::Console::task(&task.id, events))
+                            .unwrap_or_default();
+
+                        async move {
+                            let start = std::time::Instant::now();
+
+                            // Check cache first
+                            let platforms = docker_build::resolve_platforms(&root, &task.target, &platform)?;
+                            let hash = docker_build::compute_content_hash(&root, &task.target)?;
+                            let hash = docker_build::platform_hash(&hash, &platforms);
+
+                            if let Some(_artifact) = docker_build::cache_hit(&task.target, &hash) {
+                                return Ok(executor::TaskResult {
+                                    task_id: task.id,
+                                    success: true,
+                                    duration_ms: start.elapsed().as_millis() as u64,
+                                    error: None,
+                                    cached: true,
+                                    skipped: false,
+                                });
+                            }
 
-                                if let Some(_artifact) = docker_build::cache_hit(&task.target, &hash) {
+                            // Check remote cache
+                            if let Some(ref remote) = remote
+                                && let Some(artifact) = remote_cache::remote_hit(&task.target, &hash, remote, &signing)? {
+                                    docker_build::cache_store(&task.target, &hash, &artifact)?;
                                     return Ok(executor::TaskResult {
                                         task_id: task.id,
                                         success: true,
                                         duration_ms: start.elapsed().as_millis() as u64,
                                         error: None,
+                                        cached: true,
+                                        skipped: false,
                                     });
                                 }
 
-                                // Check remote cache
-                                if let Some(ref remote) = remote
-                                    && let Some(artifact) = remote_cache::remote_hit(&task.target, &hash, remote)? {
-                                        docker_build::cache_store(&task.target, &hash, &artifact)?;
-                                        return Ok(executor::TaskResult {
-                                            task_id: task.id,
-                                            success: true,
-                                            duration_ms: start.elapsed().as_millis() as u64,
-                                            error: None,
-                                        });
-                                    }
-
-                                // Build
-                                let config = docker_build::BuildConfig {
-                                    target: task.target.clone(),
-                                    image_name: image,
-                                    push,
-                                    no_cache,
-                                    context_out,
-                                    channel: task.channel.clone(),
-                                    ..Default::default()
-                                };
-
-                                let result = docker_build::docker_build(&root, config)?;
-
-                                // Store cache
-                                let artifact = docker_build::CachedArtifact {
-                                    image_ref: result.image_ref.clone(),
-                                    hash: hash.clone(),
-                                    built_at: chrono::Utc::now().to_rfc3339(),
-                                    target: task.target.clone(),
-                                };
-                                docker_build::cache_store(&task.target, &hash, &artifact)?;
-
-                                if let Some(ref remote) = remote {
-                                    remote_cache::remote_store(&task.target, &hash, &artifact, remote)?;
-                                }
+                            // Build
+                            let config = docker_build::BuildConfig {
+                                target: task.target.clone(),
+                                image_name: image,
+                                push,
+                                no_cache,
+                                context_out,
+                                channel: task.channel.clone(),
+                                log: Some(run_log.path(&task.id)),
+                                platforms,
+                                builder,
+                                base_layout,
+                                sbom,
+                                console,
+                                ..Default::default()
+                            };
+
+                            let result = docker_build::docker_build(&root, config)?;
+
+                            // Store cache
+                            let artifact = docker_build::CachedArtifact::new(&task.target, &hash, &result);
+                            docker_build::cache_store(&task.target, &hash, &artifact)?;
+
+                            if let Some(ref remote) = remote {
+                                remote_cache::remote_store(&task.target, &hash, &artifact, remote, &signing)?;
+                            }
 
-                                Ok(executor::TaskResult {
-                                    task_id: task.id,
-                                    success: true,
-                                    duration_ms: start.elapsed().as_millis() as u64,
-                                    error: None,
-                                })
+                            Ok(executor::TaskResult {
+                                task_id: task.id,
+                                success: true,
+                                duration_ms: start.elapsed().as_millis() as u64,
+                                error: None,
+                                cached: false,
+                                skipped: false,
+                            })
+                        }
+                    };
+                    let mut results = rt.block_on(exec.execute(build_fn.clone()))?;
+
+                    // TUI: rebuild failed projects on request until the user quits
+                    if let Some(ui) = &ui {
+                        while let Some(task_ui::UiCommand::Rerun(task_id)) = ui.next_command() {
+                            let mut rerun = executor::ParallelExecutor::new(worker_count)
+                                .failure_policy(failure_policy(continue_on_error, bail))
+                                .events(ui.events());
+                            for task in commands::run::rerun_tasks(&tasks, &results, &task_id) {
+                                rerun.add_task(task);
+                            }
+                            for result in rt.block_on(rerun.execute(build_fn.clone()))? {
+                                match results.iter_mut().find(|r| r.task_id == result.task_id) {
+                                    Some(previous) => *previous = result,
+                                    None => results.push(result),
+                                }
                             }
-                        }).await
-                    })?;
+                        }
+                    }
+                    if let Some(ui) = ui {
+                        let tui = ui.is_tui();
+                        ui.finish()?;
+                        if tui {
+                            executor::print_summary(&results, results.len());
+                        }
+                    }
+
+                    history.record(&results);
+                    history.save(&root)?;
+                    run_log.finish(&results)?;
+
+                    if let Some(path) = &profile {
+                        profile::write(path, "airis build --docker", &exec, &results)?;
+                    }
 
-                    let failed: Vec<_> = results.iter().filter(|r| !r.success).collect();
-                    if !failed.is_empty() {
-                        anyhow::bail!("{} build(s) failed", failed.len());
+                    let failed = results.iter().filter(|r| !r.success && !r.skipped).count();
+                    let skipped = results.iter().filter(|r| r.skipped).count();
+                    if failed + skipped > 0 {
+                        anyhow::bail!("{} build(s) failed, {} skipped", failed, skipped);
                     }
                 }
             } else if docker {
@@ -813,6 +1113,20 @@ fn main() -> Result<()> {
 
                 // Parse remote cache URL if provided
                 let remote = remote_cache.as_ref().map(|url| remote_cache::Remote::parse(url)).transpose()?;
+                let signing = remote_cache::Signing::load(&root, parse_verify_mode(remote_cache_verify.as_deref()))?;
+                if remote.is_some() {
+                    signing.require_key()?;
+                }
+
+                // Validate before starting the run log, so failed runs don't leave empty entries
+                for build_channel in &build_targets {
+                    channel::RuntimeChannel::parse(build_channel)?;
+                }
+                let (lock, dag) = docker_build::load_workspace(&root, &target)?;
+
+                let platforms = docker_build::resolve_platforms(&root, &target, &platform)?;
+                let run_log = task_logs::RunLog::start(&root, "airis build --docker")?;
+                let mut logged = Vec::new();
 
                 if build_targets.len() > 1 {
                     println!("{}", "==================================".bright_blue());
@@ -829,8 +1143,8 @@ fn main() -> Result<()> {
 
                     // Calculate content hash for cache lookup (includes channel in hash)
                     let base_hash = docker_build::compute_content_hash(&root, &target)?;
-                    let hash = format!("{}-{}", base_hash, build_channel);
-                    let final_hash = blake3::hash(hash.as_bytes()).to_hex()[..12].to_string();
+                    let base_hash = docker_build::platform_hash(&base_hash, &platforms);
+                    let final_hash = docker_build::channel_hash(&base_hash, build_channel);
 
                     // Check local cache first
                     if let Some(artifact) = docker_build::cache_hit(&target, &final_hash) {
@@ -840,7 +1154,7 @@ fn main() -> Result<()> {
 
                     // Check remote cache if configured
                     if let Some(ref remote) = remote
-                        && let Some(artifact) = remote_cache::remote_hit(&target, &final_hash, remote)? {
+                        && let Some(artifact) = remote_cache::remote_hit(&target, &final_hash, remote, &signing)? {
                             println!("{}", format!("  ✅ Remote cache hit: {}", artifact.image_ref).green());
                             // Store to local cache for next time
                             docker_build::cache_store(&target, &final_hash, &artifact)?;
@@ -860,6 +1174,11 @@ fn main() -> Result<()> {
                         image.clone()
                     };
 
+                    let task_id = if build_targets.len() > 1 {
+                        format!("{}#build-{}", target, build_channel)
+                    } else {
+                        target.clone()
+                    };
                     let config = docker_build::BuildConfig {
                         target: target.clone(),
                         image_name: target_image_name,
@@ -867,23 +1186,36 @@ fn main() -> Result<()> {
                         no_cache,
                         context_out: context_out.clone(),
                         channel: build_channel.clone(),
+                        log: Some(run_log.path(&task_id)),
+                        platforms: platforms.clone(),
+                        builder,
+                        base_layout: base_layout.clone(),
+                        oci_out: oci_out.clone(),
+                        sbom: sbom.clone(),
                         ..Default::default()
                     };
-                    let result = docker_build::docker_build(&root, config)?;
+                    let start = std::time::Instant::now();
+                    let built = docker_build::docker_build_in(&root, config, &lock, &dag);
+                    // Index the log before bailing out, that's when it's needed
+                    logged.push(executor::TaskResult {
+                        task_id,
+                        success: built.is_ok(),
+                        duration_ms: start.elapsed().as_millis() as u64,
+                        error: built.as_ref().err().map(|e| e.to_string()),
+                        cached: false,
+                        skipped: false,
+                    });
+                    run_log.finish(&logged)?;
+                    let result = built?;
 
                     // Store to local cache
-                    let artifact = docker_build::CachedArtifact {
-                        image_ref: result.image_ref.clone(),
-                        hash: final_hash.clone(),
-                        built_at: chrono::Utc::now().to_rfc3339(),
-                        target: target.clone(),
-                    };
+                    let artifact = docker_build::CachedArtifact::new(&target, &final_hash, &result);
                     docker_build::cache_store(&target, &final_hash, &artifact)?;
 
                     // Store to remote cache if configured
                     if let Some(ref remote) = remote {
                         println!("{}", "  📤 Pushing to remote cache...".cyan());
-                        remote_cache::remote_store(&target, &final_hash, &artifact, remote)?;
+                        remote_cache::remote_store(&target, &final_hash, &artifact, remote, &signing)?;
                     }
                 }
 
@@ -901,19 +1233,81 @@ fn main() -> Result<()> {
                 })?;
                 commands::run::run_build_quick(app_name)?;
             } else {
-                commands::run::run("build")?;
+                let options = commands::run::RunOptions {
+                    parallel,
+                    no_cache,
+                    affected: affected.then_some((base, head)),
+                    filter,
+                    failure_policy: failure_policy(continue_on_error, bail),
+                    retries,
+                    profile,
+                    ..Default::default()
+                };
+                commands::run::run_with_options("build", &options)?;
+            }
+        }
+        Commands::Clean { dry_run, filter } => {
+            if filter.is_empty() {
+                commands::clean::run(dry_run)?
+            } else {
+                let dag = workspace_graph::load(std::path::Path::new("."))?;
+                commands::clean::run_packages(dry_run, &filter::resolve(&dag, &filter)?)?
             }
         }
-        Commands::Clean { dry_run } => commands::clean::run(dry_run)?,
-        Commands::Bundle { project, output, k8s } => {
-            commands::bundle::run(&project, output.as_deref(), k8s)?;
+        Commands::Bundle { action: Some(BundleCommands::Verify { dir, key, allow_unsigned }), .. } => {
+            commands::bundle::verify(&dir, key.as_deref(), allow_unsigned)?;
+        }
+        Commands::Bundle {
+            action: Some(BundleCommands::Load { dir, tag, platform, key, allow_unsigned, no_verify, smoke, health, port, timeout }),
+            ..
+        } => {
+            let options = commands::bundle::LoadOptions {
+                tag,
+                platform,
+                key,
+                allow_unsigned,
+                skip_verify: no_verify,
+                smoke,
+                health,
+                port,
+                timeout,
+            };
+            commands::bundle::load(&dir, &options)?;
+        }
+        Commands::Bundle { action: None, project, filter, output, k8s, platform, sbom, sign_key, build, from_remote_cache, remote_cache_verify } => {
+            let sbom = sbom.iter().map(|s| sbom::SbomFormat::parse(s)).collect::<anyhow::Result<Vec<_>>>()?;
+            let projects = match project {
+                Some(project) => vec![project],
+                None => filter::resolve(&workspace_graph::load(std::path::Path::new("."))?, &filter)?,
+            };
+            let on_miss = if let Some(url) = from_remote_cache {
+                let signing = remote_cache::Signing::load(
+                    &std::env::current_dir()?,
+                    parse_verify_mode(remote_cache_verify.as_deref()),
+                )?;
+                signing.require_key()?;
+                commands::bundle::OnCacheMiss::Remote { remote: remote_cache::Remote::parse(&url)?, signing }
+            } else if build {
+                commands::bundle::OnCacheMiss::Build
+            } else {
+                commands::bundle::OnCacheMiss::Fail
+            };
+            for project in &projects {
+                commands::bundle::run(project, output.as_deref(), k8s, &platform, &sbom, sign_key.as_deref(), &on_miss)?;
+            }
         }
         Commands::Lint => commands::run::run("lint")?,
         Commands::Format => commands::run::run("format")?,
         Commands::Typecheck => commands::run::run("typecheck")?,
         Commands::Ps => commands::run::run("ps")?,
-        Commands::Logs { service, follow, tail } => {
-            commands::run::run_logs(service.as_deref(), follow, tail)?
+        Commands::Logs { service, task, run, runs, follow, tail } => {
+            if let Some(task) = task {
+                task_logs::replay(&task, run.as_deref())?
+            } else if runs {
+                task_logs::list_runs()?
+            } else {
+                commands::run::run_logs(service.as_deref(), follow, tail)?
+            }
         }
         Commands::Exec { service, cmd } => {
             commands::run::run_exec(&service, &cmd)?
@@ -949,8 +1343,13 @@ fn main() -> Result<()> {
                 }
             }
         }
-        Commands::Affected { base, head } => {
-            commands::affected::run(&base, &head)?;
+        Commands::Affected { base, head, json, format, task } => {
+            let format = match format {
+                Some(f) => commands::affected::OutputFormat::parse(&f)?,
+                None if json => commands::affected::OutputFormat::Json,
+                None => commands::affected::OutputFormat::Text,
+            };
+            commands::affected::output(&base, &head, format, task.as_deref())?;
         }
         Commands::Generate { action } => match action {
             GenerateCommands::Files { dry_run } => {
@@ -998,7 +1397,10 @@ fn main() -> Result<()> {
         Commands::Deps { action } => match action {
             DepsCommands::Tree => commands::deps::tree()?,
             DepsCommands::Json => commands::deps::json()?,
-            DepsCommands::Show { package } => commands::deps::show(&package)?,
+            DepsCommands::Show { package, filter } => match package {
+                Some(package) => commands::deps::show(&package)?,
+                None => commands::deps::show_filtered(&filter)?,
+            },
             DepsCommands::Check => commands::deps::check()?,
         },
     }
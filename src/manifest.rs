@@ -62,6 +62,9 @@ pub struct Manifest {
     /// User-defined commands (airis run <task>)
     #[serde(default)]
     pub commands: IndexMap<String, String>,
+    /// Per-package task pipeline (airis run <task> over the workspace DAG)
+    #[serde(default)]
+    pub tasks: IndexMap<String, TaskConfig>,
     /// LLM command remapping (e.g., "npm install" → "airis install")
     #[serde(default)]
     pub remap: IndexMap<String, String>,
@@ -83,6 +86,12 @@ pub struct Manifest {
     /// Environment variable validation
     #[serde(default)]
     pub env: EnvSection,
+    /// Remote cache signing and verification
+    #[serde(default)]
+    pub remote_cache: RemoteCacheSection,
+    /// Affected package detection (airis affected, build --affected)
+    #[serde(default)]
+    pub affected: AffectedSection,
 }
 
 impl Manifest {
@@ -96,6 +105,14 @@ impl Manifest {
         Ok(manifest)
     }
 
+    /// `[[app]]` entry for a project path (matched by path or directory name)
+    pub fn find_app(&self, project: &str) -> Option<&ProjectDefinition> {
+        let dir_name = project.rsplit('/').next().unwrap_or(project);
+        self.app
+            .iter()
+            .find(|app| app.path.as_deref() == Some(project) || app.name == dir_name || app.name == project)
+    }
+
     pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
         let content = toml::to_string_pretty(self)
             .with_context(|| "Failed to serialize manifest.toml contents")?;
@@ -260,6 +277,7 @@ impl Manifest {
                 cmds.insert("ps".to_string(), "docker compose ps".to_string());
                 cmds
             },
+            tasks: IndexMap::new(),
             remap,
             versioning: VersioningSection {
                 strategy: VersioningStrategy::Manual,
@@ -270,6 +288,8 @@ impl Manifest {
             templates: TemplatesSection::default(),
             runtimes: RuntimesSection::default(),
             env: EnvSection::default(),
+            remote_cache: RemoteCacheSection::default(),
+            affected: AffectedSection::default(),
         }
     }
 }
@@ -762,6 +782,108 @@ pub struct EnvValidation {
     pub example: Option<String>,
 }
 
+/// Affected detection section
+/// Example:
+/// ```toml
+/// [affected]
+/// global_inputs = ["tsconfig.base.json", "manifest.toml", "pnpm-workspace.yaml", ".github/workflows/*"]
+/// ```
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct AffectedSection {
+    /// Root files (globs) whose change affects every package
+    /// pnpm-lock.yaml is diffed per importer instead of listed here
+    #[serde(default = "default_global_inputs")]
+    pub global_inputs: Vec<String>,
+}
+
+impl Default for AffectedSection {
+    fn default() -> Self {
+        AffectedSection {
+            global_inputs: default_global_inputs(),
+        }
+    }
+}
+
+fn default_global_inputs() -> Vec<String> {
+    vec![
+        "tsconfig.base.json".to_string(),
+        "manifest.toml".to_string(),
+        "pnpm-workspace.yaml".to_string(),
+    ]
+}
+
+/// Remote cache signing section
+/// Example:
+/// ```toml
+/// [remote_cache]
+/// signing_key_env = "AIRIS_REMOTE_CACHE_KEY"  # env var holding the HMAC-SHA256 key
+/// verify = "strict"                           # or "warn"
+/// ```
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct RemoteCacheSection {
+    /// Environment variable holding the signing key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_key_env: Option<String>,
+    /// File holding the signing key (relative to the workspace root)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_key_file: Option<String>,
+    /// What to do with artifacts that fail verification
+    /// (default: strict once a signing key is available, warn until then)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verify: Option<RemoteCacheVerify>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RemoteCacheVerify {
+    /// Reject unverified artifacts (treated as cache miss)
+    Strict,
+    /// Use unverified artifacts with a warning
+    #[default]
+    Warn,
+}
+
+/// Per-package task definition
+/// Example:
+/// ```toml
+/// [tasks.build]
+/// depends_on = ["^build"]
+///
+/// [tasks.test]
+/// depends_on = ["build"]
+/// command = "vitest run"
+/// inputs = ["src/**", "package.json"]
+/// ```
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct TaskConfig {
+    /// Tasks that must finish first:
+    /// - "^build" → `build` in every workspace dependency of the package
+    /// - "lint" → `lint` in the same package
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Shell command run in the package directory
+    /// (default: `<package_manager> run <task>`, skipped if the script is missing)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// Input globs relative to the package (e.g., ["src/**", "package.json"])
+    /// Declaring inputs enables local caching with log replay
+    #[serde(default)]
+    pub inputs: Vec<String>,
+    /// Output globs relative to the package, restored on cache hit (e.g., ["dist/**"])
+    #[serde(default)]
+    pub outputs: Vec<String>,
+    /// Environment variables whose values feed the cache key (e.g., ["NODE_ENV"])
+    #[serde(default)]
+    pub env: Vec<String>,
+    /// Extra attempts after a failure, with exponential backoff (default: `--retries`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+    /// Worker slots the task occupies while running (default: 1)
+    /// Raise it for heavy tasks so fewer run alongside them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight: Option<usize>,
+}
+
 /// Runtime configuration for Docker builds
 #[derive(Debug, Deserialize, Serialize, Clone, Default)]
 pub struct RuntimeConfig {
@@ -807,6 +929,18 @@ pub struct ProjectDefinition {
     /// Runtime configuration for Docker builds
     #[serde(skip_serializing_if = "Option::is_none")]
     pub runner: Option<RuntimeConfig>,
+    /// Globs (relative to the project) that make up the Docker build cache key;
+    /// empty: every file not in .gitignore/.airisignore
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inputs: Vec<String>,
+    /// Handlebars Dockerfile template for `airis build --docker`, relative to the
+    /// workspace root (default: templates/docker/<family>.hbs, then built-in)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dockerfile_template: Option<String>,
+    /// Image platforms for `airis build --docker` (e.g., ["linux/amd64", "linux/arm64"]);
+    /// empty: the host platform. `--platform` overrides it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub platforms: Vec<String>,
     #[serde(default)]
     pub scripts: IndexMap<String, String>,
     #[serde(default)]
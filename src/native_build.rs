@@ -0,0 +1,725 @@
+//! Daemonless image builds: `airis build --docker --builder native`
+//!
+//! Node, Bun and Deno images are a runtime base image plus the app, so they
+//! can be assembled without BuildKit or a Docker daemon (e.g., on rootless CI
+//! runners):
+//!
+//! 1. the pruned build context is installed and built on the host
+//!    (`pnpm install` + `pnpm build`, `bun install` + `bun run build`, `deno cache`)
+//! 2. the result (for Next.js, its standalone server) is packed into one layer under `/app`
+//! 3. a config and manifest are written on top of the base image's layers,
+//!    read from an OCI layout (`--base-layout`, else pulled with `skopeo`)
+//!
+//! The image lands in an OCI layout in the build cache, like multi-platform
+//! `docker buildx` builds, so `airis bundle` ships it as is. Dependencies are
+//! installed on the host, so native modules need the host to match the image
+//! (same libc and architecture); other platforms are refused.
+
+use anyhow::{bail, Context, Result};
+use flate2::write::GzEncoder;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::channel::{RuntimeFamily, Toolchain};
+use crate::docker_build::{cache_dir, copy_dir_recursive, detect_nextjs, image_name, BuildConfig, BuildResult};
+use crate::oci::{self, Descriptor, Platform};
+use crate::task_logs::{tee_command, Console};
+
+/// What goes on top of the base image
+#[derive(Debug, Clone)]
+pub struct ImageSpec {
+    /// Image name recorded in the layout's index.json (e.g., "web:airis-abc123")
+    pub name: String,
+    pub platform: Platform,
+    pub workdir: String,
+    /// "KEY=value", replacing the base image's value of KEY
+    pub env: Vec<String>,
+    pub cmd: Vec<String>,
+    pub port: u16,
+}
+
+/// Build `config.target` from its context without Docker
+pub fn build(
+    ctx_dir: &Path,
+    config: &BuildConfig,
+    toolchain: &Toolchain,
+    hash: &str,
+    port: u16,
+) -> Result<BuildResult> {
+    let start = std::time::Instant::now();
+
+    if !matches!(toolchain.family, RuntimeFamily::Node | RuntimeFamily::Bun | RuntimeFamily::Deno) {
+        bail!(
+            "--builder native supports the node, bun and deno families (got {})",
+            toolchain.family.as_str()
+        );
+    }
+    let platform = match config.platforms.as_slice() {
+        [] => Platform::host(),
+        [platform] => Platform::parse(platform)?,
+        platforms => bail!(
+            "--builder native builds one platform at a time (got {})",
+            platforms.join(", ")
+        ),
+    };
+    // Dependencies (and their native modules) are installed for the host
+    if !Platform::host().matches(&platform) {
+        bail!(
+            "--builder native can only build for the host platform ({}), not {}; \
+             use the Docker builder for other platforms",
+            Platform::host(),
+            platform
+        );
+    }
+    let nextjs = toolchain.family == RuntimeFamily::Node && detect_nextjs(&config.target);
+
+    let image_name = image_name(config, hash);
+    config.console.say(format!("🧱 Assembling image: {} ({})", image_name, platform));
+    config.console.say(format!("   Context: {}", ctx_dir.display()));
+
+    let base = base_layout(toolchain, config.base_layout.as_deref(), &config.console)?;
+    let mut app = prepare_app(ctx_dir, config, toolchain.family)?;
+    if nextjs {
+        app = nextjs_standalone(ctx_dir, &app, &config.target)?;
+    }
+    let spec = image_spec(&config.target, toolchain.family, nextjs, image_name.clone(), platform, port);
+    let layout = cache_dir(&config.target, hash).join("oci");
+    let manifest = assemble(&base, &app, &layout, &spec)?;
+
+    if let Some(out) = &config.oci_out {
+        export(&layout, out)?;
+        config.console.say(format!("   Wrote {}", out.display()));
+    }
+    if config.push {
+        push(&layout, &image_name, &config.console)?;
+    }
+
+    let platform = manifest.platform.as_ref().unwrap_or(&spec.platform).to_string();
+    Ok(BuildResult {
+        image_ref: image_name,
+        hash: hash.to_string(),
+        duration_secs: start.elapsed().as_secs(),
+        platforms: BTreeMap::from([(platform, manifest.digest)]),
+        index_digest: None,
+        oci_layout: Some(layout),
+    })
+}
+
+/// OCI layout of the toolchain image: `explicit` (`--base-layout`), else
+/// `~/.airis/.cache/base/<image>/`, pulled with `skopeo` on first use
+pub fn base_layout(toolchain: &Toolchain, explicit: Option<&Path>, console: &Console) -> Result<PathBuf> {
+    if let Some(path) = explicit {
+        if !path.join("index.json").is_file() {
+            bail!("Not an OCI image layout: {} (no index.json)", path.display());
+        }
+        return Ok(path.to_path_buf());
+    }
+
+    let image_ref = match &toolchain.digest {
+        Some(digest) => format!("{}@{}", toolchain.image, digest),
+        None => toolchain.image.clone(),
+    };
+    let dir = cache_dir("base", &image_ref.replace(['/', ':', '@'], "_"));
+    if dir.join("index.json").is_file() {
+        return Ok(dir);
+    }
+
+    console.say(format!("📥 Pulling base image {}", image_ref));
+    fs::create_dir_all(&dir)?;
+    let status = tee_command(
+        Command::new("skopeo")
+            .args(["copy", "--all"])
+            .arg(format!("docker://{}", image_ref))
+            .arg(format!("oci:{}", dir.display())),
+        None,
+        console,
+    );
+    match status {
+        Ok(status) if status.success() => Ok(dir),
+        Ok(status) => {
+            let _ = fs::remove_dir_all(&dir);
+            bail!("skopeo copy of {} failed with exit code: {:?}", image_ref, status.code())
+        }
+        Err(_) => {
+            let _ = fs::remove_dir_all(&dir);
+            bail!(
+                "--builder native needs {} as an OCI layout: install skopeo, or pass --base-layout <dir> \
+                 (e.g., from `skopeo copy docker://{} oci:<dir>`)",
+                image_ref,
+                image_ref
+            )
+        }
+    }
+}
+
+/// Copy the context into `<ctx>/native/app` and install/build it on the host,
+/// like the Dockerfile's build stage would
+fn prepare_app(ctx_dir: &Path, config: &BuildConfig, family: RuntimeFamily) -> Result<PathBuf> {
+    let app = ctx_dir.join("native").join("app");
+    if app.exists() {
+        fs::remove_dir_all(&app)?;
+    }
+    // json/ first: with a bun.lock it also has the manifests of packages outside full/
+    copy_dir_recursive(&ctx_dir.join("json"), &app)?;
+    copy_dir_recursive(&ctx_dir.join("full"), &app)?;
+    for file in ["pnpm-lock.yaml", "pnpm-workspace.yaml"] {
+        if ctx_dir.join(file).exists() {
+            fs::copy(ctx_dir.join(file), app.join(file))?;
+        }
+    }
+
+    let log = match &config.log {
+        Some(path) => {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            Some(fs::File::create(path).with_context(|| format!("Failed to create {}", path.display()))?)
+        }
+        None => None,
+    };
+    let package = app.join(&config.target);
+
+    match family {
+        RuntimeFamily::Bun => {
+            if !app.join("bun.lock").exists() && !app.join("bun.lockb").exists() {
+                bail!(
+                    "--builder native installs Bun apps from bun.lock (or bun.lockb) at the workspace root; none found"
+                );
+            }
+            run_step(
+                Command::new("bun").args(["install", "--frozen-lockfile"]).current_dir(&app),
+                log.as_ref(),
+                &config.console,
+            )
+            .context("Frozen bun install failed (is bun.lock up to date with the workspace's package.json files?)")?;
+            run_step(
+                Command::new("bun")
+                    .args(["run", "build"])
+                    .envs(&config.build_args)
+                    .env("NODE_ENV", "production")
+                    .current_dir(&package),
+                log.as_ref(),
+                &config.console,
+            )?;
+        }
+        RuntimeFamily::Deno => {
+            run_step(
+                Command::new("deno")
+                    .args(["cache", "src/main.ts"])
+                    .env("DENO_DIR", app.join(".deno"))
+                    .current_dir(&package),
+                log.as_ref(),
+                &config.console,
+            )?;
+        }
+        _ => {
+            run_step(
+                Command::new("pnpm").args(["install", "--frozen-lockfile"]).current_dir(&app),
+                log.as_ref(),
+                &config.console,
+            )?;
+            run_step(
+                Command::new("pnpm")
+                    .arg("-r")
+                    .arg(format!("--filter=./{}...", config.target))
+                    .arg("build")
+                    .envs(&config.build_args)
+                    .env("NODE_ENV", "production")
+                    .current_dir(&app),
+                log.as_ref(),
+                &config.console,
+            )?;
+        }
+    }
+
+    Ok(app)
+}
+
+fn run_step(cmd: &mut Command, log: Option<&fs::File>, console: &Console) -> Result<()> {
+    let line: Vec<String> = std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|s| s.to_string_lossy().into_owned())
+        .collect();
+    let line = line.join(" ");
+    console.say(format!("   $ {}", line));
+
+    let log = log.map(fs::File::try_clone).transpose()?;
+    let status = tee_command(cmd, log, console).with_context(|| format!("Failed to run {}", line))?;
+    if !status.success() {
+        bail!("`{}` failed with exit code: {:?}", line, status.code());
+    }
+    Ok(())
+}
+
+/// Next.js standalone output laid out like the Next.js Dockerfile's runtime stage
+///
+/// `.next/standalone` holds a traced `node_modules` and `<target>/server.js`;
+/// static assets and `public/` aren't traced, so they are copied next to it.
+fn nextjs_standalone(ctx_dir: &Path, app: &Path, target: &str) -> Result<PathBuf> {
+    let build = app.join(target).join(".next");
+    if !build.join("standalone").is_dir() {
+        bail!(
+            "{} is a Next.js app, but the build has no .next/standalone; \
+             set `output: \"standalone\"` in its next.config",
+            target
+        );
+    }
+
+    let out = ctx_dir.join("native").join("standalone");
+    if out.exists() {
+        fs::remove_dir_all(&out)?;
+    }
+    copy_dir_recursive(&build.join("standalone"), &out)?;
+    if build.join("static").is_dir() {
+        copy_dir_recursive(&build.join("static"), &out.join(target).join(".next").join("static"))?;
+    }
+    if app.join(target).join("public").is_dir() {
+        copy_dir_recursive(&app.join(target).join("public"), &out.join(target).join("public"))?;
+    }
+    Ok(out)
+}
+
+/// Entry point and environment by runtime family (the built-in Dockerfiles' runtime stages)
+fn image_spec(
+    target: &str,
+    family: RuntimeFamily,
+    nextjs: bool,
+    name: String,
+    platform: Platform,
+    port: u16,
+) -> ImageSpec {
+    if nextjs {
+        return ImageSpec {
+            name,
+            platform,
+            workdir: "/app".to_string(),
+            env: vec![
+                "NODE_ENV=production".to_string(),
+                "NEXT_TELEMETRY_DISABLED=1".to_string(),
+                "HOSTNAME=0.0.0.0".to_string(),
+                format!("PORT={}", port),
+            ],
+            cmd: vec!["node".to_string(), format!("{}/server.js", target)],
+            port,
+        };
+    }
+
+    let (cmd, mut env): (&[&str], Vec<String>) = match family {
+        RuntimeFamily::Bun => (&["bun", "run", "dist/index.js"], vec!["NODE_ENV=production".to_string()]),
+        RuntimeFamily::Deno => (
+            &["deno", "run", "--allow-net", "--allow-env", "src/main.ts"],
+            vec!["DENO_DIR=/app/.deno".to_string()],
+        ),
+        _ => (&["node", "dist/index.js"], vec!["NODE_ENV=production".to_string()]),
+    };
+    env.push(format!("PORT={}", port));
+
+    ImageSpec {
+        name,
+        platform,
+        workdir: format!("/app/{}", target),
+        env,
+        cmd: cmd.iter().map(|s| s.to_string()).collect(),
+        port,
+    }
+}
+
+/// Write the image of `spec` to `layout`: the base image's layers for
+/// `spec.platform` plus `app` under `/app`
+///
+/// The app layer is reproducible (sorted entries, fixed mtimes and owners),
+/// and timestamps are left at the base image's, so the same app yields the
+/// same image digest.
+pub fn assemble(base: &Path, app: &Path, layout: &Path, spec: &ImageSpec) -> Result<Descriptor> {
+    let (base_descriptor, base_manifest) = oci::resolve_manifest(base, &spec.platform)?;
+    let mut config: Value = oci::read_json(base, &base_manifest.config.digest)?;
+
+    if layout.exists() {
+        fs::remove_dir_all(layout)?;
+    }
+    oci::init_layout(layout)?;
+    for layer in &base_manifest.layers {
+        link_blob(base, layout, &layer.digest)?;
+    }
+    let (layer, diff_id) = write_app_layer(app, layout)?;
+
+    configure(&mut config, spec, &diff_id)?;
+    let config = oci::write_json(layout, oci::MEDIA_TYPE_CONFIG, &config)?;
+
+    let mut layers = base_manifest.layers;
+    layers.push(layer);
+    let manifest = oci::Manifest {
+        schema_version: 2,
+        media_type: Some(oci::MEDIA_TYPE_MANIFEST.to_string()),
+        config,
+        layers,
+        annotations: BTreeMap::new(),
+    };
+    let mut descriptor = oci::write_json(layout, oci::MEDIA_TYPE_MANIFEST, &manifest)?;
+    descriptor.platform = Some(base_descriptor.platform.unwrap_or_else(|| spec.platform.clone()));
+    descriptor
+        .annotations
+        .insert(oci::ANNOTATION_REF_NAME.to_string(), spec.name.clone());
+    oci::write_index(layout, vec![descriptor.clone()])?;
+
+    Ok(descriptor)
+}
+
+/// Point the base image config at the app: env, workdir, command, port, new layer
+fn configure(config: &mut Value, spec: &ImageSpec, diff_id: &str) -> Result<()> {
+    let Some(root) = config.as_object_mut() else {
+        bail!("Invalid base image config (not a JSON object)");
+    };
+
+    let image = root.entry("config").or_insert_with(|| json!({}));
+    let Some(image) = image.as_object_mut() else {
+        bail!("Invalid base image config (config is not an object)");
+    };
+    let mut env: Vec<String> = image
+        .get("Env")
+        .and_then(Value::as_array)
+        .map(|vars| vars.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+        .unwrap_or_default();
+    for var in &spec.env {
+        let key = var.split('=').next().unwrap_or(var);
+        env.retain(|v| v.split('=').next() != Some(key));
+        env.push(var.clone());
+    }
+    image.insert("Env".to_string(), json!(env));
+    image.insert("WorkingDir".to_string(), json!(spec.workdir));
+    image.insert("Cmd".to_string(), json!(spec.cmd));
+    let ports = image.entry("ExposedPorts").or_insert_with(|| json!({}));
+    if let Some(ports) = ports.as_object_mut() {
+        ports.insert(format!("{}/tcp", spec.port), json!({}));
+    }
+
+    let rootfs = root
+        .entry("rootfs")
+        .or_insert_with(|| json!({"type": "layers", "diff_ids": []}));
+    match rootfs.get_mut("diff_ids").and_then(Value::as_array_mut) {
+        Some(diff_ids) => diff_ids.push(json!(diff_id)),
+        None => bail!("Invalid base image config (no rootfs.diff_ids)"),
+    }
+
+    let history = root.entry("history").or_insert_with(|| json!([]));
+    if let Some(history) = history.as_array_mut() {
+        history.push(json!({
+            "created_by": "airis build --docker --builder native",
+            "comment": format!("app at {}", spec.workdir),
+        }));
+    }
+
+    Ok(())
+}
+
+/// Pack `app` as `app/...` into a gzipped tar blob
+///
+/// Returns the blob's descriptor and the layer's diff ID (digest of the
+/// uncompressed tar).
+fn write_app_layer(app: &Path, layout: &Path) -> Result<(Descriptor, String)> {
+    let tmp = layout.join("blobs").join("layer.tmp");
+    let file = fs::File::create(&tmp).with_context(|| format!("Failed to create {}", tmp.display()))?;
+    let gzip = GzEncoder::new(DigestWriter::new(file), flate2::Compression::default());
+    let mut tar = tar::Builder::new(DigestWriter::new(gzip));
+    tar.mode(tar::HeaderMode::Deterministic);
+    tar.follow_symlinks(false);
+
+    for entry in walkdir::WalkDir::new(app).follow_links(false).sort_by_file_name() {
+        let entry = entry?;
+        let rel = entry.path().strip_prefix(app)?;
+        tar.append_path_with_name(entry.path(), Path::new("app").join(rel))
+            .with_context(|| format!("Failed to add {} to the image", entry.path().display()))?;
+    }
+
+    let (gzip, diff_id, _) = tar.into_inner()?.finish();
+    let (mut file, digest, size) = gzip.finish()?.finish();
+    file.flush()?;
+    drop(file);
+
+    let path = oci::blob_path(layout, &digest)?;
+    fs::rename(&tmp, &path).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok((Descriptor::new(oci::MEDIA_TYPE_LAYER_GZIP, digest, size), diff_id))
+}
+
+/// Hard-link (else copy) a blob of `from` into `to`
+fn link_blob(from: &Path, to: &Path, digest: &str) -> Result<()> {
+    let src = oci::blob_path(from, digest)?;
+    let dst = oci::blob_path(to, digest)?;
+    if dst.exists() {
+        return Ok(());
+    }
+    if let Some(parent) = dst.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::hard_link(&src, &dst).is_err() {
+        fs::copy(&src, &dst).with_context(|| format!("Failed to copy base layer {}", src.display()))?;
+    }
+    Ok(())
+}
+
+/// Copy the layout to `out`: an OCI archive if it ends in `.tar`, else a layout directory
+fn export(layout: &Path, out: &Path) -> Result<()> {
+    if let Some(parent) = out.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if out.extension().is_some_and(|ext| ext == "tar") {
+        return oci::write_archive(layout, out);
+    }
+    if out.exists() {
+        fs::remove_dir_all(out)?;
+    }
+    copy_dir_recursive(layout, out)
+}
+
+/// Push the layout's image to the registry (skopeo talks to it directly)
+fn push(layout: &Path, image_name: &str, console: &Console) -> Result<()> {
+    console.say(format!("📤 Pushing {}", image_name));
+    let status = tee_command(
+        Command::new("skopeo")
+            .arg("copy")
+            .arg(format!("oci:{}", layout.display()))
+            .arg(format!("docker://{}", image_name)),
+        None,
+        console,
+    )
+    .context("Failed to run skopeo (needed to push without Docker)")?;
+    if !status.success() {
+        bail!("skopeo push of {} failed with exit code: {:?}", image_name, status.code());
+    }
+    Ok(())
+}
+
+/// Writer that SHA-256 hashes and counts what passes through
+struct DigestWriter<W> {
+    inner: W,
+    hasher: Sha256,
+    size: u64,
+}
+
+impl<W> DigestWriter<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            hasher: Sha256::new(),
+            size: 0,
+        }
+    }
+
+    /// The inner writer, the "sha256:..." digest and the byte count
+    fn finish(self) -> (W, String, u64) {
+        (self.inner, format!("sha256:{:x}", self.hasher.finalize()), self.size)
+    }
+}
+
+impl<W: Write> Write for DigestWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        self.size += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    /// A two-platform base image layout, like `skopeo copy --all` writes
+    fn fixture_base(dir: &Path) -> PathBuf {
+        let base = dir.join("base");
+        oci::init_layout(&base).unwrap();
+
+        let mut manifests = Vec::new();
+        for arch in ["amd64", "arm64"] {
+            let mut tar = tar::Builder::new(Vec::new());
+            let content = format!("ID=alpine\nARCH={}\n", arch);
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(&mut header, "etc/os-release", content.as_bytes()).unwrap();
+            let layer_tar = tar.into_inner().unwrap();
+            let diff_id = format!("sha256:{:x}", Sha256::digest(&layer_tar));
+            let mut gzip = GzEncoder::new(Vec::new(), flate2::Compression::default());
+            gzip.write_all(&layer_tar).unwrap();
+            let layer = oci::write_blob(&base, oci::MEDIA_TYPE_LAYER_GZIP, &gzip.finish().unwrap()).unwrap();
+
+            let config = json!({
+                "architecture": arch,
+                "os": "linux",
+                "config": {
+                    "Env": ["PATH=/usr/local/bin:/usr/bin", "NODE_ENV=development"],
+                    "Entrypoint": ["docker-entrypoint.sh"],
+                    "Cmd": ["node"],
+                },
+                "rootfs": {"type": "layers", "diff_ids": [diff_id]},
+                "history": [{"created_by": "ADD rootfs"}],
+            });
+            let config = oci::write_json(&base, oci::MEDIA_TYPE_CONFIG, &config).unwrap();
+            let manifest = oci::Manifest {
+                schema_version: 2,
+                media_type: Some(oci::MEDIA_TYPE_MANIFEST.to_string()),
+                config,
+                layers: vec![layer],
+                annotations: BTreeMap::new(),
+            };
+            let mut descriptor = oci::write_json(&base, oci::MEDIA_TYPE_MANIFEST, &manifest).unwrap();
+            descriptor.platform = Some(Platform::parse(&format!("linux/{}", arch)).unwrap());
+            manifests.push(descriptor);
+        }
+        let index = oci::Index {
+            schema_version: 2,
+            media_type: Some(oci::MEDIA_TYPE_INDEX.to_string()),
+            manifests,
+        };
+        let index = oci::write_json(&base, oci::MEDIA_TYPE_INDEX, &index).unwrap();
+        oci::write_index(&base, vec![index]).unwrap();
+        base
+    }
+
+    fn fixture_app(dir: &Path) -> PathBuf {
+        let app = dir.join("app");
+        fs::create_dir_all(app.join("apps/api/dist")).unwrap();
+        fs::create_dir_all(app.join("node_modules/.pnpm/lodash@4/node_modules/lodash")).unwrap();
+        fs::write(app.join("apps/api/dist/index.js"), "console.log('hi')").unwrap();
+        fs::write(app.join("node_modules/.pnpm/lodash@4/node_modules/lodash/index.js"), "").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(".pnpm/lodash@4/node_modules/lodash", app.join("node_modules/lodash")).unwrap();
+        app
+    }
+
+    fn spec(platform: &str) -> ImageSpec {
+        image_spec(
+            "apps/api",
+            RuntimeFamily::Node,
+            false,
+            "api:airis-abc".to_string(),
+            Platform::parse(platform).unwrap(),
+            8080,
+        )
+    }
+
+    #[test]
+    fn test_assemble_on_fixture_base() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = fixture_base(dir.path());
+        let app = fixture_app(dir.path());
+        let layout = dir.path().join("out");
+
+        let descriptor = assemble(&base, &app, &layout, &spec("linux/arm64")).unwrap();
+        assert_eq!(descriptor.platform.as_ref().unwrap().to_string(), "linux/arm64");
+        assert_eq!(descriptor.annotations[oci::ANNOTATION_REF_NAME], "api:airis-abc");
+        assert!(layout.join("oci-layout").is_file());
+        assert_eq!(oci::read_index(&layout).unwrap().manifests, vec![descriptor.clone()]);
+
+        let manifest: oci::Manifest = oci::read_json(&layout, &descriptor.digest).unwrap();
+        assert_eq!(manifest.layers.len(), 2);
+        for blob in manifest.layers.iter().chain([&manifest.config]) {
+            let content = fs::read(oci::blob_path(&layout, &blob.digest).unwrap()).unwrap();
+            assert_eq!(format!("sha256:{:x}", Sha256::digest(&content)), blob.digest);
+            assert_eq!(content.len() as u64, blob.size);
+        }
+
+        // The arm64 base layer, then the app
+        let config: Value = oci::read_json(&layout, &manifest.config.digest).unwrap();
+        assert_eq!(config["architecture"], "arm64");
+        assert_eq!(config["config"]["WorkingDir"], "/app/apps/api");
+        assert_eq!(config["config"]["Cmd"], json!(["node", "dist/index.js"]));
+        assert_eq!(config["config"]["Entrypoint"], json!(["docker-entrypoint.sh"]));
+        assert_eq!(
+            config["config"]["Env"],
+            json!(["PATH=/usr/local/bin:/usr/bin", "NODE_ENV=production", "PORT=8080"])
+        );
+        assert!(config["config"]["ExposedPorts"]["8080/tcp"].is_object());
+        assert_eq!(config["rootfs"]["diff_ids"].as_array().unwrap().len(), 2);
+        assert_eq!(config["history"].as_array().unwrap().len(), 2);
+
+        let app_layer = fs::read(oci::blob_path(&layout, &manifest.layers[1].digest).unwrap()).unwrap();
+        let mut layer_tar = Vec::new();
+        flate2::read::GzDecoder::new(app_layer.as_slice()).read_to_end(&mut layer_tar).unwrap();
+        assert_eq!(
+            config["rootfs"]["diff_ids"][1],
+            json!(format!("sha256:{:x}", Sha256::digest(&layer_tar)))
+        );
+        let mut archive = tar::Archive::new(layer_tar.as_slice());
+        let entries: Vec<(String, tar::EntryType)> = archive
+            .entries()
+            .unwrap()
+            .map(|e| {
+                let e = e.unwrap();
+                (e.path().unwrap().to_string_lossy().into_owned(), e.header().entry_type())
+            })
+            .collect();
+        assert!(entries.contains(&("app/apps/api/dist/index.js".to_string(), tar::EntryType::Regular)));
+        #[cfg(unix)]
+        assert!(entries.contains(&("app/node_modules/lodash".to_string(), tar::EntryType::Symlink)));
+
+        // Same app, same image
+        let again = assemble(&base, &app, &dir.path().join("again"), &spec("linux/arm64")).unwrap();
+        assert_eq!(again.digest, descriptor.digest);
+    }
+
+    #[test]
+    fn test_nextjs_standalone_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = dir.path().join("app");
+        let next = app.join("apps/web/.next");
+        fs::create_dir_all(next.join("standalone/apps/web")).unwrap();
+        fs::create_dir_all(next.join("static/chunks")).unwrap();
+        fs::create_dir_all(app.join("apps/web/public")).unwrap();
+        fs::write(next.join("standalone/apps/web/server.js"), "").unwrap();
+        fs::write(next.join("static/chunks/main.js"), "").unwrap();
+        fs::write(app.join("apps/web/public/favicon.ico"), "").unwrap();
+
+        let out = nextjs_standalone(dir.path(), &app, "apps/web").unwrap();
+        assert!(out.join("apps/web/server.js").is_file());
+        assert!(out.join("apps/web/.next/static/chunks/main.js").is_file());
+        assert!(out.join("apps/web/public/favicon.ico").is_file());
+
+        let name = "web:airis-abc".to_string();
+        let spec = image_spec("apps/web", RuntimeFamily::Node, true, name, Platform::host(), 3000);
+        assert_eq!(spec.workdir, "/app");
+        assert_eq!(spec.cmd, vec!["node", "apps/web/server.js"]);
+
+        fs::remove_dir_all(next.join("standalone")).unwrap();
+        let err = nextjs_standalone(dir.path(), &app, "apps/web").unwrap_err();
+        assert!(err.to_string().contains("output: \"standalone\""));
+    }
+
+    #[test]
+    fn test_assemble_requires_platform_in_base() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = fixture_base(dir.path());
+        let app = fixture_app(dir.path());
+
+        let err = assemble(&base, &app, &dir.path().join("out"), &spec("linux/s390x")).unwrap_err();
+        assert!(err.to_string().contains("no image for linux/s390x"));
+    }
+
+    #[test]
+    fn test_export_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = fixture_base(dir.path());
+        let archive = dir.path().join("image.tar");
+
+        export(&base, &archive).unwrap();
+        let mut archive = tar::Archive::new(fs::File::open(&archive).unwrap());
+        let names: Vec<String> = archive
+            .entries()
+            .unwrap()
+            .map(|e| e.unwrap().path().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert!(names.iter().any(|n| n.ends_with("index.json")));
+        assert!(names.iter().any(|n| n.ends_with("oci-layout")));
+    }
+}
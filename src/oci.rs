@@ -0,0 +1,403 @@
+//! OCI image layouts (`oci-layout`, `index.json`, `blobs/sha256/...`)
+//!
+//! Multi-platform `airis build --docker` builds export one next to the build
+//! cache, `--builder native` writes one itself, and `airis bundle` ships it
+//! instead of a `docker save` tarball.
+//! See <https://github.com/opencontainers/image-spec/blob/main/image-layout.md>.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+pub const MEDIA_TYPE_INDEX: &str = "application/vnd.oci.image.index.v1+json";
+pub const MEDIA_TYPE_MANIFEST: &str = "application/vnd.oci.image.manifest.v1+json";
+pub const MEDIA_TYPE_CONFIG: &str = "application/vnd.oci.image.config.v1+json";
+pub const MEDIA_TYPE_LAYER_GZIP: &str = "application/vnd.oci.image.layer.v1.tar+gzip";
+const DOCKER_MANIFEST_LIST: &str = "application/vnd.docker.distribution.manifest.list.v2+json";
+const DOCKER_MANIFEST: &str = "application/vnd.docker.distribution.manifest.v2+json";
+
+/// Annotation naming an image in `index.json` (e.g., "web:1.0")
+pub const ANNOTATION_REF_NAME: &str = "org.opencontainers.image.ref.name";
+
+/// Content descriptor (a manifest, config or layer blob)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Descriptor {
+    pub media_type: String,
+    pub digest: String,
+    pub size: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platform: Option<Platform>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<String, String>,
+}
+
+impl Descriptor {
+    pub fn new(media_type: &str, digest: String, size: u64) -> Self {
+        Self {
+            media_type: media_type.to_string(),
+            digest,
+            size,
+            platform: None,
+            annotations: BTreeMap::new(),
+        }
+    }
+
+    fn is_index(&self) -> bool {
+        self.media_type == MEDIA_TYPE_INDEX || self.media_type == DOCKER_MANIFEST_LIST
+    }
+
+    fn is_manifest(&self) -> bool {
+        self.media_type == MEDIA_TYPE_MANIFEST || self.media_type == DOCKER_MANIFEST
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Platform {
+    pub architecture: String,
+    pub os: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant: Option<String>,
+}
+
+impl Platform {
+    /// Parse "os/arch[/variant]" (e.g., "linux/arm64/v8")
+    pub fn parse(platform: &str) -> Result<Self> {
+        let parts: Vec<&str> = platform.split('/').collect();
+        match parts.as_slice() {
+            [os, arch] | [os, arch, _] if !os.is_empty() && !arch.is_empty() => Ok(Self {
+                os: os.to_string(),
+                architecture: arch.to_string(),
+                variant: parts.get(2).map(|v| v.to_string()),
+            }),
+            _ => bail!("Invalid platform '{}' (expected os/arch, e.g., linux/amd64)", platform),
+        }
+    }
+
+    /// Linux on the host's architecture (images are Linux on every host)
+    pub fn host() -> Self {
+        let architecture = match std::env::consts::ARCH {
+            "x86_64" => "amd64",
+            "aarch64" => "arm64",
+            other => other,
+        };
+        Self {
+            os: "linux".to_string(),
+            architecture: architecture.to_string(),
+            variant: None,
+        }
+    }
+
+    /// Whether an image for `self` satisfies a request for `wanted`
+    /// ("linux/arm64" matches "linux/arm64/v8")
+    pub fn matches(&self, wanted: &Platform) -> bool {
+        self.os == wanted.os
+            && self.architecture == wanted.architecture
+            && (wanted.variant.is_none() || self.variant == wanted.variant)
+    }
+}
+
+impl std::fmt::Display for Platform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.os, self.architecture)?;
+        if let Some(variant) = &self.variant {
+            write!(f, "/{}", variant)?;
+        }
+        Ok(())
+    }
+}
+
+/// Image index: `index.json` of a layout, or a multi-platform manifest list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Index {
+    pub schema_version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+    pub manifests: Vec<Descriptor>,
+}
+
+/// Image manifest: a config blob plus layers, bottom first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+    pub schema_version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+    pub config: Descriptor,
+    pub layers: Vec<Descriptor>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<String, String>,
+}
+
+/// Path of a blob in a layout
+///
+/// Digests come from untrusted index.json files, so only well-formed
+/// `sha256:<64 lowercase hex>` digests map to a path.
+pub fn blob_path(layout: &Path, digest: &str) -> Result<PathBuf> {
+    let Some((algorithm, hex)) = digest.split_once(':') else {
+        bail!("Invalid digest: {}", digest);
+    };
+    if algorithm != "sha256" {
+        bail!("Unsupported digest algorithm: {}", digest);
+    }
+    if hex.len() != 64 || !hex.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+        bail!("Invalid digest: {}", digest);
+    }
+    Ok(layout.join("blobs").join(algorithm).join(hex))
+}
+
+/// Read `index.json` of a layout
+pub fn read_index(layout: &Path) -> Result<Index> {
+    let path = layout.join("index.json");
+    let content = fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_slice(&content).with_context(|| format!("Invalid OCI index: {}", path.display()))
+}
+
+/// Read and parse a JSON blob
+pub fn read_json<T: serde::de::DeserializeOwned>(layout: &Path, digest: &str) -> Result<T> {
+    let path = blob_path(layout, digest)?;
+    let content = fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_slice(&content).with_context(|| format!("Invalid OCI blob: {}", path.display()))
+}
+
+/// Create an empty layout (`oci-layout` and `blobs/sha256/`)
+pub fn init_layout(layout: &Path) -> Result<()> {
+    fs::create_dir_all(layout.join("blobs").join("sha256"))
+        .with_context(|| format!("Failed to create {}", layout.display()))?;
+    fs::write(layout.join("oci-layout"), r#"{"imageLayoutVersion":"1.0.0"}"#)?;
+    Ok(())
+}
+
+/// Store `content` as a blob, returning its descriptor
+pub fn write_blob(layout: &Path, media_type: &str, content: &[u8]) -> Result<Descriptor> {
+    use sha2::{Digest, Sha256};
+    let digest = format!("sha256:{:x}", Sha256::digest(content));
+    let path = blob_path(layout, &digest)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(Descriptor::new(media_type, digest, content.len() as u64))
+}
+
+/// Store a serialized JSON blob
+pub fn write_json(layout: &Path, media_type: &str, value: &impl Serialize) -> Result<Descriptor> {
+    write_blob(layout, media_type, &serde_json::to_vec(value)?)
+}
+
+/// Write `index.json` listing `manifests`
+pub fn write_index(layout: &Path, manifests: Vec<Descriptor>) -> Result<()> {
+    let index = Index {
+        schema_version: 2,
+        media_type: Some(MEDIA_TYPE_INDEX.to_string()),
+        manifests,
+    };
+    fs::write(layout.join("index.json"), serde_json::to_vec_pretty(&index)?)
+        .with_context(|| format!("Failed to write {}/index.json", layout.display()))
+}
+
+/// Image manifest of a layout for `platform`, following image indexes
+///
+/// A single manifest without platform information is taken as is (its
+/// config names the platform).
+pub fn resolve_manifest(layout: &Path, platform: &Platform) -> Result<(Descriptor, Manifest)> {
+    let mut candidates = read_index(layout)?.manifests;
+    let mut untagged = Vec::new();
+
+    while let Some(descriptor) = candidates.pop() {
+        if descriptor.is_index() {
+            let nested: Index = read_json(layout, &descriptor.digest)?;
+            candidates.extend(nested.manifests);
+        } else if descriptor.platform.as_ref().is_some_and(|p| p.matches(platform)) {
+            let manifest = read_json(layout, &descriptor.digest)?;
+            return Ok((descriptor, manifest));
+        } else if descriptor.platform.is_none() {
+            untagged.push(descriptor);
+        }
+    }
+
+    if let [descriptor] = untagged.as_slice() {
+        let manifest = read_json(layout, &descriptor.digest)?;
+        return Ok((descriptor.clone(), manifest));
+    }
+    bail!("{} has no image for {}", layout.display(), platform)
+}
+
+/// Pack a layout into an OCI archive (a tar of the layout directory)
+pub fn write_archive(layout: &Path, archive: &Path) -> Result<()> {
+    let file = fs::File::create(archive).with_context(|| format!("Failed to create {}", archive.display()))?;
+    let mut tar = tar::Builder::new(file);
+    tar.mode(tar::HeaderMode::Deterministic);
+    tar.append_dir_all(".", layout)
+        .with_context(|| format!("Failed to archive {}", layout.display()))?;
+    tar.into_inner()?.flush()?;
+    Ok(())
+}
+
+/// Image manifests of a layout by platform ("linux/amd64" -> "sha256:..."),
+/// plus the digest of the multi-platform index if the image has one
+///
+/// Attestation manifests (platform "unknown/unknown") are left out.
+pub fn platform_digests(layout: &Path) -> Result<(Option<String>, BTreeMap<String, String>)> {
+    let mut index_digest = None;
+    let mut platforms = BTreeMap::new();
+
+    for descriptor in read_index(layout)?.manifests {
+        if descriptor.is_index() {
+            let nested: Index = read_json(layout, &descriptor.digest)?;
+            index_digest.get_or_insert(descriptor.digest);
+            platforms.extend(nested.manifests.iter().filter_map(platform_entry));
+        } else if let Some((platform, digest)) = platform_entry(&descriptor) {
+            platforms.insert(platform, digest);
+        }
+    }
+
+    Ok((index_digest, platforms))
+}
+
+fn platform_entry(descriptor: &Descriptor) -> Option<(String, String)> {
+    let platform = descriptor.platform.as_ref()?;
+    if platform.os == "unknown" {
+        return None;
+    }
+    Some((platform.to_string(), descriptor.digest.clone()))
+}
+
+/// Check every blob reachable from `index.json` against its descriptor's
+/// digest and size
+///
+/// Returns the number of blobs checked and the problems found.
+pub fn check_blobs(layout: &Path) -> Result<(usize, Vec<String>)> {
+    use sha2::{Digest, Sha256};
+
+    let mut queue = read_index(layout)?.manifests;
+    let mut checked = std::collections::BTreeSet::new();
+    let mut problems = Vec::new();
+
+    while let Some(descriptor) = queue.pop() {
+        if !checked.insert(descriptor.digest.clone()) {
+            continue;
+        }
+        let path = match blob_path(layout, &descriptor.digest) {
+            Ok(path) => path,
+            Err(e) => {
+                problems.push(e.to_string());
+                continue;
+            }
+        };
+        let Ok(mut file) = fs::File::open(&path) else {
+            problems.push(format!("missing blob {}", descriptor.digest));
+            continue;
+        };
+        let mut hasher = Sha256::new();
+        let size = std::io::copy(&mut file, &mut hasher)?;
+        if format!("sha256:{:x}", hasher.finalize()) != descriptor.digest {
+            problems.push(format!("digest mismatch for blob {}", descriptor.digest));
+            continue;
+        }
+        if size != descriptor.size {
+            problems.push(format!("size mismatch for blob {} ({} != {})", descriptor.digest, size, descriptor.size));
+            continue;
+        }
+
+        if descriptor.is_index() {
+            let nested: Index = read_json(layout, &descriptor.digest)?;
+            queue.extend(nested.manifests);
+        } else if descriptor.is_manifest() {
+            let manifest: Manifest = read_json(layout, &descriptor.digest)?;
+            queue.push(manifest.config);
+            queue.extend(manifest.layers);
+        }
+    }
+
+    Ok((checked.len(), problems))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(digest: &str, platform: &str) -> Descriptor {
+        Descriptor {
+            platform: Some(Platform::parse(platform).unwrap()),
+            ..Descriptor::new(MEDIA_TYPE_MANIFEST, digest.to_string(), 100)
+        }
+    }
+
+    #[test]
+    fn test_platform_parse() {
+        let platform = Platform::parse("linux/arm64/v8").unwrap();
+        assert_eq!(platform.architecture, "arm64");
+        assert_eq!(platform.variant.as_deref(), Some("v8"));
+        assert_eq!(platform.to_string(), "linux/arm64/v8");
+        assert_eq!(Platform::parse("linux/amd64").unwrap().to_string(), "linux/amd64");
+        assert!(Platform::parse("amd64").is_err());
+        assert!(Platform::parse("linux/").is_err());
+    }
+
+    #[test]
+    fn test_platform_digests_of_multi_platform_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        let layout = dir.path();
+
+        // What `docker buildx build --platform ... --output type=oci` writes:
+        // index.json -> image index -> per-platform manifests (+ attestations)
+        let nested = Index {
+            schema_version: 2,
+            media_type: Some(MEDIA_TYPE_INDEX.to_string()),
+            manifests: vec![
+                manifest("sha256:aaa", "linux/amd64"),
+                manifest("sha256:bbb", "linux/arm64"),
+                manifest("sha256:ccc", "unknown/unknown"),
+            ],
+        };
+        let index_blob = write_json(layout, MEDIA_TYPE_INDEX, &nested).unwrap();
+        write_index(layout, vec![index_blob.clone()]).unwrap();
+
+        let (index_digest, platforms) = platform_digests(layout).unwrap();
+        assert_eq!(index_digest, Some(index_blob.digest));
+        assert_eq!(
+            platforms.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("linux/amd64".to_string(), "sha256:aaa".to_string()),
+                ("linux/arm64".to_string(), "sha256:bbb".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_blobs() {
+        let dir = tempfile::tempdir().unwrap();
+        let layout = dir.path();
+        init_layout(layout).unwrap();
+        let config = write_blob(layout, MEDIA_TYPE_CONFIG, b"{}").unwrap();
+        let layer = write_blob(layout, MEDIA_TYPE_LAYER_GZIP, b"layer").unwrap();
+        let manifest = Manifest {
+            schema_version: 2,
+            media_type: Some(MEDIA_TYPE_MANIFEST.to_string()),
+            config,
+            layers: vec![layer.clone()],
+            annotations: BTreeMap::new(),
+        };
+        let descriptor = write_json(layout, MEDIA_TYPE_MANIFEST, &manifest).unwrap();
+        write_index(layout, vec![descriptor]).unwrap();
+        assert_eq!(check_blobs(layout).unwrap(), (3, Vec::new()));
+
+        fs::write(blob_path(layout, &layer.digest).unwrap(), b"tampered").unwrap();
+        let (_, problems) = check_blobs(layout).unwrap();
+        assert_eq!(problems, vec![format!("digest mismatch for blob {}", layer.digest)]);
+
+        // Digests that can't be checked, or that would escape blobs/
+        let descriptor = |digest: &str| Descriptor::new(MEDIA_TYPE_CONFIG, digest.to_string(), 2);
+        write_index(layout, vec![descriptor("sha512:abcd"), descriptor("sha256:../../../etc/passwd")]).unwrap();
+        let (_, problems) = check_blobs(layout).unwrap();
+        assert_eq!(
+            problems,
+            vec!["Invalid digest: sha256:../../../etc/passwd", "Unsupported digest algorithm: sha512:abcd"]
+        );
+    }
+}
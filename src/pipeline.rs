@@ -0,0 +1,297 @@
+//! Task pipeline for `airis run`
+//!
+//! Expands `[tasks.<name>]` definitions from manifest.toml over the workspace
+//! DAG into a graph of `<package>#<task>` nodes that `ParallelExecutor` can run.
+//!
+//! # Example
+//!
+//! ```toml
+//! [tasks.build]
+//! depends_on = ["^build"]   # build workspace deps first
+//!
+//! [tasks.test]
+//! depends_on = ["build"]    # build this package first
+//! ```
+
+use anyhow::{bail, Result};
+use indexmap::IndexMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
+
+use crate::dag::Dag;
+use crate::executor::BuildTask;
+use crate::manifest::TaskConfig;
+
+/// A single `<package>#<task>` node in the pipeline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PipelineTask {
+    pub id: String,      // e.g., "apps/web#build"
+    pub package: String, // e.g., "apps/web"
+    pub task: String,    // e.g., "build"
+    pub dependencies: Vec<String>,
+}
+
+impl PipelineTask {
+    /// Convert to an executor task
+    pub fn to_build_task(&self) -> BuildTask {
+        BuildTask {
+            id: self.id.clone(),
+            target: self.package.clone(),
+            channel: String::new(),
+            dependencies: self.dependencies.clone(),
+            retries: 0,
+            weight: 1,
+        }
+    }
+}
+
+/// Format a task ID: ("apps/web", "build") -> "apps/web#build"
+pub fn task_id(package: &str, task: &str) -> String {
+    format!("{}#{}", package, task)
+}
+
+/// Split a task ID back into (package, task)
+pub fn split_task_id(id: &str) -> Option<(&str, &str)> {
+    id.rsplit_once('#')
+}
+
+/// Expand `task` for the given packages into the full task graph
+///
+/// Returns tasks in dependency-first order. Tasks referenced through
+/// `depends_on` but not defined under `[tasks]` are included with no
+/// dependencies of their own.
+pub fn plan(
+    dag: &Dag,
+    tasks: &IndexMap<String, TaskConfig>,
+    task: &str,
+    packages: &[String],
+) -> Result<Vec<PipelineTask>> {
+    let mut planned: HashMap<String, PipelineTask> = HashMap::new();
+    let mut queue: VecDeque<(String, String)> = packages
+        .iter()
+        .map(|p| (p.clone(), task.to_string()))
+        .collect();
+
+    while let Some((package, name)) = queue.pop_front() {
+        let id = task_id(&package, &name);
+        if planned.contains_key(&id) {
+            continue;
+        }
+
+        let mut dependencies = Vec::new();
+        if let Some(config) = tasks.get(&name) {
+            for spec in &config.depends_on {
+                if let Some(upstream) = spec.strip_prefix('^') {
+                    // Same task in every workspace dependency
+                    let Some(node) = dag.nodes.get(&package) else {
+                        continue;
+                    };
+                    for dep in &node.deps {
+                        if dag.nodes.contains_key(dep) {
+                            dependencies.push(task_id(dep, upstream));
+                            queue.push_back((dep.clone(), upstream.to_string()));
+                        }
+                    }
+                } else {
+                    // Another task in the same package
+                    dependencies.push(task_id(&package, spec));
+                    queue.push_back((package.clone(), spec.clone()));
+                }
+            }
+        }
+
+        dependencies.sort();
+        dependencies.dedup();
+
+        planned.insert(
+            id.clone(),
+            PipelineTask {
+                id,
+                package,
+                task: name,
+                dependencies,
+            },
+        );
+    }
+
+    topo_sort(planned)
+}
+
+/// Order tasks dependency-first, failing on cycles
+fn topo_sort(planned: HashMap<String, PipelineTask>) -> Result<Vec<PipelineTask>> {
+    let mut in_degree: HashMap<&str, usize> = HashMap::new();
+    let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+
+    for (id, task) in &planned {
+        in_degree.entry(id.as_str()).or_insert(0);
+        for dep in &task.dependencies {
+            *in_degree.entry(id.as_str()).or_insert(0) += 1;
+            dependents.entry(dep.as_str()).or_default().push(id.as_str());
+        }
+    }
+
+    let mut ready: Vec<&str> = in_degree
+        .iter()
+        .filter(|(_, d)| **d == 0)
+        .map(|(id, _)| *id)
+        .collect();
+    ready.sort_unstable_by(|a, b| b.cmp(a));
+
+    let mut order = Vec::new();
+    while let Some(id) = ready.pop() {
+        order.push(id.to_string());
+        if let Some(next) = dependents.get(id) {
+            for dependent in next {
+                let degree = in_degree.get_mut(dependent).expect("dependent has in-degree");
+                *degree -= 1;
+                if *degree == 0 {
+                    ready.push(dependent);
+                }
+            }
+            ready.sort_unstable_by(|a, b| b.cmp(a));
+        }
+    }
+
+    if order.len() != planned.len() {
+        let done: HashSet<&String> = order.iter().collect();
+        let mut stuck: Vec<&String> = planned.keys().filter(|id| !done.contains(id)).collect();
+        stuck.sort();
+        bail!(
+            "Circular task dependency detected: {}",
+            stuck.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(", ")
+        );
+    }
+
+    let mut planned = planned;
+    Ok(order
+        .into_iter()
+        .filter_map(|id| planned.remove(&id))
+        .collect())
+}
+
+/// Resolve the shell command for a package task
+///
+/// Uses `command` from `[tasks.<name>]` if set; otherwise `<pm> run <task>`
+/// when the package.json defines that script. Returns None when there is
+/// nothing to run.
+pub fn resolve_command(
+    root: &Path,
+    package: &str,
+    task: &str,
+    config: Option<&TaskConfig>,
+    package_manager: &str,
+) -> Option<String> {
+    if let Some(cmd) = config.and_then(|c| c.command.as_ref()) {
+        return Some(cmd.clone());
+    }
+
+    let pkg_json = root.join(package).join("package.json");
+    let content = std::fs::read_to_string(pkg_json).ok()?;
+    let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+    json.get("scripts")?.get(task)?;
+
+    Some(format!("{} run {}", package_manager, task))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dag::DagNode;
+
+    fn sample_dag() -> Dag {
+        let mut dag = Dag::new();
+        for (id, deps) in [
+            ("apps/web", vec!["libs/ui"]),
+            ("libs/ui", vec!["libs/core"]),
+            ("libs/core", vec![]),
+        ] {
+            dag.add_node(DagNode {
+                id: id.to_string(),
+                name: id.rsplit('/').next().unwrap().to_string(),
+                path: id.to_string(),
+                deps: deps.into_iter().map(String::from).collect(),
+            });
+        }
+        dag
+    }
+
+    fn tasks(defs: &[(&str, &[&str])]) -> IndexMap<String, TaskConfig> {
+        defs.iter()
+            .map(|(name, deps)| {
+                (
+                    name.to_string(),
+                    TaskConfig {
+                        depends_on: deps.iter().map(|d| d.to_string()).collect(),
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn position(plan: &[PipelineTask], id: &str) -> usize {
+        plan.iter().position(|t| t.id == id).unwrap()
+    }
+
+    #[test]
+    fn test_plan_caret_dependency() {
+        let dag = sample_dag();
+        let tasks = tasks(&[("build", &["^build"])]);
+
+        let plan = plan(&dag, &tasks, "build", &["apps/web".to_string()]).unwrap();
+
+        assert_eq!(plan.len(), 3);
+        assert!(position(&plan, "libs/core#build") < position(&plan, "libs/ui#build"));
+        assert!(position(&plan, "libs/ui#build") < position(&plan, "apps/web#build"));
+        assert_eq!(plan[position(&plan, "apps/web#build")].dependencies, vec!["libs/ui#build"]);
+    }
+
+    #[test]
+    fn test_plan_same_package_dependency() {
+        let dag = sample_dag();
+        let tasks = tasks(&[("build", &["^build"]), ("test", &["build"])]);
+
+        let plan = plan(&dag, &tasks, "test", &["libs/ui".to_string()]).unwrap();
+        let ids: Vec<_> = plan.iter().map(|t| t.id.as_str()).collect();
+
+        assert_eq!(ids, vec!["libs/core#build", "libs/ui#build", "libs/ui#test"]);
+    }
+
+    #[test]
+    fn test_plan_detects_cycle() {
+        let dag = sample_dag();
+        let tasks = tasks(&[("a", &["b"]), ("b", &["a"])]);
+
+        let err = plan(&dag, &tasks, "a", &["libs/core".to_string()]).unwrap_err();
+        assert!(err.to_string().contains("Circular task dependency"));
+    }
+
+    #[test]
+    fn test_split_task_id() {
+        assert_eq!(split_task_id("apps/web#build"), Some(("apps/web", "build")));
+        assert_eq!(split_task_id("apps/web"), None);
+    }
+
+    #[test]
+    fn test_resolve_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let pkg = dir.path().join("libs/ui");
+        std::fs::create_dir_all(&pkg).unwrap();
+        std::fs::write(pkg.join("package.json"), r#"{"scripts": {"build": "tsup"}}"#).unwrap();
+
+        assert_eq!(
+            resolve_command(dir.path(), "libs/ui", "build", None, "pnpm"),
+            Some("pnpm run build".to_string())
+        );
+        assert_eq!(resolve_command(dir.path(), "libs/ui", "lint", None, "pnpm"), None);
+
+        let config = TaskConfig {
+            command: Some("eslint .".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            resolve_command(dir.path(), "libs/ui", "lint", Some(&config), "pnpm"),
+            Some("eslint .".to_string())
+        );
+    }
+}
@@ -4,7 +4,7 @@
 
 use anyhow::{Context, Result};
 use serde::Deserialize;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::Path;
 
 /// pnpm-lock.yaml v9 structure (minimal for dependency resolution)
@@ -14,6 +14,38 @@ pub struct PnpmLock {
     pub lockfile_version: String,
     #[serde(default)]
     pub importers: HashMap<String, Importer>,
+    /// Resolved packages keyed by "name@version(peers)"
+    #[serde(default)]
+    pub snapshots: HashMap<String, Snapshot>,
+    /// Package metadata keyed by "name@version" (snapshot keys without peers)
+    #[serde(default)]
+    pub packages: HashMap<String, PackageInfo>,
+}
+
+/// Where a package was resolved from
+#[derive(Debug, Deserialize, Default)]
+pub struct PackageInfo {
+    #[serde(default)]
+    pub resolution: Resolution,
+}
+
+/// `resolution` of a package (registry packages have an integrity hash)
+#[derive(Debug, Deserialize, Default)]
+pub struct Resolution {
+    /// Subresource integrity, e.g., "sha512-<base64>"
+    pub integrity: Option<String>,
+    /// Non-registry tarball URL
+    pub tarball: Option<String>,
+}
+
+/// A resolved package and its own dependencies
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    #[serde(default)]
+    pub dependencies: HashMap<String, String>,
+    #[serde(default)]
+    pub optional_dependencies: HashMap<String, String>,
 }
 
 /// An importer is a workspace package
@@ -25,7 +57,6 @@ pub struct Importer {
     #[serde(default)]
     pub dev_dependencies: HashMap<String, Dependency>,
     #[serde(default)]
-    #[allow(dead_code)]
     pub optional_dependencies: HashMap<String, Dependency>,
     #[serde(default)]
     pub peer_dependencies: HashMap<String, Dependency>,
@@ -61,7 +92,12 @@ impl PnpmLock {
         let content = std::fs::read_to_string(path)
             .with_context(|| format!("Failed to read {}", path.display()))?;
 
-        let lock: PnpmLock = serde_yaml::from_str(&content)
+        Self::parse(&content)
+    }
+
+    /// Parse pnpm-lock.yaml content (e.g., from `git show <rev>:pnpm-lock.yaml`)
+    pub fn parse(content: &str) -> Result<Self> {
+        let lock: PnpmLock = serde_yaml::from_str(content)
             .with_context(|| "Failed to parse pnpm-lock.yaml")?;
 
         if !lock.lockfile_version.starts_with("9.") {
@@ -105,35 +141,94 @@ impl PnpmLock {
 
     /// Resolve workspace link relative to importer path
     /// e.g., importer="libs/supabase/client", version="link:../types" -> "libs/supabase/types"
+    ///
+    /// `file:` directory deps are recorded relative to the lockfile root
+    /// (e.g., "file:libs/ui") and only count when they point at an importer.
     fn resolve_workspace_link(&self, importer_path: &str, version: &str) -> Option<String> {
-        if !version.starts_with("link:") {
-            return None;
+        if let Some(link_path) = version.strip_prefix("link:") {
+            return Some(normalize_path(importer_path, link_path));
         }
 
-        let link_path = version.strip_prefix("link:")?;
+        let file_path = version.strip_prefix("file:")?;
+        [normalize_path("", file_path), normalize_path(importer_path, file_path)]
+            .into_iter()
+            .find(|path| self.importers.contains_key(path))
+    }
 
-        // Use std::path for proper path resolution
-        use std::path::PathBuf;
+    /// All resolved packages an importer pulls in, directly or transitively
+    ///
+    /// Entries are "name@version" snapshot keys; workspace links appear as
+    /// "name@link:..." and are not followed (dependents cover those).
+    pub fn resolved_closure(&self, importer_path: &str) -> BTreeSet<String> {
+        let Some(importer) = self.importers.get(importer_path) else {
+            return BTreeSet::new();
+        };
+        self.closure(
+            importer
+                .dependencies
+                .iter()
+                .chain(&importer.dev_dependencies)
+                .chain(&importer.optional_dependencies)
+                .chain(&importer.peer_dependencies),
+        )
+    }
 
-        let base = PathBuf::from(importer_path);
-        let resolved = base.join(link_path);
+    /// Like [`Self::resolved_closure`], without devDependencies: what the
+    /// importer needs at runtime
+    pub fn runtime_closure(&self, importer_path: &str) -> BTreeSet<String> {
+        let Some(importer) = self.importers.get(importer_path) else {
+            return BTreeSet::new();
+        };
+        self.closure(
+            importer
+                .dependencies
+                .iter()
+                .chain(&importer.optional_dependencies)
+                .chain(&importer.peer_dependencies),
+        )
+    }
 
-        // Normalize the path (resolve .. and .)
-        let mut components = Vec::new();
-        for component in resolved.components() {
-            match component {
-                std::path::Component::ParentDir => {
-                    components.pop();
-                }
-                std::path::Component::Normal(s) => {
-                    components.push(s.to_string_lossy().to_string());
+    fn closure<'a>(&self, roots: impl Iterator<Item = (&'a String, &'a Dependency)>) -> BTreeSet<String> {
+        let mut closure = BTreeSet::new();
+        let mut queue: Vec<String> = roots.map(|(name, dep)| format!("{}@{}", name, dep.version)).collect();
+
+        while let Some(key) = queue.pop() {
+            if !closure.insert(key.clone()) {
+                continue;
+            }
+            if let Some(snapshot) = self.snapshots.get(&key) {
+                for (name, version) in snapshot.dependencies.iter().chain(&snapshot.optional_dependencies) {
+                    queue.push(format!("{}@{}", name, version));
                 }
-                std::path::Component::CurDir => {}
-                _ => {}
             }
         }
 
-        Some(components.join("/"))
+        closure
+    }
+
+    /// Reduce raw lockfile `content` to `importers` and the packages they resolve to
+    ///
+    /// Like `turbo prune`: an unrelated lock change then leaves the pruned file
+    /// (and Docker's install layer) untouched. Other top-level sections
+    /// (settings, catalogs, overrides, ...) are kept as is.
+    pub fn prune(&self, content: &str, importers: &[String]) -> Result<String> {
+        let snapshots: BTreeSet<String> = importers
+            .iter()
+            .flat_map(|importer| self.resolved_closure(importer))
+            .collect();
+        // "packages" keys are snapshot keys without the peer suffix
+        let packages: BTreeSet<&str> = snapshots
+            .iter()
+            .map(|key| key.split_once('(').map_or(key.as_str(), |(package, _)| package))
+            .collect();
+
+        let mut lock: serde_yaml::Mapping =
+            serde_yaml::from_str(content).with_context(|| "Failed to parse pnpm-lock.yaml")?;
+        retain_keys(&mut lock, "importers", |key| importers.iter().any(|i| i == key));
+        retain_keys(&mut lock, "snapshots", |key| snapshots.contains(key));
+        retain_keys(&mut lock, "packages", |key| packages.contains(key));
+
+        serde_yaml::to_string(&lock).with_context(|| "Failed to serialize pruned pnpm-lock.yaml")
     }
 
     /// Get all workspace package paths from importers
@@ -161,6 +256,65 @@ impl PnpmWorkspace {
     }
 }
 
+/// Keep only the entries of the `section` mapping whose key passes `keep`
+fn retain_keys(lock: &mut serde_yaml::Mapping, section: &str, keep: impl Fn(&str) -> bool) {
+    if let Some(serde_yaml::Value::Mapping(entries)) = lock.get_mut(section) {
+        entries.retain(|key, _| key.as_str().is_some_and(&keep));
+    }
+}
+
+/// pnpm-workspace.yaml listing exactly `packages` (e.g., for a pruned Docker context)
+///
+/// Other settings of the original (`content`), like catalogs, are kept.
+pub fn prune_workspace(content: Option<&str>, packages: &[String]) -> Result<String> {
+    let mut workspace: serde_yaml::Mapping = match content {
+        Some(content) => serde_yaml::from_str(content).with_context(|| "Failed to parse pnpm-workspace.yaml")?,
+        None => serde_yaml::Mapping::new(),
+    };
+    let packages = packages
+        .iter()
+        .filter(|p| *p != ".")
+        .map(|p| serde_yaml::Value::String(p.clone()))
+        .collect();
+    workspace.insert("packages".into(), serde_yaml::Value::Sequence(packages));
+
+    serde_yaml::to_string(&workspace).with_context(|| "Failed to serialize pnpm-workspace.yaml")
+}
+
+/// Join `rel` onto `base` and resolve `.`/`..` (e.g., ("apps/web", "../../libs/ui") -> "libs/ui")
+pub fn normalize_path(base: &str, rel: &str) -> String {
+    let mut components = Vec::new();
+    for component in Path::new(base).join(rel).components() {
+        match component {
+            std::path::Component::ParentDir => {
+                components.pop();
+            }
+            std::path::Component::Normal(s) => {
+                components.push(s.to_string_lossy().to_string());
+            }
+            _ => {}
+        }
+    }
+    components.join("/")
+}
+
+/// Importers whose resolved dependencies differ between two lockfiles
+///
+/// Includes importers added or removed between revisions. The root importer
+/// (".") is reported like any other; callers decide what it invalidates.
+pub fn changed_importers(old: &PnpmLock, new: &PnpmLock) -> Vec<String> {
+    let paths: BTreeSet<&String> = old.importers.keys().chain(new.importers.keys()).collect();
+
+    paths
+        .into_iter()
+        .filter(|path| {
+            old.importers.contains_key(*path) != new.importers.contains_key(*path)
+                || old.resolved_closure(path) != new.resolved_closure(path)
+        })
+        .cloned()
+        .collect()
+}
+
 /// Build workspace package map from lockfile
 /// Returns: path -> WorkspacePackage
 pub fn build_workspace_map(lock: &PnpmLock) -> HashMap<String, WorkspacePackage> {
@@ -250,6 +404,8 @@ mod tests {
         let lock = PnpmLock {
             lockfile_version: "9.0".to_string(),
             importers: HashMap::new(),
+            snapshots: HashMap::new(),
+            packages: HashMap::new(),
         };
 
         // apps/focustoday-api depends on link:../../libs/env-config
@@ -266,6 +422,119 @@ mod tests {
 
         // Non-link versions return None
         assert_eq!(lock.resolve_workspace_link("apps/foo", "1.2.3"), None);
+        assert_eq!(lock.resolve_workspace_link("apps/foo", "file:libs/unknown"), None);
         assert_eq!(lock.resolve_workspace_link("apps/foo", "workspace:*"), None);
     }
+
+    const LOCK_V1: &str = r#"
+lockfileVersion: '9.0'
+importers:
+  .:
+    devDependencies:
+      typescript:
+        specifier: ^5.0.0
+        version: 5.4.0
+  apps/web:
+    dependencies:
+      react:
+        specifier: ^18.0.0
+        version: 18.2.0
+      '@workspace/ui':
+        specifier: workspace:*
+        version: link:../../libs/ui
+  libs/ui:
+    dependencies:
+      clsx:
+        specifier: ^2.0.0
+        version: 2.1.0
+snapshots:
+  react@18.2.0:
+    dependencies:
+      loose-envify: 1.4.0
+  loose-envify@1.4.0: {}
+  clsx@2.1.0: {}
+  typescript@5.4.0: {}
+"#;
+
+    #[test]
+    fn test_file_dependency_resolves_to_importer() {
+        let lock = PnpmLock::parse(
+            r#"
+lockfileVersion: '9.0'
+importers:
+  apps/web:
+    dependencies:
+      ui:
+        specifier: file:../../libs/ui
+        version: file:libs/ui
+  libs/ui: {}
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(lock.get_workspace_deps("apps/web"), vec!["libs/ui"]);
+    }
+
+    #[test]
+    fn test_changed_importers_direct_dependency() {
+        let old = PnpmLock::parse(LOCK_V1).unwrap();
+        let new = PnpmLock::parse(&LOCK_V1.replace("2.1.0", "2.1.1")).unwrap();
+
+        assert_eq!(changed_importers(&old, &new), vec!["libs/ui"]);
+    }
+
+    #[test]
+    fn test_changed_importers_transitive_dependency() {
+        let old = PnpmLock::parse(LOCK_V1).unwrap();
+        let new = PnpmLock::parse(&LOCK_V1.replace("1.4.0", "1.4.1")).unwrap();
+
+        // Only apps/web reaches loose-envify (through react)
+        assert_eq!(changed_importers(&old, &new), vec!["apps/web"]);
+        assert!(changed_importers(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_prune_keeps_only_reachable_packages() {
+        let content = format!(
+            "{}packages:\n  react@18.2.0:\n    resolution: {{integrity: sha512-r}}\n  loose-envify@1.4.0: {{}}\n  clsx@2.1.0: {{}}\n  typescript@5.4.0: {{}}\nsettings:\n  autoInstallPeers: true\n",
+            LOCK_V1
+        );
+        let lock = PnpmLock::parse(&content).unwrap();
+
+        let pruned = lock.prune(&content, &["libs/ui".to_string()]).unwrap();
+        let pruned = PnpmLock::parse(&pruned).unwrap();
+        assert_eq!(pruned.importers.keys().collect::<Vec<_>>(), vec!["libs/ui"]);
+        assert_eq!(pruned.snapshots.keys().collect::<Vec<_>>(), vec!["clsx@2.1.0"]);
+
+        let importers = vec![".".to_string(), "apps/web".to_string(), "libs/ui".to_string()];
+        let pruned = lock.prune(&content, &importers).unwrap();
+        let value: serde_yaml::Value = serde_yaml::from_str(&pruned).unwrap();
+        assert_eq!(value["packages"].as_mapping().unwrap().len(), 4);
+        assert_eq!(value["packages"]["react@18.2.0"]["resolution"]["integrity"], "sha512-r");
+        assert_eq!(value["settings"]["autoInstallPeers"], true);
+    }
+
+    #[test]
+    fn test_prune_workspace_keeps_settings() {
+        let pruned = prune_workspace(
+            Some("packages:\n  - apps/*\n  - libs/*\ncatalog:\n  react: ^18.0.0\n"),
+            &[".".to_string(), "libs/ui".to_string(), "apps/web".to_string()],
+        )
+        .unwrap();
+        let value: serde_yaml::Value = serde_yaml::from_str(&pruned).unwrap();
+        assert_eq!(value["packages"], serde_yaml::from_str::<serde_yaml::Value>("[libs/ui, apps/web]").unwrap());
+        assert_eq!(value["catalog"]["react"], "^18.0.0");
+    }
+
+    #[test]
+    fn test_changed_importers_added_importer() {
+        let old = PnpmLock::parse(LOCK_V1).unwrap();
+        let new_content = LOCK_V1.replace(
+            "snapshots:",
+            "  libs/new: {}\nsnapshots:",
+        );
+        let new = PnpmLock::parse(&new_content).unwrap();
+
+        assert_eq!(changed_importers(&old, &new), vec!["libs/new"]);
+    }
 }
@@ -0,0 +1,216 @@
+//! Build timeline export (`--profile out.json`)
+//!
+//! Writes a Chrome trace-event file for `airis run` / `airis build` that can be
+//! opened in chrome://tracing or https://ui.perfetto.dev:
+//!
+//! - one lane per worker slot with a span for every task that ran
+//! - a "critical path" lane: the chain of tasks that decided the total time
+//! - cache hits and failures colored (and tagged in `args`)
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::executor::{ParallelExecutor, TaskResult, TaskSpan};
+
+const PID: u64 = 1;
+/// Lane (tid) for the critical path; worker lanes start at 1
+const CRITICAL_PATH_TID: u64 = 0;
+
+/// Write the trace for a finished `execute` and print the critical path
+pub fn write(path: &Path, title: &str, exec: &ParallelExecutor, results: &[TaskResult]) -> Result<()> {
+    let spans = exec.timeline();
+    let deps: HashMap<String, Vec<String>> = exec
+        .tasks()
+        .map(|t| (t.id.clone(), t.dependencies.clone()))
+        .collect();
+
+    let critical = critical_path(&spans, &deps);
+    let trace = chrome_trace(title, &spans, results, &critical);
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string(&trace)?)
+        .with_context(|| format!("Failed to write profile: {}", path.display()))?;
+
+    println!("{}", format!("📈 Profile written to {}", path.display()).cyan());
+    if !critical.is_empty() {
+        let by_id: HashMap<&str, &TaskSpan> = spans.iter().map(|s| (s.task_id.as_str(), s)).collect();
+        let total_us: u64 = critical.iter().filter_map(|id| by_id.get(id.as_str())).map(|s| s.end_us - s.start_us).sum();
+        println!(
+            "   Critical path ({:.1}s of work): {}",
+            total_us as f64 / 1_000_000.0,
+            critical.join(" → ")
+        );
+    }
+
+    Ok(())
+}
+
+/// Chain of tasks that determined when the run finished
+///
+/// Starts at the last task to finish and repeatedly steps to the dependency
+/// that finished last (the one it actually waited for).
+pub fn critical_path(spans: &[TaskSpan], deps: &HashMap<String, Vec<String>>) -> Vec<String> {
+    let by_id: HashMap<&str, &TaskSpan> = spans.iter().map(|s| (s.task_id.as_str(), s)).collect();
+
+    let Some(mut current) = spans.iter().max_by_key(|s| s.end_us) else {
+        return vec![];
+    };
+    let mut path = vec![current.task_id.clone()];
+
+    while let Some(next) = deps
+        .get(&current.task_id)
+        .into_iter()
+        .flatten()
+        .filter_map(|d| by_id.get(d.as_str()))
+        .max_by_key(|s| s.end_us)
+    {
+        path.push(next.task_id.clone());
+        current = next;
+    }
+
+    path.reverse();
+    path
+}
+
+/// Build the trace-event JSON object
+fn chrome_trace(title: &str, spans: &[TaskSpan], results: &[TaskResult], critical: &[String]) -> Value {
+    let results: HashMap<&str, &TaskResult> = results.iter().map(|r| (r.task_id.as_str(), r)).collect();
+    let lanes = spans.iter().map(|s| s.lane + 1).max().unwrap_or(0);
+
+    let mut events = vec![
+        metadata("process_name", CRITICAL_PATH_TID, title),
+        metadata("thread_name", CRITICAL_PATH_TID, "critical path"),
+    ];
+    for lane in 0..lanes {
+        events.push(metadata("thread_name", lane as u64 + 1, &format!("worker {}", lane + 1)));
+    }
+
+    for span in spans {
+        let result = results.get(span.task_id.as_str());
+        let cached = result.is_some_and(|r| r.cached);
+        let success = result.is_none_or(|r| r.success);
+        let on_critical_path = critical.contains(&span.task_id);
+
+        let mut event = json!({
+            "name": span.task_id,
+            "cat": if cached { "cache-hit" } else { "task" },
+            "ph": "X",
+            "ts": span.start_us,
+            "dur": span.end_us - span.start_us,
+            "pid": PID,
+            "tid": span.lane as u64 + 1,
+            "args": {
+                "status": if !success { "failed" } else if cached { "cached" } else { "succeeded" },
+                "critical_path": on_critical_path,
+            },
+        });
+        // Reserved trace-viewer colors
+        if cached {
+            event["cname"] = json!("good");
+        } else if !success {
+            event["cname"] = json!("terrible");
+            event["args"]["error"] = json!(result.and_then(|r| r.error.clone()));
+        }
+        events.push(event.clone());
+
+        if on_critical_path {
+            event["tid"] = json!(CRITICAL_PATH_TID);
+            event["cat"] = json!("critical-path");
+            events.push(event);
+        }
+    }
+
+    json!({
+        "traceEvents": events,
+        "displayTimeUnit": "ms",
+        "otherData": {
+            "airis_version": env!("CARGO_PKG_VERSION"),
+            "critical_path": critical,
+        },
+    })
+}
+
+fn metadata(name: &str, tid: u64, value: &str) -> Value {
+    json!({ "name": name, "ph": "M", "pid": PID, "tid": tid, "args": { "name": value } })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(id: &str, lane: usize, start_us: u64, end_us: u64) -> TaskSpan {
+        TaskSpan { task_id: id.to_string(), lane, start_us, end_us }
+    }
+
+    fn result(id: &str, success: bool, cached: bool) -> TaskResult {
+        TaskResult {
+            task_id: id.to_string(),
+            success,
+            duration_ms: 0,
+            error: (!success).then(|| "exit code Some(1)".to_string()),
+            cached,
+            skipped: false,
+        }
+    }
+
+    /// web waits for ui (slow) and utils (fast); ui waits for core
+    fn sample() -> (Vec<TaskSpan>, HashMap<String, Vec<String>>) {
+        let spans = vec![
+            span("core", 0, 0, 100),
+            span("utils", 1, 0, 20),
+            span("ui", 0, 100, 400),
+            span("web", 0, 400, 450),
+        ];
+        let deps = HashMap::from([
+            ("web".to_string(), vec!["ui".to_string(), "utils".to_string()]),
+            ("ui".to_string(), vec!["core".to_string()]),
+        ]);
+        (spans, deps)
+    }
+
+    #[test]
+    fn test_critical_path() {
+        let (spans, deps) = sample();
+        assert_eq!(critical_path(&spans, &deps), vec!["core", "ui", "web"]);
+        assert!(critical_path(&[], &deps).is_empty());
+    }
+
+    #[test]
+    fn test_chrome_trace_events() {
+        let (spans, deps) = sample();
+        let critical = critical_path(&spans, &deps);
+        let results = vec![
+            result("core", true, true),
+            result("utils", true, false),
+            result("ui", true, false),
+            result("web", false, false),
+        ];
+
+        let trace = chrome_trace("airis run build", &spans, &results, &critical);
+        let events = trace["traceEvents"].as_array().unwrap();
+
+        let complete: Vec<&Value> = events.iter().filter(|e| e["ph"] == "X").collect();
+        // 4 worker spans + 3 copies on the critical path lane
+        assert_eq!(complete.len(), 7);
+
+        let core = complete.iter().find(|e| e["name"] == "core" && e["tid"] == 1).unwrap();
+        assert_eq!(core["cat"], "cache-hit");
+        assert_eq!(core["cname"], "good");
+
+        let web = complete.iter().find(|e| e["name"] == "web" && e["tid"] == 1).unwrap();
+        assert_eq!(web["args"]["status"], "failed");
+        assert_eq!(web["dur"], 50);
+
+        let threads: Vec<&str> = events
+            .iter()
+            .filter(|e| e["name"] == "thread_name")
+            .map(|e| e["args"]["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(threads, vec!["critical path", "worker 1", "worker 2"]);
+    }
+}
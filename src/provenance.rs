@@ -0,0 +1,392 @@
+//! Build provenance for bundles: an in-toto Statement (SLSA Provenance v1)
+//! in a DSSE envelope
+//!
+//! The statement's subjects are the bundle's files with their SHA-256; the
+//! predicate records the materials (source commit, lockfile, base image),
+//! the build command, the builder identity and the content hash.
+//!
+//! With a key (`--sign-key` or `AIRIS_PROVENANCE_KEY`) the envelope carries
+//! an HMAC-SHA256 signature over the DSSE pre-authentication encoding, like
+//! remote cache artifacts. Verifying needs the same key.
+
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Envelope file in the bundle directory
+pub const FILE_NAME: &str = "provenance.intoto.json";
+
+/// Default environment variable for the signing key
+pub const DEFAULT_KEY_ENV: &str = "AIRIS_PROVENANCE_KEY";
+
+const PAYLOAD_TYPE: &str = "application/vnd.in-toto+json";
+const STATEMENT_TYPE: &str = "https://in-toto.io/Statement/v1";
+const PREDICATE_TYPE: &str = "https://slsa.dev/provenance/v1";
+const BUILD_TYPE: &str = "https://github.com/agiletec-inc/airis-monorepo/bundle/v1";
+
+/// in-toto Statement v1
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Statement {
+    #[serde(rename = "_type")]
+    pub statement_type: String,
+    pub subject: Vec<ResourceDescriptor>,
+    pub predicate_type: String,
+    pub predicate: Value,
+}
+
+/// A subject or material: name/URI and digests ("sha256" -> hex)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourceDescriptor {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    pub digest: BTreeMap<String, String>,
+}
+
+impl ResourceDescriptor {
+    pub fn material(uri: impl Into<String>, algorithm: &str, digest: impl Into<String>) -> Self {
+        Self {
+            name: None,
+            uri: Some(uri.into()),
+            digest: BTreeMap::from([(algorithm.to_string(), digest.into())]),
+        }
+    }
+}
+
+/// DSSE envelope
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Envelope {
+    pub payload_type: String,
+    /// Base64 of the serialized statement
+    pub payload: String,
+    pub signatures: Vec<Signature>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Signature {
+    /// First 16 hex digits of the key's SHA-256
+    pub keyid: String,
+    /// Base64 HMAC-SHA256 over the DSSE PAE
+    pub sig: String,
+}
+
+/// What a bundle's image was built from, and how
+#[derive(Debug, Clone)]
+pub struct Provenance {
+    pub target: String,
+    pub channel: String,
+    pub platforms: Vec<String>,
+    pub content_hash: String,
+    pub image_ref: String,
+    /// Command that builds the bundled image
+    pub command: String,
+    pub builder_id: String,
+    pub built_at: String,
+    pub materials: Vec<ResourceDescriptor>,
+}
+
+impl Provenance {
+    /// SLSA Provenance v1 predicate
+    pub fn predicate(&self) -> Value {
+        json!({
+            "buildDefinition": {
+                "buildType": BUILD_TYPE,
+                "externalParameters": {
+                    "target": self.target,
+                    "channel": self.channel,
+                    "platforms": self.platforms,
+                    "command": self.command,
+                },
+                "internalParameters": {
+                    "contentHash": self.content_hash,
+                    "imageRef": self.image_ref,
+                },
+                "resolvedDependencies": self.materials,
+            },
+            "runDetails": {
+                "builder": {
+                    "id": self.builder_id,
+                    "version": {"airis": env!("CARGO_PKG_VERSION")},
+                },
+                "metadata": {
+                    "invocationId": self.content_hash,
+                    "finishedOn": self.built_at,
+                },
+            },
+        })
+    }
+
+    /// Statement over every file in `dir` (except the envelope itself)
+    pub fn statement(&self, dir: &Path) -> Result<Statement> {
+        Ok(Statement {
+            statement_type: STATEMENT_TYPE.to_string(),
+            subject: subjects(dir)?,
+            predicate_type: PREDICATE_TYPE.to_string(),
+            predicate: self.predicate(),
+        })
+    }
+}
+
+/// Who is building: `AIRIS_BUILDER_ID`, the GitHub Actions run, or this host
+pub fn builder_id() -> String {
+    if let Ok(id) = std::env::var("AIRIS_BUILDER_ID")
+        && !id.is_empty() {
+            return id;
+        }
+    if let (Ok(server), Ok(repo), Ok(run)) = (
+        std::env::var("GITHUB_SERVER_URL"),
+        std::env::var("GITHUB_REPOSITORY"),
+        std::env::var("GITHUB_RUN_ID"),
+    ) {
+        return format!("{}/{}/actions/runs/{}", server, repo, run);
+    }
+    let host = Command::new("hostname")
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_else(|| "localhost".to_string());
+    format!("local://{}", host)
+}
+
+/// Signing key from `path`, else `AIRIS_PROVENANCE_KEY`
+pub fn load_key(path: Option<&Path>) -> Result<Option<Vec<u8>>> {
+    if let Some(path) = path {
+        let key = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read provenance signing key {}", path.display()))?;
+        return Ok(Some(key.trim().as_bytes().to_vec()));
+    }
+    Ok(std::env::var(DEFAULT_KEY_ENV).ok().filter(|k| !k.is_empty()).map(String::into_bytes))
+}
+
+/// Hex SHA-256 of a file
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Files under `dir` with their SHA-256, by '/'-separated relative path
+fn subjects(dir: &Path) -> Result<Vec<ResourceDescriptor>> {
+    let mut subjects = Vec::new();
+    for entry in walkdir::WalkDir::new(dir).sort_by_file_name() {
+        let entry = entry?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let rel = entry.path().strip_prefix(dir)?;
+        if rel == Path::new(FILE_NAME) {
+            continue;
+        }
+        let name = rel.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+        subjects.push(ResourceDescriptor {
+            name: Some(name),
+            uri: None,
+            digest: BTreeMap::from([("sha256".to_string(), sha256_file(entry.path())?)]),
+        });
+    }
+    Ok(subjects)
+}
+
+/// DSSE pre-authentication encoding
+fn pae(payload_type: &str, payload: &[u8]) -> Vec<u8> {
+    let mut pae = format!("DSSEv1 {} {} {} ", payload_type.len(), payload_type, payload.len()).into_bytes();
+    pae.extend_from_slice(payload);
+    pae
+}
+
+fn mac(key: &[u8], payload_type: &str, payload: &[u8]) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(&pae(payload_type, payload));
+    mac
+}
+
+fn keyid(key: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(key))[..16].to_string()
+}
+
+/// Wrap a statement in an envelope, signed if there is a key
+pub fn seal(statement: &Statement, key: Option<&[u8]>) -> Result<Envelope> {
+    let payload = serde_json::to_vec(statement)?;
+    let signatures = key
+        .map(|key| Signature {
+            keyid: keyid(key),
+            sig: base64::engine::general_purpose::STANDARD
+                .encode(mac(key, PAYLOAD_TYPE, &payload).finalize().into_bytes()),
+        })
+        .into_iter()
+        .collect();
+    Ok(Envelope {
+        payload_type: PAYLOAD_TYPE.to_string(),
+        payload: base64::engine::general_purpose::STANDARD.encode(&payload),
+        signatures,
+    })
+}
+
+/// Result of [`verify`]
+#[derive(Debug)]
+pub struct Verified {
+    pub statement: Statement,
+    /// Subjects whose digest matched
+    pub files: usize,
+    /// Whether a signature was checked (a key was given)
+    pub signed: bool,
+}
+
+/// Check `dir`'s files against its provenance
+///
+/// With a key the envelope must carry a matching signature. Every subject
+/// must exist with its digest, and the bundle may not hold unlisted files.
+pub fn verify(dir: &Path, key: Option<&[u8]>, allow_unsigned: bool) -> Result<Verified> {
+    let path = dir.join(FILE_NAME);
+    let content = fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let envelope: Envelope = serde_json::from_str(&content).context("provenance is not a DSSE envelope")?;
+    if envelope.payload_type != PAYLOAD_TYPE {
+        bail!("unexpected payload type {}", envelope.payload_type);
+    }
+    let payload = base64::engine::general_purpose::STANDARD
+        .decode(&envelope.payload)
+        .context("malformed envelope payload")?;
+
+    // A signed bundle must not pass just because the key was left out
+    if key.is_none() && !envelope.signatures.is_empty() && !allow_unsigned {
+        bail!(
+            "provenance is signed, but no key was given to check it \
+             (pass --key or set {}, or --allow-unsigned to skip the signature)",
+            DEFAULT_KEY_ENV
+        );
+    }
+    if let Some(key) = key {
+        let keyid = keyid(key);
+        let Some(signature) = envelope.signatures.iter().find(|s| s.keyid == keyid) else {
+            bail!("provenance is not signed with this key ({})", keyid);
+        };
+        let sig = base64::engine::general_purpose::STANDARD
+            .decode(&signature.sig)
+            .context("malformed signature")?;
+        mac(key, PAYLOAD_TYPE, &payload)
+            .verify_slice(&sig)
+            .map_err(|_| anyhow::anyhow!("provenance signature mismatch"))?;
+    }
+
+    let statement: Statement = serde_json::from_slice(&payload).context("Failed to parse provenance statement")?;
+    if statement.statement_type != STATEMENT_TYPE || statement.predicate_type != PREDICATE_TYPE {
+        bail!("unsupported statement {} / {}", statement.statement_type, statement.predicate_type);
+    }
+
+    let actual: BTreeMap<String, BTreeMap<String, String>> = subjects(dir)?
+        .into_iter()
+        .filter_map(|s| Some((s.name?, s.digest)))
+        .collect();
+    let mut problems = Vec::new();
+    for subject in &statement.subject {
+        let name = subject.name.as_deref().unwrap_or_default();
+        match actual.get(name) {
+            None => problems.push(format!("missing: {}", name)),
+            Some(digest) if digest.get("sha256") != subject.digest.get("sha256") => {
+                problems.push(format!("modified: {}", name))
+            }
+            Some(_) => {}
+        }
+    }
+    for name in actual.keys() {
+        if !statement.subject.iter().any(|s| s.name.as_deref() == Some(name)) {
+            problems.push(format!("not in provenance: {}", name));
+        }
+    }
+    if !problems.is_empty() {
+        bail!("bundle does not match its provenance:\n  {}", problems.join("\n  "));
+    }
+
+    Ok(Verified {
+        files: statement.subject.len(),
+        statement,
+        signed: key.is_some(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle() -> (tempfile::TempDir, Provenance) {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("bundle.json"), "{}").unwrap();
+        fs::create_dir_all(dir.path().join("image/blobs/sha256")).unwrap();
+        fs::write(dir.path().join("image/index.json"), "{}").unwrap();
+        let provenance = Provenance {
+            target: "apps/web".to_string(),
+            channel: "lts".to_string(),
+            platforms: Vec::new(),
+            content_hash: "abc123".to_string(),
+            image_ref: "web:airis-abc123".to_string(),
+            command: "airis build --docker apps/web --channel lts".to_string(),
+            builder_id: "local://test".to_string(),
+            built_at: "2025-01-01T00:00:00Z".to_string(),
+            materials: vec![ResourceDescriptor::material("pnpm-lock.yaml", "sha256", "00ff")],
+        };
+        (dir, provenance)
+    }
+
+    fn write(dir: &Path, provenance: &Provenance, key: Option<&[u8]>) {
+        let envelope = seal(&provenance.statement(dir).unwrap(), key).unwrap();
+        fs::write(dir.join(FILE_NAME), serde_json::to_string(&envelope).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_statement_lists_bundle_files() {
+        let (dir, provenance) = bundle();
+        let statement = provenance.statement(dir.path()).unwrap();
+        let names: Vec<&str> = statement.subject.iter().filter_map(|s| s.name.as_deref()).collect();
+        assert_eq!(names, vec!["bundle.json", "image/index.json"]);
+        assert_eq!(
+            statement.subject[0].digest["sha256"],
+            "44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a"
+        );
+        assert_eq!(statement.predicate["buildDefinition"]["internalParameters"]["contentHash"], "abc123");
+        assert_eq!(statement.predicate["buildDefinition"]["resolvedDependencies"][0]["uri"], "pnpm-lock.yaml");
+    }
+
+    #[test]
+    fn test_verify_signed_bundle() {
+        let (dir, provenance) = bundle();
+        write(dir.path(), &provenance, Some(b"secret"));
+
+        let verified = verify(dir.path(), Some(b"secret"), false).unwrap();
+        assert_eq!(verified.files, 2);
+        assert!(verified.signed);
+        // Without a key, a signed bundle only passes when explicitly allowed
+        let err = verify(dir.path(), None, false).unwrap_err();
+        assert!(err.to_string().contains("--allow-unsigned"));
+        assert!(!verify(dir.path(), None, true).unwrap().signed);
+
+        let err = verify(dir.path(), Some(b"other"), false).unwrap_err();
+        assert!(err.to_string().contains("not signed with this key"));
+    }
+
+    #[test]
+    fn test_verify_detects_tampering() {
+        let (dir, provenance) = bundle();
+        write(dir.path(), &provenance, None);
+        assert!(verify(dir.path(), Some(b"secret"), false).is_err());
+
+        fs::write(dir.path().join("bundle.json"), r#"{"tampered":true}"#).unwrap();
+        fs::write(dir.path().join("extra.sh"), "").unwrap();
+        fs::remove_file(dir.path().join("image/index.json")).unwrap();
+        let err = verify(dir.path(), None, false).unwrap_err().to_string();
+        assert!(err.contains("modified: bundle.json"));
+        assert!(err.contains("missing: image/index.json"));
+        assert!(err.contains("not in provenance: extra.sh"));
+    }
+}
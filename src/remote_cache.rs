@@ -1,31 +1,62 @@
 //! Remote cache for Docker build artifacts
 //!
-//! Supports S3 and OCI registry backends for sharing build cache across CI/CD.
+//! Supports S3, OCI registry, HTTP and shared-filesystem backends for sharing
+//! build cache across CI/CD. Every backend implements [`RemoteBackend`].
 //!
 //! # Usage
 //!
 //! ```ignore
-//! // S3 backend
+//! // S3 backend (uses aws CLI)
 //! let remote = Remote::parse("s3://bucket/prefix")?;
 //!
-//! // OCI backend
+//! // OCI backend (uses oras CLI)
 //! let remote = Remote::parse("oci://ghcr.io/org/cache")?;
 //!
+//! // HTTP backend (Turborepo remote cache protocol, no CLI required)
+//! let remote = Remote::parse("https://cache.example.com")?;
+//!
+//! // Shared directory (e.g., NFS mount)
+//! let remote = Remote::parse("file:///mnt/airis-cache")?;
+//!
+//! // Signing key and verify mode from [remote_cache] in manifest.toml
+//! let signing = Signing::from_manifest(&manifest.remote_cache, &root, None)?;
+//!
 //! // Check for cache hit
-//! if let Some(artifact) = remote_hit("apps/web", "abc123", &remote)? {
+//! if let Some(artifact) = remote_hit("apps/web", "abc123", &remote, &signing)? {
 //!     println!("Cache hit: {}", artifact.image_ref);
 //! }
 //!
 //! // Store after build
-//! remote_store("apps/web", "abc123", &artifact, &remote)?;
+//! remote_store("apps/web", "abc123", &artifact, &remote, &signing)?;
 //! ```
+//!
+//! # Integrity
+//!
+//! Artifacts are stored in an envelope carrying a BLAKE3 digest of the payload
+//! and, when a signing key is configured, an HMAC-SHA256 signature over the
+//! project, hash and payload. Entries that fail verification are rejected
+//! (`strict`) or used with a warning (`warn`).
 
 use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::time::Duration;
 
 use crate::docker_build::CachedArtifact;
+use crate::manifest::{RemoteCacheSection, RemoteCacheVerify};
+
+/// Envelope format version
+const ENVELOPE_VERSION: u32 = 1;
+
+/// Default environment variable for the signing key
+pub const DEFAULT_SIGNING_KEY_ENV: &str = "AIRIS_REMOTE_CACHE_KEY";
 
-/// Remote cache backend
+/// Remote cache location
 #[derive(Debug, Clone)]
 pub enum Remote {
     /// S3 bucket storage
@@ -37,6 +68,22 @@ pub enum Remote {
     Oci {
         registry: String,
     },
+    /// HTTP server speaking the Turborepo remote cache protocol
+    Http {
+        base_url: String,
+    },
+    /// Shared directory (local disk or NFS mount)
+    File {
+        dir: PathBuf,
+    },
+}
+
+/// Storage backend for raw cache artifacts
+pub trait RemoteBackend {
+    /// Fetch artifact bytes (None on cache miss)
+    fn get(&self, project: &str, hash: &str) -> Result<Option<Vec<u8>>>;
+    /// Store artifact bytes
+    fn put(&self, project: &str, hash: &str, data: &[u8]) -> Result<()>;
 }
 
 impl Remote {
@@ -45,6 +92,8 @@ impl Remote {
     /// Supported formats:
     /// - `s3://bucket/prefix`
     /// - `oci://registry/image`
+    /// - `http://host[/path]` or `https://host[/path]`
+    /// - `file:///path/to/dir`
     pub fn parse(url: &str) -> Result<Self> {
         if let Some(rest) = url.strip_prefix("s3://") {
             let parts: Vec<&str> = rest.splitn(2, '/').collect();
@@ -63,52 +112,254 @@ impl Remote {
             Ok(Remote::Oci {
                 registry: rest.to_string(),
             })
+        } else if url.starts_with("http://") || url.starts_with("https://") {
+            let host = url.split_once("://").map(|(_, h)| h).unwrap_or_default();
+            if host.is_empty() {
+                bail!("Invalid HTTP URL: missing host");
+            }
+            Ok(Remote::Http {
+                base_url: url.trim_end_matches('/').to_string(),
+            })
+        } else if let Some(rest) = url.strip_prefix("file://") {
+            if rest.is_empty() {
+                bail!("Invalid file URL: missing directory");
+            }
+            Ok(Remote::File {
+                dir: PathBuf::from(rest),
+            })
         } else {
             bail!(
-                "Invalid remote cache URL: '{}'. Expected s3://bucket/prefix or oci://registry/image",
+                "Invalid remote cache URL: '{}'. Expected s3://bucket/prefix, oci://registry/image, https://host or file:///path",
                 url
             )
         }
     }
 
-    /// Get cache key path for S3
-    fn s3_key(&self, project: &str, hash: &str) -> String {
-        let project_safe = project.replace('/', "_");
+    /// Get the storage backend for this remote
+    pub fn backend(&self) -> Box<dyn RemoteBackend> {
         match self {
-            Remote::S3 { prefix, .. } => {
-                if prefix.is_empty() {
-                    format!("{}/{}/artifact.json", project_safe, hash)
-                } else {
-                    format!("{}/{}/{}/artifact.json", prefix, project_safe, hash)
+            Remote::S3 { bucket, prefix } => Box::new(S3Backend {
+                bucket: bucket.clone(),
+                prefix: prefix.clone(),
+            }),
+            Remote::Oci { registry } => Box::new(OciBackend {
+                registry: registry.clone(),
+            }),
+            Remote::Http { base_url } => Box::new(HttpBackend::new(base_url)),
+            Remote::File { dir } => Box::new(FileBackend { dir: dir.clone() }),
+        }
+    }
+}
+
+/// Signing key and verification policy for remote artifacts
+#[derive(Debug, Clone, Default)]
+pub struct Signing {
+    key: Option<Vec<u8>>,
+    /// Where the key was expected, for error messages
+    key_env: Option<String>,
+    pub verify: RemoteCacheVerify,
+}
+
+impl Signing {
+    pub fn new(key: Option<Vec<u8>>, verify: RemoteCacheVerify) -> Self {
+        Self { key, key_env: None, verify }
+    }
+
+    /// Load the key from `[remote_cache]` (env var, then key file)
+    ///
+    /// Falls back to `AIRIS_REMOTE_CACHE_KEY` when no key source is configured.
+    /// `verify` overrides the manifest's verify mode (e.g., from the CLI); with
+    /// neither, caches are strict once a key is available and warn until then.
+    pub fn from_manifest(
+        section: &RemoteCacheSection,
+        root: &Path,
+        verify: Option<RemoteCacheVerify>,
+    ) -> Result<Self> {
+        let env_name = section
+            .signing_key_env
+            .as_deref()
+            .unwrap_or(DEFAULT_SIGNING_KEY_ENV);
+
+        let key = match std::env::var(env_name).ok().filter(|k| !k.is_empty()) {
+            Some(key) => Some(key.into_bytes()),
+            None => match &section.signing_key_file {
+                Some(file) => {
+                    let path = root.join(file);
+                    let key = std::fs::read_to_string(&path).with_context(|| {
+                        format!("Failed to read remote cache signing key {}", path.display())
+                    })?;
+                    Some(key.trim().as_bytes().to_vec())
                 }
-            }
-            _ => unreachable!(),
+                None => None,
+            },
+        };
+
+        let verify = verify.or(section.verify).unwrap_or(match key {
+            Some(_) => RemoteCacheVerify::Strict,
+            None => RemoteCacheVerify::Warn,
+        });
+        if key.is_none() && section.signing_key_env.is_some() && verify == RemoteCacheVerify::Warn {
+            eprintln!(
+                "{}",
+                format!("⚠️  {} is not set; remote cache artifacts will not be signed", env_name)
+                    .yellow()
+            );
         }
+
+        Ok(Self {
+            key_env: Some(env_name.to_string()),
+            ..Self::new(key, verify)
+        })
     }
 
-    /// Get OCI tag for cache
-    fn oci_tag(&self, project: &str, hash: &str) -> String {
-        let project_safe = project.replace('/', "-");
-        match self {
-            Remote::Oci { registry } => {
-                format!("{}:{}-{}", registry, project_safe, hash)
+    /// Strict mode can't verify anything without a key, so refuse to run
+    pub fn require_key(&self) -> Result<()> {
+        if self.key.is_none() && self.verify == RemoteCacheVerify::Strict {
+            bail!(
+                "Remote cache verify mode is strict but no signing key is set. \
+                 Set {} (or [remote_cache] signing_key_file), or pass --remote-cache-verify=warn",
+                self.key_env.as_deref().unwrap_or(DEFAULT_SIGNING_KEY_ENV)
+            );
+        }
+        Ok(())
+    }
+
+    /// Load from `<root>/manifest.toml` if present, otherwise defaults
+    pub fn load(root: &Path, verify: Option<RemoteCacheVerify>) -> Result<Self> {
+        let manifest_path = root.join("manifest.toml");
+        let section = if manifest_path.exists() {
+            crate::manifest::Manifest::load(&manifest_path)?.remote_cache
+        } else {
+            RemoteCacheSection::default()
+        };
+        Self::from_manifest(&section, root, verify)
+    }
+
+    fn mac(&self, key: &[u8], project: &str, hash: &str, payload: &str) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+        // Bind the signature to the cache key so entries can't be replayed elsewhere
+        mac.update(project.as_bytes());
+        mac.update(b"\0");
+        mac.update(hash.as_bytes());
+        mac.update(b"\0");
+        mac.update(payload.as_bytes());
+        mac
+    }
+
+    /// Wrap an artifact in a digest (and signature, if a key is configured)
+    pub fn seal(&self, project: &str, hash: &str, artifact: &CachedArtifact) -> Result<Envelope> {
+        let payload = serde_json::to_string(artifact)?;
+        let signature = self.key.as_ref().map(|key| {
+            hex(&self.mac(key, project, hash, &payload).finalize().into_bytes())
+        });
+
+        Ok(Envelope {
+            version: ENVELOPE_VERSION,
+            digest: blake3::hash(payload.as_bytes()).to_hex().to_string(),
+            algorithm: signature.as_ref().map(|_| "hmac-sha256".to_string()),
+            signature,
+            payload,
+        })
+    }
+
+    /// Verify raw entry bytes and extract the artifact
+    pub fn open(&self, project: &str, hash: &str, data: &[u8]) -> Result<CachedArtifact> {
+        let envelope: Envelope = serde_json::from_slice(data)
+            .context("entry is not a valid artifact envelope (truncated or unsigned?)")?;
+
+        if envelope.version != ENVELOPE_VERSION {
+            bail!("unsupported envelope version {}", envelope.version);
+        }
+
+        let digest = blake3::hash(envelope.payload.as_bytes()).to_hex().to_string();
+        if digest != envelope.digest {
+            bail!("BLAKE3 digest mismatch (payload was modified)");
+        }
+
+        match &self.key {
+            Some(key) => {
+                let Some(signature) = &envelope.signature else {
+                    bail!("entry is not signed");
+                };
+                let signature = unhex(signature).context("malformed signature")?;
+                self.mac(key, project, hash, &envelope.payload)
+                    .verify_slice(&signature)
+                    .map_err(|_| anyhow::anyhow!("signature mismatch"))?;
+            }
+            None if self.verify == RemoteCacheVerify::Strict => {
+                bail!("no signing key to verify the entry with");
             }
-            _ => unreachable!(),
+            None => {}
         }
+
+        let artifact: CachedArtifact = serde_json::from_str(&envelope.payload)
+            .context("Failed to parse cached artifact payload")?;
+
+        if artifact.hash != hash || artifact.target != project {
+            bail!(
+                "artifact is for {}@{}, expected {}@{}",
+                artifact.target,
+                artifact.hash,
+                project,
+                hash
+            );
+        }
+
+        Ok(artifact)
     }
 }
 
+/// Stored remote cache entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Envelope {
+    pub version: u32,
+    /// BLAKE3 hex digest of `payload`
+    pub digest: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub algorithm: Option<String>,
+    /// Hex HMAC-SHA256 over project, hash and payload
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Serialized `CachedArtifact`, kept verbatim so the digest is stable
+    pub payload: String,
+}
+
 /// Check for remote cache hit
-pub fn remote_hit(project: &str, hash: &str, remote: &Remote) -> Result<Option<CachedArtifact>> {
-    match remote {
-        Remote::S3 { bucket, .. } => {
-            let key = remote.s3_key(project, hash);
-            s3_get(bucket, &key)
-        }
-        Remote::Oci { .. } => {
-            let tag = remote.oci_tag(project, hash);
-            oci_pull(&tag)
-        }
+pub fn remote_hit(
+    project: &str,
+    hash: &str,
+    remote: &Remote,
+    signing: &Signing,
+) -> Result<Option<CachedArtifact>> {
+    signing.require_key()?;
+    let Some(data) = remote.backend().get(project, hash)? else {
+        return Ok(None);
+    };
+
+    match signing.open(project, hash, &data) {
+        Ok(artifact) => Ok(Some(artifact)),
+        Err(e) => match signing.verify {
+            RemoteCacheVerify::Strict => {
+                eprintln!(
+                    "{}",
+                    format!("  ❌ Rejected remote cache entry {}@{}: {:#}", project, hash, e).red()
+                );
+                Ok(None)
+            }
+            RemoteCacheVerify::Warn => {
+                eprintln!(
+                    "{}",
+                    format!("  ⚠️  Unverified remote cache entry {}@{}: {:#}", project, hash, e)
+                        .yellow()
+                );
+                // Accept legacy (bare artifact) and unsigned entries if they still parse
+                let artifact = serde_json::from_slice::<Envelope>(&data)
+                    .ok()
+                    .and_then(|env| serde_json::from_str(&env.payload).ok())
+                    .or_else(|| serde_json::from_slice(&data).ok());
+                Ok(artifact)
+            }
+        },
     }
 }
 
@@ -118,149 +369,315 @@ pub fn remote_store(
     hash: &str,
     artifact: &CachedArtifact,
     remote: &Remote,
+    signing: &Signing,
 ) -> Result<()> {
-    match remote {
-        Remote::S3 { bucket, .. } => {
-            let key = remote.s3_key(project, hash);
-            s3_put(bucket, &key, artifact)
-        }
-        Remote::Oci { .. } => {
-            let tag = remote.oci_tag(project, hash);
-            oci_push(&tag, artifact)
-        }
-    }
+    signing.require_key()?;
+    // The OCI layout path only means something on this machine
+    let artifact = CachedArtifact {
+        oci_layout: None,
+        ..artifact.clone()
+    };
+    let envelope = signing.seal(project, hash, &artifact)?;
+    let content = serde_json::to_string_pretty(&envelope)?;
+    remote.backend().put(project, hash, content.as_bytes())
 }
 
 // =============================================================================
 // S3 Backend (uses AWS CLI)
 // =============================================================================
 
-fn s3_get(bucket: &str, key: &str) -> Result<Option<CachedArtifact>> {
-    let url = format!("s3://{}/{}", bucket, key);
-
-    // Try to get the artifact
-    let output = Command::new("aws")
-        .args(["s3", "cp", &url, "-"])
-        .output()
-        .context("Failed to run aws s3 cp (is AWS CLI installed?)")?;
+pub struct S3Backend {
+    bucket: String,
+    prefix: String,
+}
 
-    if !output.status.success() {
-        // Not found or error - treat as cache miss
-        return Ok(None);
+impl S3Backend {
+    /// Get cache key path for S3
+    fn key(&self, project: &str, hash: &str) -> String {
+        let project_safe = project.replace('/', "_");
+        if self.prefix.is_empty() {
+            format!("{}/{}/artifact.json", project_safe, hash)
+        } else {
+            format!("{}/{}/{}/artifact.json", self.prefix, project_safe, hash)
+        }
     }
+}
 
-    let content = String::from_utf8(output.stdout)
-        .context("Invalid UTF-8 from S3")?;
+impl RemoteBackend for S3Backend {
+    fn get(&self, project: &str, hash: &str) -> Result<Option<Vec<u8>>> {
+        let url = format!("s3://{}/{}", self.bucket, self.key(project, hash));
 
-    let artifact: CachedArtifact = serde_json::from_str(&content)
-        .context("Failed to parse cached artifact from S3")?;
+        let output = Command::new("aws")
+            .args(["s3", "cp", &url, "-"])
+            .output()
+            .context("Failed to run aws s3 cp (is AWS CLI installed?)")?;
 
-    Ok(Some(artifact))
-}
+        if !output.status.success() {
+            // Not found or error - treat as cache miss
+            return Ok(None);
+        }
 
-fn s3_put(bucket: &str, key: &str, artifact: &CachedArtifact) -> Result<()> {
-    let url = format!("s3://{}/{}", bucket, key);
-    let content = serde_json::to_string_pretty(artifact)?;
+        Ok(Some(output.stdout))
+    }
 
-    // Write to temp file first
-    let temp_file = std::env::temp_dir().join(format!("airis-cache-{}.json", uuid_v4()));
-    std::fs::write(&temp_file, &content)?;
+    fn put(&self, project: &str, hash: &str, data: &[u8]) -> Result<()> {
+        let url = format!("s3://{}/{}", self.bucket, self.key(project, hash));
 
-    let temp_file_str = temp_file
-        .to_str()
-        .context("Temp file path contains non-UTF-8 characters")?;
+        // Write to temp file first
+        let temp_file = std::env::temp_dir().join(format!("airis-cache-{}.json", uuid_v4()));
+        std::fs::write(&temp_file, data)?;
 
-    let output = Command::new("aws")
-        .args(["s3", "cp", temp_file_str, &url])
-        .output()
-        .context("Failed to run aws s3 cp")?;
+        let temp_file_str = temp_file
+            .to_str()
+            .context("Temp file path contains non-UTF-8 characters")?;
 
-    // Cleanup temp file
-    let _ = std::fs::remove_file(&temp_file);
+        let output = Command::new("aws")
+            .args(["s3", "cp", temp_file_str, &url])
+            .output()
+            .context("Failed to run aws s3 cp")?;
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        bail!("Failed to upload to S3: {}", stderr);
-    }
+        // Cleanup temp file
+        let _ = std::fs::remove_file(&temp_file);
 
-    Ok(())
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!("Failed to upload to S3: {}", stderr);
+        }
+
+        Ok(())
+    }
 }
 
 // =============================================================================
 // OCI Backend (uses oras CLI)
 // =============================================================================
 
-fn oci_pull(tag: &str) -> Result<Option<CachedArtifact>> {
-    // Create temp directory for pull
-    let temp_dir = std::env::temp_dir().join(format!("airis-oci-{}", uuid_v4()));
-    std::fs::create_dir_all(&temp_dir)?;
+pub struct OciBackend {
+    registry: String,
+}
 
-    let temp_dir_str = temp_dir
-        .to_str()
-        .context("Temp directory path contains non-UTF-8 characters")?;
+impl OciBackend {
+    /// Get OCI tag for cache
+    fn tag(&self, project: &str, hash: &str) -> String {
+        let project_safe = project.replace('/', "-");
+        format!("{}:{}-{}", self.registry, project_safe, hash)
+    }
+}
+
+impl RemoteBackend for OciBackend {
+    fn get(&self, project: &str, hash: &str) -> Result<Option<Vec<u8>>> {
+        let tag = self.tag(project, hash);
+
+        // Create temp directory for pull
+        let temp_dir = std::env::temp_dir().join(format!("airis-oci-{}", uuid_v4()));
+        std::fs::create_dir_all(&temp_dir)?;
+
+        let temp_dir_str = temp_dir
+            .to_str()
+            .context("Temp directory path contains non-UTF-8 characters")?;
+
+        let output = Command::new("oras")
+            .args(["pull", &tag, "-o", temp_dir_str])
+            .output()
+            .context("Failed to run oras pull (is oras installed?)")?;
 
-    let output = Command::new("oras")
-        .args(["pull", tag, "-o", temp_dir_str])
-        .output()
-        .context("Failed to run oras pull (is oras installed?)")?;
+        if !output.status.success() {
+            // Not found or error - treat as cache miss
+            let _ = std::fs::remove_dir_all(&temp_dir);
+            return Ok(None);
+        }
 
-    if !output.status.success() {
-        // Not found or error - treat as cache miss
+        // Read artifact.json from pulled content
+        let content = std::fs::read(temp_dir.join("artifact.json")).ok();
         let _ = std::fs::remove_dir_all(&temp_dir);
-        return Ok(None);
+
+        Ok(content)
     }
 
-    // Read artifact.json from pulled content
-    let artifact_path = temp_dir.join("artifact.json");
-    if !artifact_path.exists() {
+    fn put(&self, project: &str, hash: &str, data: &[u8]) -> Result<()> {
+        let tag = self.tag(project, hash);
+
+        // Create temp directory for push
+        let temp_dir = std::env::temp_dir().join(format!("airis-oci-{}", uuid_v4()));
+        std::fs::create_dir_all(&temp_dir)?;
+        std::fs::write(temp_dir.join("artifact.json"), data)?;
+
+        let output = Command::new("oras")
+            .args(["push", &tag, "artifact.json:application/json"])
+            .current_dir(&temp_dir)
+            .output()
+            .context("Failed to run oras push")?;
+
+        // Cleanup
         let _ = std::fs::remove_dir_all(&temp_dir);
-        return Ok(None);
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!("Failed to push to OCI registry: {}", stderr);
+        }
+
+        Ok(())
+    }
+}
+
+// =============================================================================
+// HTTP Backend (Turborepo remote cache protocol)
+// =============================================================================
+
+/// HTTP backend: `GET`/`PUT {base_url}/v8/artifacts/{id}`
+///
+/// Authentication uses `AIRIS_REMOTE_CACHE_TOKEN` (or `TURBO_TOKEN`) as a
+/// bearer token; `AIRIS_REMOTE_CACHE_TEAM` (or `TURBO_TEAM`) is sent as `slug`.
+pub struct HttpBackend {
+    base_url: String,
+    token: Option<String>,
+    team: Option<String>,
+    agent: ureq::Agent,
+}
+
+impl HttpBackend {
+    pub fn new(base_url: &str) -> Self {
+        let env = |keys: &[&str]| keys.iter().find_map(|k| std::env::var(k).ok().filter(|v| !v.is_empty()));
+
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token: env(&["AIRIS_REMOTE_CACHE_TOKEN", "TURBO_TOKEN"]),
+            team: env(&["AIRIS_REMOTE_CACHE_TEAM", "TURBO_TEAM"]),
+            agent: ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs(60))
+                .build(),
+        }
+    }
+
+    /// Artifact URL: project and hash are combined into one artifact ID
+    fn url(&self, project: &str, hash: &str) -> String {
+        let id = format!("{}-{}", project.replace('/', "-"), hash);
+        match &self.team {
+            Some(team) => {
+                // Percent-encode everything but RFC 3986 unreserved characters
+                let slug: String = team
+                    .bytes()
+                    .map(|b| match b {
+                        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+                        _ => format!("%{:02X}", b),
+                    })
+                    .collect();
+                format!("{}/v8/artifacts/{}?slug={}", self.base_url, id, slug)
+            }
+            None => format!("{}/v8/artifacts/{}", self.base_url, id),
+        }
+    }
+
+    fn request(&self, method: &str, url: &str) -> ureq::Request {
+        let request = self.agent.request(method, url);
+        match &self.token {
+            Some(token) => request.set("Authorization", &format!("Bearer {}", token)),
+            None => request,
+        }
+    }
+}
+
+impl RemoteBackend for HttpBackend {
+    fn get(&self, project: &str, hash: &str) -> Result<Option<Vec<u8>>> {
+        let url = self.url(project, hash);
+
+        match self.request("GET", &url).call() {
+            Ok(response) => {
+                let mut data = Vec::new();
+                response
+                    .into_reader()
+                    .read_to_end(&mut data)
+                    .context("Failed to read artifact from HTTP cache")?;
+                Ok(Some(data))
+            }
+            Err(ureq::Error::Status(404, _)) => Ok(None),
+            Err(ureq::Error::Status(code, _)) => {
+                bail!("HTTP cache GET {} failed with status {}", url, code)
+            }
+            // Unreachable server - treat as cache miss like the CLI backends
+            Err(ureq::Error::Transport(_)) => Ok(None),
+        }
     }
 
-    let content = std::fs::read_to_string(&artifact_path)?;
-    let _ = std::fs::remove_dir_all(&temp_dir);
+    fn put(&self, project: &str, hash: &str, data: &[u8]) -> Result<()> {
+        let url = self.url(project, hash);
 
-    let artifact: CachedArtifact = serde_json::from_str(&content)
-        .context("Failed to parse cached artifact from OCI")?;
+        self.request("PUT", &url)
+            .set("Content-Type", "application/octet-stream")
+            .send_bytes(data)
+            .map_err(|e| anyhow::anyhow!("Failed to upload to HTTP cache {}: {}", url, e))?;
 
-    Ok(Some(artifact))
+        Ok(())
+    }
 }
 
-fn oci_push(tag: &str, artifact: &CachedArtifact) -> Result<()> {
-    // Create temp directory for push
-    let temp_dir = std::env::temp_dir().join(format!("airis-oci-{}", uuid_v4()));
-    std::fs::create_dir_all(&temp_dir)?;
+// =============================================================================
+// File Backend (shared directory, e.g. NFS)
+// =============================================================================
 
-    // Write artifact.json
-    let artifact_path = temp_dir.join("artifact.json");
-    let content = serde_json::to_string_pretty(artifact)?;
-    std::fs::write(&artifact_path, &content)?;
+pub struct FileBackend {
+    dir: PathBuf,
+}
 
-    let output = Command::new("oras")
-        .args([
-            "push",
-            tag,
-            "artifact.json:application/json",
-        ])
-        .current_dir(&temp_dir)
-        .output()
-        .context("Failed to run oras push")?;
+impl FileBackend {
+    /// Same layout as the local cache: <dir>/<project>/<hash>/artifact.json
+    fn path(&self, project: &str, hash: &str) -> PathBuf {
+        self.dir
+            .join(project.replace('/', "_"))
+            .join(hash)
+            .join("artifact.json")
+    }
+}
 
-    // Cleanup
-    let _ = std::fs::remove_dir_all(&temp_dir);
+impl RemoteBackend for FileBackend {
+    fn get(&self, project: &str, hash: &str) -> Result<Option<Vec<u8>>> {
+        let path = self.path(project, hash);
+        if !path.exists() {
+            return Ok(None);
+        }
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        bail!("Failed to push to OCI registry: {}", stderr);
+        let data = std::fs::read(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Ok(Some(data))
     }
 
-    Ok(())
+    fn put(&self, project: &str, hash: &str, data: &[u8]) -> Result<()> {
+        let path = self.path(project, hash);
+        let parent = path.parent().context("Invalid cache path")?;
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+
+        // Write-then-rename so concurrent readers never see partial files
+        let temp = parent.join(format!(".artifact-{}.tmp", uuid_v4()));
+        std::fs::write(&temp, data)
+            .with_context(|| format!("Failed to write {}", temp.display()))?;
+        std::fs::rename(&temp, &path)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+
+        Ok(())
+    }
 }
 
 // =============================================================================
 // Helpers
 // =============================================================================
 
+/// Lowercase hex encoding of `bytes`
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Bytes of a hex string, `None` if malformed
+pub(crate) fn unhex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
 /// Generate a simple UUID v4 (good enough for temp files)
 fn uuid_v4() -> String {
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -274,6 +691,84 @@ fn uuid_v4() -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::{BTreeMap, HashMap};
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    fn sample_artifact() -> CachedArtifact {
+        CachedArtifact {
+            image_ref: "web:airis-abc123".to_string(),
+            hash: "abc123".to_string(),
+            built_at: "2025-01-01T00:00:00Z".to_string(),
+            target: "apps/web".to_string(),
+            platforms: BTreeMap::new(),
+            index_digest: None,
+            oci_layout: None,
+        }
+    }
+
+    type MockStore = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+
+    /// Minimal in-memory Turborepo-style cache server (one request per connection)
+    fn spawn_mock_server() -> (String, MockStore) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let store: MockStore = Arc::default();
+        let server_store = Arc::clone(&store);
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut parts = request_line.split_whitespace();
+                let method = parts.next().unwrap_or_default().to_string();
+                let path = parts.next().unwrap_or_default().to_string();
+
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((k, v)) = line.split_once(':')
+                        && k.eq_ignore_ascii_case("content-length") {
+                            content_length = v.trim().parse().unwrap();
+                        }
+                }
+
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+
+                let mut store = server_store.lock().unwrap();
+                let (status, payload) = match method.as_str() {
+                    "PUT" => {
+                        store.insert(path, body);
+                        ("200 OK", Vec::new())
+                    }
+                    "GET" => match store.get(&path) {
+                        Some(data) => ("200 OK", data.clone()),
+                        None => ("404 Not Found", Vec::new()),
+                    },
+                    _ => ("405 Method Not Allowed", Vec::new()),
+                };
+
+                let header = format!(
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    payload.len()
+                );
+                let _ = stream.write_all(header.as_bytes());
+                let _ = stream.write_all(&payload);
+            }
+        });
+
+        (format!("http://{}", addr), store)
+    }
 
     #[test]
     fn test_parse_s3_url() {
@@ -310,29 +805,193 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_http_and_file_urls() {
+        match Remote::parse("https://cache.example.com/").unwrap() {
+            Remote::Http { base_url } => assert_eq!(base_url, "https://cache.example.com"),
+            _ => panic!("Expected HTTP"),
+        }
+        match Remote::parse("file:///mnt/cache").unwrap() {
+            Remote::File { dir } => assert_eq!(dir, PathBuf::from("/mnt/cache")),
+            _ => panic!("Expected File"),
+        }
+    }
+
     #[test]
     fn test_parse_invalid_url() {
-        assert!(Remote::parse("http://example.com").is_err());
+        assert!(Remote::parse("ftp://example.com").is_err());
         assert!(Remote::parse("s3://").is_err());
         assert!(Remote::parse("oci://").is_err());
+        assert!(Remote::parse("https://").is_err());
+        assert!(Remote::parse("file://").is_err());
     }
 
     #[test]
     fn test_s3_key() {
-        let remote = Remote::S3 {
+        let backend = S3Backend {
             bucket: "bucket".to_string(),
             prefix: "cache".to_string(),
         };
-        let key = remote.s3_key("apps/web", "abc123");
+        let key = backend.key("apps/web", "abc123");
         assert_eq!(key, "cache/apps_web/abc123/artifact.json");
     }
 
     #[test]
     fn test_oci_tag() {
-        let remote = Remote::Oci {
+        let backend = OciBackend {
             registry: "ghcr.io/org/cache".to_string(),
         };
-        let tag = remote.oci_tag("apps/web", "abc123");
+        let tag = backend.tag("apps/web", "abc123");
         assert_eq!(tag, "ghcr.io/org/cache:apps-web-abc123");
     }
+
+    #[test]
+    fn test_http_backend_roundtrip() {
+        let (url, store) = spawn_mock_server();
+        let remote = Remote::parse(&url).unwrap();
+        let signing = Signing::new(Some(b"secret".to_vec()), RemoteCacheVerify::Strict);
+
+        assert!(remote_hit("apps/web", "abc123", &remote, &signing).unwrap().is_none());
+
+        remote_store("apps/web", "abc123", &sample_artifact(), &remote, &signing).unwrap();
+        assert!(store.lock().unwrap().contains_key("/v8/artifacts/apps-web-abc123"));
+
+        let hit = remote_hit("apps/web", "abc123", &remote, &signing).unwrap().unwrap();
+        assert_eq!(hit.image_ref, "web:airis-abc123");
+    }
+
+    #[test]
+    fn test_http_backend_encodes_team() {
+        let backend = HttpBackend {
+            team: Some("my team&x=1".to_string()),
+            ..HttpBackend::new("https://cache.example.com/")
+        };
+        assert_eq!(
+            backend.url("apps/web", "abc123"),
+            "https://cache.example.com/v8/artifacts/apps-web-abc123?slug=my%20team%26x%3D1"
+        );
+    }
+
+    #[test]
+    fn test_file_backend_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let remote = Remote::File {
+            dir: dir.path().to_path_buf(),
+        };
+        let signing = Signing::new(Some(b"secret".to_vec()), RemoteCacheVerify::Strict);
+
+        assert!(remote_hit("apps/web", "abc123", &remote, &signing).unwrap().is_none());
+
+        remote_store("apps/web", "abc123", &sample_artifact(), &remote, &signing).unwrap();
+        assert!(dir.path().join("apps_web/abc123/artifact.json").exists());
+
+        let hit = remote_hit("apps/web", "abc123", &remote, &signing).unwrap().unwrap();
+        assert_eq!(hit.target, "apps/web");
+    }
+
+    #[test]
+    fn test_signed_artifact_roundtrip() {
+        let signing = Signing::new(Some(b"secret".to_vec()), RemoteCacheVerify::Strict);
+        let envelope = signing.seal("apps/web", "abc123", &sample_artifact()).unwrap();
+        assert_eq!(envelope.algorithm.as_deref(), Some("hmac-sha256"));
+
+        let data = serde_json::to_vec(&envelope).unwrap();
+        let artifact = signing.open("apps/web", "abc123", &data).unwrap();
+        assert_eq!(artifact.image_ref, "web:airis-abc123");
+
+        // Wrong key
+        let other = Signing::new(Some(b"other".to_vec()), RemoteCacheVerify::Strict);
+        assert!(other.open("apps/web", "abc123", &data).is_err());
+
+        // Replayed under another cache key
+        assert!(signing.open("apps/api", "abc123", &data).is_err());
+    }
+
+    #[test]
+    fn test_tampered_and_truncated_entries_rejected() {
+        let signing = Signing::new(Some(b"secret".to_vec()), RemoteCacheVerify::Strict);
+        let mut envelope = signing.seal("apps/web", "abc123", &sample_artifact()).unwrap();
+
+        let data = serde_json::to_vec(&envelope).unwrap();
+        assert!(signing.open("apps/web", "abc123", &data[..data.len() / 2]).is_err());
+
+        // Payload swapped with a matching digest still fails the signature
+        envelope.payload = envelope.payload.replace("web:airis-abc123", "evil:latest");
+        envelope.digest = blake3::hash(envelope.payload.as_bytes()).to_hex().to_string();
+        let tampered = serde_json::to_vec(&envelope).unwrap();
+        let err = signing.open("apps/web", "abc123", &tampered).unwrap_err();
+        assert!(err.to_string().contains("signature"));
+
+        // Payload changed without updating the digest
+        let unsigned = Signing::new(None, RemoteCacheVerify::Warn);
+        let mut envelope = unsigned.seal("apps/web", "abc123", &sample_artifact()).unwrap();
+        envelope.payload = envelope.payload.replace("web:airis-abc123", "evil:latest");
+        let tampered = serde_json::to_vec(&envelope).unwrap();
+        let err = unsigned.open("apps/web", "abc123", &tampered).unwrap_err();
+        assert!(err.to_string().contains("digest"));
+    }
+
+    #[test]
+    fn test_verify_mode_strict_vs_warn() {
+        let dir = tempfile::tempdir().unwrap();
+        let remote = Remote::File {
+            dir: dir.path().to_path_buf(),
+        };
+
+        // Unsigned entry (e.g., pushed by a client without the key)
+        let unsigned = Signing::new(None, RemoteCacheVerify::Warn);
+        remote_store("apps/web", "abc123", &sample_artifact(), &remote, &unsigned).unwrap();
+
+        let strict = Signing::new(Some(b"secret".to_vec()), RemoteCacheVerify::Strict);
+        assert!(remote_hit("apps/web", "abc123", &remote, &strict).unwrap().is_none());
+
+        // Strict without a key can't verify, so it refuses instead of trusting the digest
+        let keyless = Signing::new(None, RemoteCacheVerify::Strict);
+        assert!(remote_hit("apps/web", "abc123", &remote, &keyless).is_err());
+        let data = serde_json::to_vec(&unsigned.seal("apps/web", "abc123", &sample_artifact()).unwrap()).unwrap();
+        assert!(keyless.open("apps/web", "abc123", &data).is_err());
+
+        let warn = Signing::new(Some(b"secret".to_vec()), RemoteCacheVerify::Warn);
+        assert!(remote_hit("apps/web", "abc123", &remote, &warn).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_signing_key_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("cache.key"), "secret\n").unwrap();
+        let section = RemoteCacheSection {
+            signing_key_env: Some("AIRIS_TEST_UNSET_SIGNING_KEY".to_string()),
+            signing_key_file: Some("cache.key".to_string()),
+            verify: Some(RemoteCacheVerify::Warn),
+        };
+
+        let signing = Signing::from_manifest(&section, dir.path(), Some(RemoteCacheVerify::Strict)).unwrap();
+        assert_eq!(signing.key.as_deref(), Some(b"secret".as_slice()));
+        assert_eq!(signing.verify, RemoteCacheVerify::Strict);
+
+        // A key without an explicit mode means strict
+        let section = RemoteCacheSection { verify: None, ..section };
+        assert_eq!(Signing::from_manifest(&section, dir.path(), None).unwrap().verify, RemoteCacheVerify::Strict);
+    }
+
+    #[test]
+    fn test_no_key_defaults_to_warn() {
+        // Existing `--remote-cache` setups without a key keep working (with warnings)
+        let dir = tempfile::tempdir().unwrap();
+        let section = RemoteCacheSection {
+            signing_key_env: Some("AIRIS_TEST_UNSET_SIGNING_KEY".to_string()),
+            ..Default::default()
+        };
+        let signing = Signing::from_manifest(&section, dir.path(), None).unwrap();
+        assert_eq!(signing.verify, RemoteCacheVerify::Warn);
+        signing.require_key().unwrap();
+
+        let remote = Remote::File { dir: dir.path().join("remote") };
+        remote_store("apps/web", "abc123", &sample_artifact(), &remote, &signing).unwrap();
+        assert!(remote_hit("apps/web", "abc123", &remote, &signing).unwrap().is_some());
+
+        // Asking for strict without a key is still an error
+        let strict = Signing::from_manifest(&section, dir.path(), Some(RemoteCacheVerify::Strict)).unwrap();
+        assert!(strict.require_key().is_err());
+    }
 }
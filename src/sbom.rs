@@ -0,0 +1,580 @@
+//! SBOMs for `airis build --docker` and `airis bundle` (CycloneDX 1.5 / SPDX 2.3 JSON)
+//!
+//! Components come from pnpm-lock.yaml: the packages the target and its
+//! workspace dependencies resolve to, transitively. Packages only reached
+//! through devDependencies are needed to build the image, not shipped in it:
+//! CycloneDX marks them `"scope": "excluded"`, SPDX relates them with
+//! `DEV_DEPENDENCY_OF`. The toolchain base image is listed with its digest.
+
+use anyhow::{bail, Result};
+use base64::Engine;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::channel::Toolchain;
+use crate::dag::Dag;
+use crate::pnpm::{Dependency, PnpmLock};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SbomFormat {
+    CycloneDx,
+    Spdx,
+}
+
+impl SbomFormat {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "cyclonedx" => Ok(SbomFormat::CycloneDx),
+            "spdx" => Ok(SbomFormat::Spdx),
+            _ => bail!("Unknown SBOM format '{}' (expected cyclonedx or spdx)", s),
+        }
+    }
+
+    /// File name in a bundle or build cache entry
+    pub fn file_name(self) -> &'static str {
+        match self {
+            SbomFormat::CycloneDx => "sbom.cdx.json",
+            SbomFormat::Spdx => "sbom.spdx.json",
+        }
+    }
+}
+
+/// A third-party package from the lockfile
+#[derive(Debug, Clone, PartialEq)]
+pub struct Package {
+    pub name: String,
+    pub version: String,
+    /// SHA-512 (hex) from the lockfile's integrity, for registry packages
+    pub sha512: Option<String>,
+    pub tarball: Option<String>,
+    /// Only reached through devDependencies
+    pub dev: bool,
+    /// "name@version" of its dependencies
+    pub depends_on: BTreeSet<String>,
+}
+
+impl Package {
+    fn key(&self) -> String {
+        format!("{}@{}", self.name, self.version)
+    }
+
+    /// Package URL, e.g., "pkg:npm/%40scope/name@1.0.0"
+    pub fn purl(&self) -> String {
+        format!("pkg:npm/{}@{}", self.name.replacen('@', "%40", 1), self.version)
+    }
+}
+
+/// A workspace package: the target or one of its workspace dependencies
+#[derive(Debug, Clone)]
+pub struct Member {
+    pub path: String,
+    pub name: String,
+    pub version: String,
+    /// "name@version" of its direct third-party dependencies
+    pub depends_on: BTreeSet<String>,
+    /// ... of which devDependencies
+    pub dev_depends_on: BTreeSet<String>,
+    /// Paths of its workspace dependencies
+    pub members: Vec<String>,
+}
+
+/// Software bill of materials of one build target
+#[derive(Debug, Clone)]
+pub struct Sbom {
+    /// The target first
+    pub members: Vec<Member>,
+    /// Sorted by name and version
+    pub packages: Vec<Package>,
+    pub toolchain: Toolchain,
+    pub created_at: String,
+}
+
+impl Sbom {
+    /// Collect the packages `target` and its workspace dependencies resolve to
+    pub fn collect(root: &Path, lock: &PnpmLock, dag: &Dag, target: &str, toolchain: &Toolchain) -> Result<Self> {
+        let importers = dag.get_dep_paths(target)?;
+
+        let mut packages: BTreeMap<String, Package> = BTreeMap::new();
+        let runtime: BTreeSet<String> = importers.iter().flat_map(|i| lock.runtime_closure(i)).collect();
+        for snapshot in importers.iter().flat_map(|i| lock.resolved_closure(i)) {
+            let Some((name, version)) = split_key(&snapshot) else {
+                continue;
+            };
+            let key = format!("{}@{}", name, version);
+            let package = packages.entry(key.clone()).or_insert_with(|| {
+                let resolution = lock.packages.get(&key).map(|p| &p.resolution);
+                Package {
+                    name: name.to_string(),
+                    version: version.to_string(),
+                    sha512: resolution.and_then(|r| r.integrity.as_deref()).and_then(sha512_hex),
+                    tarball: resolution.and_then(|r| r.tarball.clone()),
+                    dev: true,
+                    depends_on: BTreeSet::new(),
+                }
+            });
+            package.dev &= !runtime.contains(&snapshot);
+            if let Some(deps) = lock.snapshots.get(&snapshot) {
+                package.depends_on.extend(
+                    deps.dependencies
+                        .iter()
+                        .chain(&deps.optional_dependencies)
+                        .filter_map(|(name, version)| package_key(name, version)),
+                );
+            }
+        }
+
+        let members = importers
+            .iter()
+            .rev()
+            .map(|path| {
+                let node = dag.get(path);
+                let importer = lock.importers.get(path);
+                let keys = |deps: Vec<&HashMap<String, Dependency>>| -> BTreeSet<String> {
+                    deps.into_iter()
+                        .flatten()
+                        .filter_map(|(name, dep)| package_key(name, &dep.version))
+                        .collect()
+                };
+                let json = crate::workspace_graph::read_package_json(&root.join(path));
+                Member {
+                    path: path.clone(),
+                    name: node.map_or_else(|| path.clone(), |n| n.name.clone()),
+                    version: json
+                        .as_ref()
+                        .and_then(|j| j["version"].as_str())
+                        .unwrap_or("0.0.0")
+                        .to_string(),
+                    depends_on: keys(importer.map_or_else(Vec::new, |i| {
+                        vec![&i.dependencies, &i.optional_dependencies, &i.peer_dependencies]
+                    })),
+                    dev_depends_on: keys(importer.map_or_else(Vec::new, |i| vec![&i.dev_dependencies])),
+                    members: node
+                        .map(|n| n.deps.iter().filter(|d| importers.contains(d)).cloned().collect())
+                        .unwrap_or_default(),
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            members,
+            packages: packages.into_values().collect(),
+            toolchain: toolchain.clone(),
+            created_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        })
+    }
+
+    /// Load pnpm-lock.yaml and the workspace graph, then [`Self::collect`]
+    pub fn load(root: &Path, target: &str, toolchain: &Toolchain) -> Result<Self> {
+        let lock = PnpmLock::load(&root.join("pnpm-lock.yaml"))?;
+        let dag = crate::workspace_graph::from_lockfile(root, &lock);
+        Self::collect(root, &lock, &dag, target, toolchain)
+    }
+
+    /// Write the SBOM in each of `formats` to `dir`
+    pub fn write(&self, dir: &Path, formats: &[SbomFormat]) -> Result<Vec<PathBuf>> {
+        fs::create_dir_all(dir)?;
+        formats
+            .iter()
+            .map(|format| {
+                let doc = match format {
+                    SbomFormat::CycloneDx => self.cyclonedx(),
+                    SbomFormat::Spdx => self.spdx(),
+                };
+                let path = dir.join(format.file_name());
+                fs::write(&path, serde_json::to_string_pretty(&doc)?)?;
+                Ok(path)
+            })
+            .collect()
+    }
+
+    fn target(&self) -> &Member {
+        &self.members[0]
+    }
+
+    /// Stable-looking UUID for this document
+    fn serial(&self) -> String {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(self.target().path.as_bytes());
+        hasher.update(self.created_at.as_bytes());
+        for package in &self.packages {
+            hasher.update(package.key().as_bytes());
+        }
+        let mut bytes = *hasher.finalize().as_bytes();
+        bytes[6] = (bytes[6] & 0x0f) | 0x40;
+        bytes[8] = (bytes[8] & 0x3f) | 0x80;
+        let hex: String = bytes[..16].iter().map(|b| format!("{:02x}", b)).collect();
+        format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+    }
+
+    /// Base image name, version (tag) and package URL
+    fn image(&self) -> (String, String, String) {
+        let image = &self.toolchain.image;
+        let (name, tag) = match image.rsplit_once(':') {
+            Some((name, tag)) if !tag.contains('/') => (name, tag),
+            _ => (image.as_str(), "latest"),
+        };
+        let purl = match &self.toolchain.digest {
+            Some(digest) => format!("pkg:docker/{}@{}?tag={}", name, digest.replace(':', "%3A"), tag),
+            None => format!("pkg:docker/{}@{}", name, tag),
+        };
+        (name.to_string(), tag.to_string(), purl)
+    }
+
+    /// CycloneDX 1.5 JSON
+    pub fn cyclonedx(&self) -> Value {
+        let purls: BTreeMap<String, String> = self.packages.iter().map(|p| (p.key(), p.purl())).collect();
+        let (image_name, image_tag, image_purl) = self.image();
+        let member_component = |member: &Member, kind: &str| {
+            json!({"type": kind, "bom-ref": member.path, "name": member.name, "version": member.version})
+        };
+
+        let mut components: Vec<Value> = self.members[1..].iter().map(|m| member_component(m, "library")).collect();
+        for package in &self.packages {
+            let mut component = json!({
+                "type": "library",
+                "bom-ref": package.purl(),
+                "name": package.name,
+                "version": package.version,
+                "purl": package.purl(),
+                "scope": if package.dev { "excluded" } else { "required" },
+            });
+            if let Some(sha512) = &package.sha512 {
+                component["hashes"] = json!([{"alg": "SHA-512", "content": sha512}]);
+            }
+            if let Some(tarball) = &package.tarball {
+                component["externalReferences"] = json!([{"type": "distribution", "url": tarball}]);
+            }
+            components.push(component);
+        }
+        let mut image = json!({
+            "type": "container",
+            "bom-ref": image_purl,
+            "name": image_name,
+            "version": image_tag,
+            "purl": image_purl,
+        });
+        if let Some(hex) = self.toolchain.digest.as_deref().and_then(|d| d.strip_prefix("sha256:")) {
+            image["hashes"] = json!([{"alg": "SHA-256", "content": hex}]);
+        }
+        components.push(image);
+
+        let refs = |keys: &BTreeSet<String>| -> Vec<String> { keys.iter().filter_map(|k| purls.get(k).cloned()).collect() };
+        let mut dependencies: Vec<Value> = self
+            .members
+            .iter()
+            .enumerate()
+            .map(|(i, member)| {
+                let mut depends_on = member.members.clone();
+                depends_on.extend(refs(&member.depends_on));
+                depends_on.extend(refs(&member.dev_depends_on));
+                if i == 0 {
+                    depends_on.push(image_purl.clone());
+                }
+                json!({"ref": member.path, "dependsOn": depends_on})
+            })
+            .collect();
+        dependencies.extend(
+            self.packages
+                .iter()
+                .map(|p| json!({"ref": p.purl(), "dependsOn": refs(&p.depends_on)})),
+        );
+
+        json!({
+            "bomFormat": "CycloneDX",
+            "specVersion": "1.5",
+            "serialNumber": format!("urn:uuid:{}", self.serial()),
+            "version": 1,
+            "metadata": {
+                "timestamp": self.created_at,
+                "tools": {"components": [{"type": "application", "name": "airis", "version": env!("CARGO_PKG_VERSION")}]},
+                "component": member_component(self.target(), "application"),
+            },
+            "components": components,
+            "dependencies": dependencies,
+        })
+    }
+
+    /// SPDX 2.3 JSON
+    pub fn spdx(&self) -> Value {
+        let member_id = |path: &str| spdx_id("Workspace", path);
+        let package_id = |key: &str| spdx_id("Package", key);
+        let keys: BTreeSet<String> = self.packages.iter().map(Package::key).collect();
+        let (image_name, image_tag, image_purl) = self.image();
+        let image_id = spdx_id("Image", &self.toolchain.image);
+
+        let mut packages: Vec<Value> = self
+            .members
+            .iter()
+            .enumerate()
+            .map(|(i, member)| {
+                json!({
+                    "name": member.name,
+                    "SPDXID": member_id(&member.path),
+                    "versionInfo": member.version,
+                    "downloadLocation": "NOASSERTION",
+                    "filesAnalyzed": false,
+                    "licenseConcluded": "NOASSERTION",
+                    "licenseDeclared": "NOASSERTION",
+                    "copyrightText": "NOASSERTION",
+                    "primaryPackagePurpose": if i == 0 { "APPLICATION" } else { "LIBRARY" },
+                })
+            })
+            .collect();
+        for package in &self.packages {
+            let mut entry = json!({
+                "name": package.name,
+                "SPDXID": package_id(&package.key()),
+                "versionInfo": package.version,
+                "downloadLocation": package.tarball.as_deref().unwrap_or("NOASSERTION"),
+                "filesAnalyzed": false,
+                "licenseConcluded": "NOASSERTION",
+                "licenseDeclared": "NOASSERTION",
+                "copyrightText": "NOASSERTION",
+                "primaryPackagePurpose": "LIBRARY",
+                "externalRefs": [{"referenceCategory": "PACKAGE-MANAGER", "referenceType": "purl", "referenceLocator": package.purl()}],
+            });
+            if let Some(sha512) = &package.sha512 {
+                entry["checksums"] = json!([{"algorithm": "SHA512", "checksumValue": sha512}]);
+            }
+            packages.push(entry);
+        }
+        let mut image = json!({
+            "name": image_name,
+            "SPDXID": image_id,
+            "versionInfo": image_tag,
+            "downloadLocation": "NOASSERTION",
+            "filesAnalyzed": false,
+            "licenseConcluded": "NOASSERTION",
+            "licenseDeclared": "NOASSERTION",
+            "copyrightText": "NOASSERTION",
+            "primaryPackagePurpose": "CONTAINER",
+            "externalRefs": [{"referenceCategory": "PACKAGE-MANAGER", "referenceType": "purl", "referenceLocator": image_purl}],
+        });
+        if let Some(hex) = self.toolchain.digest.as_deref().and_then(|d| d.strip_prefix("sha256:")) {
+            image["checksums"] = json!([{"algorithm": "SHA256", "checksumValue": hex}]);
+        }
+        packages.push(image);
+
+        let relationship = |from: String, kind: &str, to: String| {
+            json!({"spdxElementId": from, "relationshipType": kind, "relatedSpdxElement": to})
+        };
+        let target = member_id(&self.target().path);
+        let mut relationships = vec![
+            relationship("SPDXRef-DOCUMENT".to_string(), "DESCRIBES", target.clone()),
+            relationship(target, "DEPENDS_ON", image_id),
+        ];
+        for member in &self.members {
+            let id = member_id(&member.path);
+            for dep in &member.members {
+                relationships.push(relationship(id.clone(), "DEPENDS_ON", member_id(dep)));
+            }
+            for key in member.depends_on.iter().filter(|k| keys.contains(*k)) {
+                relationships.push(relationship(id.clone(), "DEPENDS_ON", package_id(key)));
+            }
+            for key in member.dev_depends_on.iter().filter(|k| keys.contains(*k)) {
+                relationships.push(relationship(package_id(key), "DEV_DEPENDENCY_OF", id.clone()));
+            }
+        }
+        for package in &self.packages {
+            for key in package.depends_on.iter().filter(|k| keys.contains(*k)) {
+                relationships.push(relationship(package_id(&package.key()), "DEPENDS_ON", package_id(key)));
+            }
+        }
+
+        json!({
+            "spdxVersion": "SPDX-2.3",
+            "dataLicense": "CC0-1.0",
+            "SPDXID": "SPDXRef-DOCUMENT",
+            "name": format!("{}@{}", self.target().name, self.target().version),
+            "documentNamespace": format!("https://spdx.org/spdxdocs/airis/{}", self.serial()),
+            "creationInfo": {
+                "created": self.created_at,
+                "creators": [format!("Tool: airis-{}", env!("CARGO_PKG_VERSION"))],
+            },
+            "packages": packages,
+            "relationships": relationships,
+        })
+    }
+}
+
+/// "name@version(peers)" -> ("name", "version"); None for workspace links
+fn split_key(key: &str) -> Option<(&str, &str)> {
+    let base = key.split_once('(').map_or(key, |(base, _)| base);
+    // Scoped names start with '@'
+    let at = base.get(1..)?.find('@')? + 1;
+    let (name, version) = (&base[..at], &base[at + 1..]);
+    if version.starts_with("link:") || version.starts_with("file:") {
+        return None;
+    }
+    Some((name, version))
+}
+
+/// "name@version" of a dependency entry (version may carry a peer suffix)
+fn package_key(name: &str, version: &str) -> Option<String> {
+    let key = format!("{}@{}", name, version);
+    split_key(&key).map(|(name, version)| format!("{}@{}", name, version))
+}
+
+/// "sha512-<base64>" -> hex
+fn sha512_hex(integrity: &str) -> Option<String> {
+    let encoded = integrity.strip_prefix("sha512-")?;
+    let bytes = base64::engine::general_purpose::STANDARD.decode(encoded).ok()?;
+    Some(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// SPDX element id: letters, digits, '.' and '-' only
+fn spdx_id(kind: &str, name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '-' })
+        .collect();
+    format!("SPDXRef-{}-{}", kind, name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channel::RuntimeFamily;
+
+    const LOCK: &str = r#"
+lockfileVersion: '9.0'
+importers:
+  .:
+    devDependencies:
+      typescript:
+        specifier: ^5.0.0
+        version: 5.4.0
+  apps/web:
+    dependencies:
+      react-dom:
+        specifier: ^18.0.0
+        version: 18.2.0(react@18.2.0)
+      '@workspace/ui':
+        specifier: workspace:*
+        version: link:../../libs/ui
+    devDependencies:
+      vite:
+        specifier: ^5.0.0
+        version: 5.0.0
+  libs/ui:
+    dependencies:
+      '@scope/clsx':
+        specifier: ^2.0.0
+        version: 2.1.0
+  apps/other:
+    dependencies:
+      lodash:
+        specifier: ^4.0.0
+        version: 4.17.21
+packages:
+  react-dom@18.2.0:
+    resolution: {integrity: sha512-AAAA}
+  react@18.2.0:
+    resolution: {integrity: sha512-AAAA}
+  '@scope/clsx@2.1.0':
+    resolution: {tarball: https://example.com/clsx-2.1.0.tgz}
+snapshots:
+  react-dom@18.2.0(react@18.2.0):
+    dependencies:
+      react: 18.2.0
+  react@18.2.0: {}
+  '@scope/clsx@2.1.0': {}
+  vite@5.0.0:
+    dependencies:
+      react: 18.2.0
+  lodash@4.17.21: {}
+  typescript@5.4.0: {}
+"#;
+
+    fn sbom() -> Sbom {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for (path, json) in [
+            ("apps/web", r#"{"name": "web", "version": "1.2.0"}"#),
+            ("libs/ui", r#"{"name": "@workspace/ui"}"#),
+            ("apps/other", r#"{"name": "other"}"#),
+        ] {
+            fs::create_dir_all(root.join(path)).unwrap();
+            fs::write(root.join(path).join("package.json"), json).unwrap();
+        }
+        let lock = PnpmLock::parse(LOCK).unwrap();
+        let dag = crate::workspace_graph::from_lockfile(root, &lock);
+        let toolchain = Toolchain {
+            image: "node:22-alpine".to_string(),
+            digest: Some("sha256:abc123".to_string()),
+            family: RuntimeFamily::Node,
+            version: "22".to_string(),
+        };
+        Sbom::collect(root, &lock, &dag, "apps/web", &toolchain).unwrap()
+    }
+
+    #[test]
+    fn test_split_key() {
+        assert_eq!(split_key("react-dom@18.2.0(react@18.2.0)"), Some(("react-dom", "18.2.0")));
+        assert_eq!(split_key("@scope/clsx@2.1.0"), Some(("@scope/clsx", "2.1.0")));
+        assert_eq!(split_key("@workspace/ui@link:../../libs/ui"), None);
+        assert_eq!(sha512_hex("sha512-AAAA"), Some("000000".to_string()));
+    }
+
+    #[test]
+    fn test_collect_covers_workspace_deps() {
+        let sbom = sbom();
+        let members: Vec<&str> = sbom.members.iter().map(|m| m.path.as_str()).collect();
+        assert_eq!(members, vec!["apps/web", "libs/ui"]);
+        assert_eq!(sbom.members[0].version, "1.2.0");
+
+        let packages: Vec<(String, bool)> = sbom.packages.iter().map(|p| (p.key(), p.dev)).collect();
+        // lodash (other app) and typescript (root) are out; react is shipped
+        // even though vite (dev) also pulls it in
+        assert_eq!(
+            packages,
+            vec![
+                ("@scope/clsx@2.1.0".to_string(), false),
+                ("react-dom@18.2.0".to_string(), false),
+                ("react@18.2.0".to_string(), false),
+                ("vite@5.0.0".to_string(), true),
+            ]
+        );
+        assert_eq!(sbom.packages[0].purl(), "pkg:npm/%40scope/clsx@2.1.0");
+        assert_eq!(sbom.packages[1].sha512.as_deref(), Some("000000"));
+        assert!(sbom.packages[1].depends_on.contains("react@18.2.0"));
+    }
+
+    #[test]
+    fn test_cyclonedx_and_spdx_documents() {
+        let sbom = sbom();
+
+        let cdx = sbom.cyclonedx();
+        assert_eq!(cdx["bomFormat"], "CycloneDX");
+        assert_eq!(cdx["metadata"]["component"]["name"], "web");
+        let components = cdx["components"].as_array().unwrap();
+        let vite = components.iter().find(|c| c["name"] == "vite").unwrap();
+        assert_eq!(vite["scope"], "excluded");
+        let image = components.iter().find(|c| c["type"] == "container").unwrap();
+        assert_eq!(image["purl"], "pkg:docker/node@sha256%3Aabc123?tag=22-alpine");
+        assert_eq!(image["hashes"][0]["content"], "abc123");
+        let web = cdx["dependencies"].as_array().unwrap().iter().find(|d| d["ref"] == "apps/web").unwrap();
+        assert!(web["dependsOn"].as_array().unwrap().contains(&json!("libs/ui")));
+
+        let spdx = sbom.spdx();
+        assert_eq!(spdx["spdxVersion"], "SPDX-2.3");
+        assert_eq!(spdx["packages"].as_array().unwrap().len(), 2 + 4 + 1);
+        let relationships = spdx["relationships"].as_array().unwrap();
+        assert!(relationships.contains(&json!({
+            "spdxElementId": "SPDXRef-Package-vite-5.0.0",
+            "relationshipType": "DEV_DEPENDENCY_OF",
+            "relatedSpdxElement": "SPDXRef-Workspace-apps-web",
+        })));
+        assert!(relationships.contains(&json!({
+            "spdxElementId": "SPDXRef-Workspace-apps-web",
+            "relationshipType": "DEPENDS_ON",
+            "relatedSpdxElement": "SPDXRef-Image-node-22-alpine",
+        })));
+
+        let dir = tempfile::tempdir().unwrap();
+        let written = sbom.write(dir.path(), &[SbomFormat::CycloneDx, SbomFormat::Spdx]).unwrap();
+        assert_eq!(written, vec![dir.path().join("sbom.cdx.json"), dir.path().join("sbom.spdx.json")]);
+    }
+}
@@ -0,0 +1,322 @@
+//! Local cache for `[tasks]` outputs
+//!
+//! Tasks that declare `inputs` are keyed on a BLAKE3 hash of those files, the
+//! task name, command, `outputs` globs, `env` values, and the hashes of the
+//! tasks they depend on. Tasks without `inputs` are not cached, but their keys
+//! cover every package file so dependents still notice changes. Entries live next to
+//! Docker build artifacts in `~/.airis/.cache/<project>/<hash>/`:
+//!
+//! ```text
+//! task.json     # metadata
+//! stdout.log    # replayed on cache hit
+//! stderr.log
+//! outputs/      # files matched by `outputs`, restored on cache hit
+//! ```
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::content_hash;
+use crate::docker_build::cache_dir;
+use crate::hash_cache::HashCache;
+use crate::manifest::TaskConfig;
+
+/// Cached task metadata (task.json)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskCacheEntry {
+    pub task_id: String,
+    pub hash: String,
+    /// Output files relative to the package directory
+    pub outputs: Vec<String>,
+    pub created_at: String,
+}
+
+/// Compute the cache key for a package task
+///
+/// Package files hashed for tasks without `inputs` go through `cache`'s stat check.
+pub fn compute_task_hash(
+    root: &Path,
+    package: &str,
+    task: &str,
+    command: &str,
+    config: Option<&TaskConfig>,
+    dep_hashes: &[String],
+    cache: &mut HashCache,
+) -> Result<String> {
+    let mut hasher = blake3::Hasher::new();
+
+    // NUL-separated so neighbouring fields can't run into each other
+    for field in [package, task, command] {
+        hasher.update(field.as_bytes());
+        hasher.update(b"\0");
+    }
+
+    let (inputs, outputs, env) = match config {
+        Some(c) => (c.inputs.as_slice(), c.outputs.as_slice(), c.env.as_slice()),
+        None => (&[][..], &[][..], &[][..]),
+    };
+    for output in outputs {
+        hasher.update(b"output\0");
+        hasher.update(output.as_bytes());
+    }
+    for name in env {
+        hasher.update(b"env\0");
+        hasher.update(name.as_bytes());
+        hasher.update(b"=");
+        if let Ok(value) = std::env::var(name) {
+            hasher.update(value.as_bytes());
+        }
+        hasher.update(b"\0");
+    }
+
+    for dep_hash in dep_hashes {
+        hasher.update(dep_hash.as_bytes());
+    }
+
+    let pkg_dir = root.join(package);
+    if inputs.is_empty() {
+        // Not cached itself, but dependents' keys must change with any file
+        let files = content_hash::package_files(root, package, &[])?;
+        for (path, digest) in files.iter().zip(cache.digests(&files)?) {
+            let rel = path.strip_prefix(&pkg_dir).unwrap_or(path);
+            hasher.update(rel.to_string_lossy().replace('\\', "/").as_bytes());
+            hasher.update(digest.as_bytes());
+        }
+    } else {
+        for rel in expand_globs(&pkg_dir, inputs)? {
+            hasher.update(rel.as_bytes());
+            let content = fs::read(pkg_dir.join(&rel))
+                .with_context(|| format!("Failed to read input {}/{}", package, rel))?;
+            hasher.update(&content);
+        }
+    }
+
+    Ok(hasher.finalize().to_hex()[..12].to_string())
+}
+
+/// Expand globs relative to `dir` into sorted, de-duplicated file paths
+///
+/// A pattern matching a directory (e.g., `dist` or `src/**`) selects every file below it.
+pub fn expand_globs(dir: &Path, patterns: &[String]) -> Result<Vec<String>> {
+    let mut files = Vec::new();
+
+    for pattern in patterns {
+        // glob's "dir/**" yields only subdirectories; match the directory itself instead
+        let pattern = pattern.strip_suffix("/**").unwrap_or(pattern);
+        let full = dir.join(pattern);
+        let full = full.to_str().context("Glob path contains non-UTF-8 characters")?;
+        for entry in glob::glob(full).with_context(|| format!("Invalid glob: {}", pattern))? {
+            let path = entry?;
+            // Directory matches (e.g., "dist" or "src/**") include everything below them
+            let matched: Vec<_> = if path.is_dir() {
+                walkdir::WalkDir::new(&path)
+                    .into_iter()
+                    .filter_map(|e| e.ok())
+                    .filter(|e| e.file_type().is_file())
+                    .map(|e| e.into_path())
+                    .collect()
+            } else {
+                vec![path]
+            };
+
+            for file in matched {
+                if let Ok(rel) = file.strip_prefix(dir) {
+                    files.push(rel.to_string_lossy().replace('\\', "/"));
+                }
+            }
+        }
+    }
+
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// Check for a cached task result
+pub fn cache_hit(package: &str, hash: &str) -> Option<TaskCacheEntry> {
+    read_entry(&cache_dir(package, hash))
+}
+
+fn read_entry(dir: &Path) -> Option<TaskCacheEntry> {
+    let path = dir.join("task.json");
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Store task outputs and logs in cache
+pub fn cache_store(
+    root: &Path,
+    package: &str,
+    task_id: &str,
+    hash: &str,
+    outputs: &[String],
+    stdout: &[u8],
+    stderr: &[u8],
+) -> Result<TaskCacheEntry> {
+    let entry = TaskCacheEntry {
+        task_id: task_id.to_string(),
+        hash: hash.to_string(),
+        outputs: Vec::new(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    store_in(&cache_dir(package, hash), root, package, entry, outputs, (stdout, stderr))
+}
+
+fn store_in(
+    dir: &Path,
+    root: &Path,
+    package: &str,
+    mut entry: TaskCacheEntry,
+    outputs: &[String],
+    (stdout, stderr): (&[u8], &[u8]),
+) -> Result<TaskCacheEntry> {
+    if dir.exists() {
+        fs::remove_dir_all(dir)
+            .with_context(|| format!("Failed to clear cache entry: {}", dir.display()))?;
+    }
+    fs::create_dir_all(dir.join("outputs"))
+        .with_context(|| format!("Failed to create cache directory: {}", dir.display()))?;
+
+    let pkg_dir = root.join(package);
+    let files = expand_globs(&pkg_dir, outputs)?;
+    for rel in &files {
+        copy_file(&pkg_dir.join(rel), &dir.join("outputs").join(rel))?;
+    }
+
+    fs::write(dir.join("stdout.log"), stdout)?;
+    fs::write(dir.join("stderr.log"), stderr)?;
+
+    entry.outputs = files;
+    fs::write(dir.join("task.json"), serde_json::to_string_pretty(&entry)?)?;
+
+    Ok(entry)
+}
+
+/// Restore cached outputs into the package directory
+pub fn cache_restore(root: &Path, package: &str, entry: &TaskCacheEntry) -> Result<()> {
+    restore_from(&cache_dir(package, &entry.hash), root, package, entry)
+}
+
+fn restore_from(dir: &Path, root: &Path, package: &str, entry: &TaskCacheEntry) -> Result<()> {
+    let dir = dir.join("outputs");
+    let pkg_dir = root.join(package);
+
+    for rel in &entry.outputs {
+        copy_file(&dir.join(rel), &pkg_dir.join(rel))?;
+    }
+
+    Ok(())
+}
+
+/// Cached (stdout, stderr) of a task (empty if missing)
+pub fn read_logs(package: &str, hash: &str) -> (Vec<u8>, Vec<u8>) {
+    let dir = cache_dir(package, hash);
+    (
+        fs::read(dir.join("stdout.log")).unwrap_or_default(),
+        fs::read(dir.join("stderr.log")).unwrap_or_default(),
+    )
+}
+
+fn copy_file(src: &Path, dst: &Path) -> Result<()> {
+    if let Some(parent) = dst.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::copy(src, dst)
+        .with_context(|| format!("Failed to copy {} to {}", src.display(), dst.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write(root: &Path, rel: &str, content: &str) {
+        let path = root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_expand_globs() {
+        let dir = tempdir().unwrap();
+        write(dir.path(), "src/a.ts", "a");
+        write(dir.path(), "src/nested/b.ts", "b");
+        write(dir.path(), "package.json", "{}");
+
+        let files = expand_globs(
+            dir.path(),
+            &["src/**/*.ts".to_string(), "package.json".to_string(), "src/a.ts".to_string()],
+        )
+        .unwrap();
+
+        assert_eq!(files, vec!["package.json", "src/a.ts", "src/nested/b.ts"]);
+    }
+
+    #[test]
+    fn test_task_hash_changes_with_inputs_and_deps() {
+        let dir = tempdir().unwrap();
+        write(dir.path(), "libs/ui/src/index.ts", "v1");
+        let config = TaskConfig {
+            inputs: vec!["src/**".to_string()],
+            ..Default::default()
+        };
+        let hash = |task: &str, config: &TaskConfig, deps: &[String]| {
+            compute_task_hash(dir.path(), "libs/ui", task, "tsc", Some(config), deps, &mut HashCache::load(dir.path()))
+                .unwrap()
+        };
+
+        let h1 = hash("build", &config, &[]);
+        assert_eq!(h1, hash("build", &config, &[]));
+        assert_ne!(h1, hash("build", &config, &["abc".to_string()]));
+        assert_ne!(h1, hash("typecheck", &config, &[]));
+
+        let with_outputs = TaskConfig { outputs: vec!["dist/**".to_string()], ..config.clone() };
+        assert_ne!(h1, hash("build", &with_outputs, &[]));
+        let with_env = TaskConfig { env: vec!["AIRIS_TEST_TASK_HASH_ENV".to_string()], ..config.clone() };
+        assert_ne!(h1, hash("build", &with_env, &[]));
+
+        write(dir.path(), "libs/ui/src/index.ts", "v2");
+        assert_ne!(h1, hash("build", &config, &[]));
+    }
+
+    #[test]
+    fn test_task_hash_without_inputs_covers_package_files() {
+        let dir = tempdir().unwrap();
+        write(dir.path(), "libs/ui/src/index.ts", "v1");
+
+        let hash = || compute_task_hash(dir.path(), "libs/ui", "build", "tsc", None, &[], &mut HashCache::load(dir.path()));
+        let h1 = hash().unwrap();
+        write(dir.path(), "libs/ui/src/index.ts", "v2");
+        let h2 = hash().unwrap();
+        assert_ne!(h1, h2);
+    }
+
+    #[test]
+    fn test_cache_store_and_restore() {
+        let dir = tempdir().unwrap();
+        let cache = tempdir().unwrap();
+        let package = "libs/ui";
+        let pkg_dir = dir.path().join(package);
+        write(&pkg_dir, "dist/index.js", "built");
+
+        let entry = TaskCacheEntry {
+            task_id: "libs/ui#build".to_string(),
+            hash: "abc123".to_string(),
+            outputs: Vec::new(),
+            created_at: String::new(),
+        };
+        let entry = store_in(cache.path(), dir.path(), package, entry, &["dist/**".to_string()], (b"out", b"err"))
+            .unwrap();
+        assert_eq!(entry.outputs, vec!["dist/index.js"]);
+
+        fs::remove_dir_all(pkg_dir.join("dist")).unwrap();
+        let cached = read_entry(cache.path()).unwrap();
+        restore_from(cache.path(), dir.path(), package, &cached).unwrap();
+
+        assert_eq!(fs::read_to_string(pkg_dir.join("dist/index.js")).unwrap(), "built");
+        assert_eq!(fs::read(cache.path().join("stdout.log")).unwrap(), b"out");
+    }
+}
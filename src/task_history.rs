@@ -0,0 +1,113 @@
+//! Historical task durations for scheduling
+//!
+//! `ParallelExecutor` starts the tasks with the longest remaining path first.
+//! Path lengths are weighted by how long each task took on earlier runs,
+//! stored per workspace in `.airis/task-history.json`:
+//!
+//! ```json
+//! { "durations": { "apps/web#build": 84000, "libs/ui": 121000 } }
+//! ```
+//!
+//! Only tasks that did real work (succeeded, not cached) are recorded, so
+//! estimates reflect cold-cache runs.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::executor::TaskResult;
+
+const HISTORY_FILE: &str = ".airis/task-history.json";
+
+/// Expected duration per task ID
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaskHistory {
+    /// Milliseconds, smoothed over runs
+    #[serde(default)]
+    pub durations: BTreeMap<String, u64>,
+}
+
+impl TaskHistory {
+    /// Load the workspace history (empty if missing or unreadable)
+    pub fn load(root: &Path) -> Self {
+        fs::read_to_string(history_path(root))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Estimates to hand to `ParallelExecutor::estimates`
+    pub fn estimates(&self) -> HashMap<String, u64> {
+        self.durations.iter().map(|(id, ms)| (id.clone(), *ms)).collect()
+    }
+
+    /// Fold the durations of tasks that ran into the history
+    ///
+    /// New samples are averaged with the previous estimate so one slow run
+    /// doesn't reorder the next build.
+    pub fn record(&mut self, results: &[TaskResult]) {
+        for result in results.iter().filter(|r| r.success && !r.cached && !r.skipped) {
+            let estimate = match self.durations.get(&result.task_id) {
+                Some(previous) => (previous + result.duration_ms) / 2,
+                None => result.duration_ms,
+            };
+            self.durations.insert(result.task_id.clone(), estimate);
+        }
+    }
+
+    /// Write the history back to `.airis/task-history.json`
+    pub fn save(&self, root: &Path) -> Result<()> {
+        let path = history_path(root);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+fn history_path(root: &Path) -> PathBuf {
+    root.join(HISTORY_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(id: &str, duration_ms: u64, success: bool, cached: bool) -> TaskResult {
+        TaskResult {
+            task_id: id.to_string(),
+            success,
+            duration_ms,
+            error: None,
+            cached,
+            skipped: false,
+        }
+    }
+
+    #[test]
+    fn test_record_smooths_and_ignores_cached() {
+        let mut history = TaskHistory::default();
+        history.record(&[result("web#build", 1000, true, false), result("ui#build", 5, true, true)]);
+        history.record(&[result("web#build", 3000, true, false), result("api#build", 10, false, false)]);
+
+        assert_eq!(history.durations.get("web#build"), Some(&2000));
+        assert!(!history.durations.contains_key("ui#build"));
+        assert!(!history.durations.contains_key("api#build"));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(TaskHistory::load(dir.path()).durations.is_empty());
+
+        let mut history = TaskHistory::default();
+        history.record(&[result("web#build", 1200, true, false)]);
+        history.save(dir.path()).unwrap();
+
+        let loaded = TaskHistory::load(dir.path());
+        assert_eq!(loaded.estimates().get("web#build"), Some(&1200));
+    }
+}
@@ -0,0 +1,404 @@
+//! Per-task log files for `airis run` / `airis build`
+//!
+//! Every task execution writes its stdout/stderr to a file, so failures can be
+//! debugged after the fact (e.g., from CI artifacts):
+//!
+//! ```text
+//! .airis/logs/
+//!   index.json                          # recent runs, oldest first
+//!   <run-id>/<package>/<task>.log       # e.g., 20261016-101500-123/apps/web/build.log
+//! ```
+//!
+//! `airis logs --task apps/web#build [--run <id>]` replays them; only the
+//! last [`KEEP_RUNS`] runs are kept.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+
+use crate::executor::{EventSink, Stream, TaskEvent, TaskResult};
+
+const LOGS_DIR: &str = ".airis/logs";
+const INDEX_FILE: &str = "index.json";
+
+/// Runs kept in `.airis/logs`
+pub const KEEP_RUNS: usize = 20;
+
+/// One run in index.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunEntry {
+    pub id: String,
+    /// e.g., "airis run build"
+    pub command: String,
+    pub started_at: String,
+    #[serde(default)]
+    pub tasks: Vec<TaskEntry>,
+}
+
+/// One task of a run in index.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskEntry {
+    pub task_id: String,
+    pub success: bool,
+    pub cached: bool,
+    pub skipped: bool,
+    pub duration_ms: u64,
+    /// Log file relative to the run directory
+    pub log: String,
+}
+
+/// Log directory of the current run
+#[derive(Debug)]
+pub struct RunLog {
+    root: PathBuf,
+    entry: RunEntry,
+}
+
+impl RunLog {
+    /// Start a new run under `<root>/.airis/logs/`
+    pub fn start(root: &Path, command: &str) -> Result<Self> {
+        let now = chrono::Local::now();
+        let entry = RunEntry {
+            id: now.format("%Y%m%d-%H%M%S-%3f").to_string(),
+            command: command.to_string(),
+            started_at: now.to_rfc3339(),
+            tasks: Vec::new(),
+        };
+        let log = Self {
+            root: root.to_path_buf(),
+            entry,
+        };
+        fs::create_dir_all(log.dir())
+            .with_context(|| format!("Failed to create {}", log.dir().display()))?;
+        Ok(log)
+    }
+
+    pub fn id(&self) -> &str {
+        &self.entry.id
+    }
+
+    /// `<root>/.airis/logs/<run-id>`
+    pub fn dir(&self) -> PathBuf {
+        logs_dir(&self.root).join(&self.entry.id)
+    }
+
+    /// Log file of a task in this run
+    ///
+    /// Bare package ids (`airis build --docker` tasks) are the package's `build`.
+    pub fn path(&self, task_id: &str) -> PathBuf {
+        self.dir().join(log_path(task_id))
+    }
+
+    /// Create (truncate) the log file of a task
+    pub fn create(&self, task_id: &str) -> Result<File> {
+        let path = self.path(task_id);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        File::create(&path).with_context(|| format!("Failed to create {}", path.display()))
+    }
+
+    /// Record the results in index.json and drop runs beyond [`KEEP_RUNS`]
+    pub fn finish(&self, results: &[TaskResult]) -> Result<()> {
+        let mut entry = self.entry.clone();
+        entry.tasks = results
+            .iter()
+            .filter(|r| self.path(&r.task_id).exists())
+            .map(|r| TaskEntry {
+                task_id: qualified(&r.task_id),
+                success: r.success,
+                cached: r.cached,
+                skipped: r.skipped,
+                duration_ms: r.duration_ms,
+                log: log_path(&r.task_id),
+            })
+            .collect();
+
+        let mut runs = load_index(&self.root);
+        runs.retain(|r| r.id != entry.id);
+        runs.push(entry);
+        if runs.len() > KEEP_RUNS {
+            for old in runs.drain(..runs.len() - KEEP_RUNS) {
+                let _ = fs::remove_dir_all(logs_dir(&self.root).join(&old.id));
+            }
+        }
+
+        let index = logs_dir(&self.root).join(INDEX_FILE);
+        fs::write(&index, serde_json::to_string_pretty(&runs)?)
+            .with_context(|| format!("Failed to write {}", index.display()))
+    }
+}
+
+fn logs_dir(root: &Path) -> PathBuf {
+    root.join(LOGS_DIR)
+}
+
+/// "apps/web" -> "apps/web#build"; "<pkg>#<task>" as is
+fn qualified(task_id: &str) -> String {
+    if task_id.contains('#') {
+        task_id.to_string()
+    } else {
+        format!("{}#build", task_id)
+    }
+}
+
+/// Log file of a task relative to its run: "apps/web#build" -> "apps/web/build.log"
+pub fn log_path(task_id: &str) -> String {
+    let task_id = qualified(task_id);
+    let (package, task) = task_id.rsplit_once('#').unwrap_or_default();
+    format!("{}/{}.log", package, task)
+}
+
+/// Runs in index.json, oldest first (empty if there is none)
+pub fn load_index(root: &Path) -> Vec<RunEntry> {
+    fs::read_to_string(logs_dir(root).join(INDEX_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Find a task's log: in run `run_id`, or the latest run that has one
+pub fn find(root: &Path, task_id: &str, run_id: Option<&str>) -> Result<(RunEntry, PathBuf)> {
+    let task_id = qualified(task_id);
+    let task_id = task_id.as_str();
+    let runs = load_index(root);
+    let run = match run_id {
+        Some(id) => runs
+            .iter()
+            .find(|r| r.id == id)
+            .with_context(|| format!("No run '{}' in {}/{}", id, LOGS_DIR, INDEX_FILE))?,
+        None => runs
+            .iter()
+            .rev()
+            .find(|r| r.tasks.iter().any(|t| t.task_id == task_id))
+            .with_context(|| format!("No logs recorded for {}", task_id))?,
+    };
+    let Some(task) = run.tasks.iter().find(|t| t.task_id == task_id) else {
+        bail!("Run {} has no log for {}", run.id, task_id);
+    };
+
+    Ok((run.clone(), logs_dir(root).join(&run.id).join(&task.log)))
+}
+
+/// `airis logs --task <pkg>#<task> [--run <id>]`
+pub fn replay(task_id: &str, run_id: Option<&str>) -> Result<()> {
+    let root = std::env::current_dir()?;
+    let (run, path) = find(&root, task_id, run_id)?;
+    let content = fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+
+    eprintln!(
+        "{}",
+        format!("📜 {} from run {} ({}, {})", task_id, run.id, run.command, run.started_at).cyan()
+    );
+    std::io::stdout().write_all(&content)?;
+    Ok(())
+}
+
+/// `airis logs --runs`: list recorded runs, newest first
+pub fn list_runs() -> Result<()> {
+    let root = std::env::current_dir()?;
+    let runs = load_index(&root);
+    if runs.is_empty() {
+        println!("No task logs recorded yet ({}).", LOGS_DIR);
+        return Ok(());
+    }
+
+    for run in runs.iter().rev() {
+        let failed: Vec<&str> = run
+            .tasks
+            .iter()
+            .filter(|t| !t.success && !t.skipped)
+            .map(|t| t.task_id.as_str())
+            .collect();
+        let status = if failed.is_empty() {
+            format!("{} tasks ok", run.tasks.len()).green()
+        } else {
+            format!("{} failed: {}", failed.len(), failed.join(", ")).red()
+        };
+        println!("{}  {}  {}", run.id.bold(), run.command, status);
+    }
+    Ok(())
+}
+
+/// Where a task's progress lines and command output go: the terminal, or a
+/// run's display (`task_ui::TaskUi`) as that task's output
+#[derive(Debug, Clone, Default)]
+pub struct Console {
+    /// Task ID and the display's sink (None: the terminal)
+    display: Option<(String, EventSink)>,
+}
+
+impl Console {
+    /// Output of `task_id`, sent to `events`
+    pub fn task(task_id: &str, events: EventSink) -> Self {
+        Self {
+            display: Some((task_id.to_string(), events)),
+        }
+    }
+
+    /// Print a progress line
+    pub fn say(&self, line: impl std::fmt::Display) {
+        self.write(Stream::Stdout, format!("{}\n", line).as_bytes());
+    }
+
+    /// Print a warning (stderr on the terminal)
+    pub fn warn(&self, line: impl std::fmt::Display) {
+        self.write(Stream::Stderr, format!("{}\n", line).as_bytes());
+    }
+
+    fn write(&self, stream: Stream, data: &[u8]) {
+        match &self.display {
+            Some((task_id, events)) => {
+                let _ = events.send(TaskEvent::Output { task_id: task_id.clone(), stream, data: data.to_vec() });
+            }
+            None => {
+                let _ = match stream {
+                    Stream::Stdout => std::io::stdout().write_all(data).and_then(|_| std::io::stdout().flush()),
+                    Stream::Stderr => std::io::stderr().write_all(data),
+                };
+            }
+        }
+    }
+}
+
+/// Run a command, echoing its output to `console` and appending it to `log`
+pub fn tee_command(cmd: &mut Command, log: Option<File>, console: &Console) -> Result<ExitStatus> {
+    if log.is_none() && console.display.is_none() {
+        return cmd.status().context("Failed to spawn command");
+    }
+
+    let mut child = cmd
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to spawn command")?;
+    let log = log.map(|log| Arc::new(Mutex::new(log)));
+
+    let stdout = child.stdout.take().context("Failed to capture stdout")?;
+    let stderr = child.stderr.take().context("Failed to capture stderr")?;
+    let out = spawn_tee(stdout, Stream::Stdout, console.clone(), log.clone());
+    let err = spawn_tee(stderr, Stream::Stderr, console.clone(), log);
+
+    let status = child.wait()?;
+    let _ = out.join();
+    let _ = err.join();
+    Ok(status)
+}
+
+fn spawn_tee<R>(mut reader: R, stream: Stream, console: Console, log: Option<Arc<Mutex<File>>>) -> std::thread::JoinHandle<()>
+where
+    R: Read + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let mut buf = [0u8; 8192];
+        while let Ok(n) = reader.read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+            console.write(stream, &buf[..n]);
+            if let Some(Ok(mut log)) = log.as_ref().map(|log| log.lock()) {
+                let _ = log.write_all(&buf[..n]);
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(id: &str, success: bool) -> TaskResult {
+        TaskResult {
+            task_id: id.to_string(),
+            success,
+            duration_ms: 10,
+            error: None,
+            cached: false,
+            skipped: false,
+        }
+    }
+
+    #[test]
+    fn test_log_path() {
+        assert_eq!(log_path("apps/web#build"), "apps/web/build.log");
+        assert_eq!(log_path("apps/web"), "apps/web/build.log");
+    }
+
+    #[test]
+    fn test_run_log_index_and_find() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+
+        let first = RunLog::start(root, "airis run build").unwrap();
+        first.create("apps/web#build").unwrap().write_all(b"first\n").unwrap();
+        first.finish(&[result("apps/web#build", false)]).unwrap();
+
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let second = RunLog::start(root, "airis run build").unwrap();
+        second.create("apps/web#build").unwrap().write_all(b"second\n").unwrap();
+        // Tasks without a log file (e.g., nothing to run) are left out
+        second.finish(&[result("apps/web#build", true), result("libs/ui#build", true)]).unwrap();
+
+        let runs = load_index(root);
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[1].tasks.len(), 1);
+
+        let (run, path) = find(root, "apps/web#build", None).unwrap();
+        assert_eq!(run.id, second.id());
+        assert_eq!(fs::read_to_string(path).unwrap(), "second\n");
+
+        let (_, path) = find(root, "apps/web#build", Some(first.id())).unwrap();
+        assert_eq!(fs::read_to_string(path).unwrap(), "first\n");
+
+        assert!(find(root, "libs/ui#build", None).is_err());
+        // Docker builds are recorded under their package id
+        assert!(find(root, "apps/web", None).is_ok());
+        assert!(find(root, "apps/web#build", Some("nope")).is_err());
+    }
+
+    #[test]
+    fn test_old_runs_are_pruned() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+
+        let mut first_dir = None;
+        for _ in 0..KEEP_RUNS + 1 {
+            let run = RunLog::start(root, "airis run test").unwrap();
+            run.create("libs/ui#test").unwrap();
+            run.finish(&[result("libs/ui#test", true)]).unwrap();
+            first_dir.get_or_insert(run.dir());
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+
+        assert_eq!(load_index(root).len(), KEEP_RUNS);
+        assert!(!first_dir.unwrap().exists());
+    }
+
+    #[test]
+    fn test_tee_command_to_display() {
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("build.log");
+        let (events, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let console = Console::task("apps/web", events);
+
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "echo out; echo err >&2"]);
+        let status = tee_command(&mut cmd, Some(File::create(&log_path).unwrap()), &console).unwrap();
+        assert!(status.success());
+
+        let mut output: Vec<(Stream, String)> = Vec::new();
+        while let Ok(TaskEvent::Output { task_id, stream, data }) = rx.try_recv() {
+            assert_eq!(task_id, "apps/web");
+            output.push((stream, String::from_utf8(data).unwrap()));
+        }
+        assert!(output.contains(&(Stream::Stdout, "out\n".to_string())));
+        assert!(output.contains(&(Stream::Stderr, "err\n".to_string())));
+        let log = fs::read_to_string(&log_path).unwrap();
+        assert!(log.contains("out\n") && log.contains("err\n"));
+    }
+}
@@ -0,0 +1,608 @@
+//! Output for multi-task runs (`airis run`, `airis build`)
+//!
+//! `--ui` picks how executor progress and task output are shown:
+//!
+//! - `tui`: full-screen task list with state and elapsed time, a log pane for
+//!   the selected task, and `r` to re-run a failed task (default on a TTY)
+//! - `grouped`: each task's output printed as one block when it finishes,
+//!   every line prefixed with the task ID (default in CI or when piped)
+//! - `stream`: output goes straight to the terminal as it is produced
+//!
+//! Both displays consume [`TaskEvent`]s from `ParallelExecutor::events` on
+//! their own thread.
+
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use std::collections::HashMap;
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc as std_mpsc;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Notify};
+
+use crate::executor::{EventSink, Stream, TaskEvent, TaskState};
+
+/// How often the displays check for new events
+const TICK: Duration = Duration::from_millis(50);
+
+/// Log lines kept per task in the TUI
+const MAX_LOG_LINES: usize = 5000;
+
+/// Output mode for `--ui`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UiMode {
+    /// TUI on an interactive terminal, grouped otherwise
+    #[default]
+    Auto,
+    Tui,
+    Grouped,
+    Stream,
+}
+
+impl UiMode {
+    /// Parse a `--ui` value (validated by clap)
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "tui" => UiMode::Tui,
+            "grouped" => UiMode::Grouped,
+            "stream" => UiMode::Stream,
+            _ => UiMode::Auto,
+        }
+    }
+
+    /// Resolve `Auto` for the current terminal
+    pub fn resolve(self) -> Self {
+        match self {
+            UiMode::Auto => {
+                let interactive = std::io::stdout().is_terminal()
+                    && std::io::stdin().is_terminal()
+                    && std::env::var_os("CI").is_none();
+                if interactive { UiMode::Tui } else { UiMode::Grouped }
+            }
+            mode => mode,
+        }
+    }
+}
+
+/// Request from the TUI
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UiCommand {
+    /// Run a failed task (and the tasks skipped because of it) again
+    Rerun(String),
+}
+
+/// A running display for one `airis run`
+pub struct TaskUi {
+    events: EventSink,
+    /// TUI requests (None for grouped output)
+    commands: Option<std_mpsc::Receiver<UiCommand>>,
+    /// Set by `finish` (no more events) or by the TUI (user quit)
+    closed: Arc<AtomicBool>,
+    thread: Option<JoinHandle<Result<()>>>,
+}
+
+impl TaskUi {
+    /// Start displaying a run of `task_ids` (None for `UiMode::Stream`)
+    ///
+    /// `cancel` is notified when the user quits the TUI mid-run, since the
+    /// terminal's raw mode swallows Ctrl-C.
+    pub fn start(mode: UiMode, title: &str, task_ids: &[String], cancel: Arc<Notify>) -> Result<Option<Self>> {
+        let (events, rx) = mpsc::unbounded_channel();
+        let closed = Arc::new(AtomicBool::new(false));
+        let thread_closed = Arc::clone(&closed);
+
+        let (commands, thread) = match mode.resolve() {
+            UiMode::Tui => {
+                let (tx, commands) = std_mpsc::channel();
+                let app = tui::App::new(title, task_ids);
+                let thread = std::thread::spawn(move || tui::run(app, rx, tx, cancel, thread_closed));
+                (Some(commands), thread)
+            }
+            UiMode::Grouped => {
+                let thread = std::thread::spawn(move || {
+                    grouped(rx, thread_closed);
+                    Ok(())
+                });
+                (None, thread)
+            }
+            UiMode::Auto | UiMode::Stream => return Ok(None),
+        };
+
+        Ok(Some(Self {
+            events,
+            commands,
+            closed,
+            thread: Some(thread),
+        }))
+    }
+
+    /// Sink to hand to `ParallelExecutor::events` and task closures
+    pub fn events(&self) -> EventSink {
+        self.events.clone()
+    }
+
+    /// Whether this is the full-screen TUI
+    pub fn is_tui(&self) -> bool {
+        self.commands.is_some()
+    }
+
+    /// Wait for the next TUI request; None once the user quits
+    pub fn next_command(&self) -> Option<UiCommand> {
+        let commands = self.commands.as_ref()?;
+        loop {
+            if self.closed.load(Ordering::SeqCst) {
+                return None;
+            }
+            match commands.recv_timeout(TICK) {
+                Ok(command) => return Some(command),
+                Err(std_mpsc::RecvTimeoutError::Timeout) => continue,
+                Err(std_mpsc::RecvTimeoutError::Disconnected) => return None,
+            }
+        }
+    }
+
+    /// Flush remaining output and restore the terminal
+    pub fn finish(mut self) -> Result<()> {
+        self.close()
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.closed.store(true, Ordering::SeqCst);
+        match self.thread.take() {
+            Some(thread) => thread.join().map_err(|_| anyhow!("Task display panicked"))?,
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for TaskUi {
+    fn drop(&mut self) {
+        // Leaving raw mode matters more than reporting the error here
+        let _ = self.close();
+    }
+}
+
+/// Receive the next event, polling until `closed` once the queue is empty
+fn next_event(rx: &mut mpsc::UnboundedReceiver<TaskEvent>, closed: &AtomicBool) -> Option<TaskEvent> {
+    loop {
+        match rx.try_recv() {
+            Ok(event) => return Some(event),
+            Err(mpsc::error::TryRecvError::Disconnected) => return None,
+            Err(mpsc::error::TryRecvError::Empty) if closed.load(Ordering::SeqCst) => return None,
+            Err(mpsc::error::TryRecvError::Empty) => std::thread::sleep(TICK),
+        }
+    }
+}
+
+/// Print each task's output as one prefixed block when it finishes
+fn grouped(mut rx: mpsc::UnboundedReceiver<TaskEvent>, closed: Arc<AtomicBool>) {
+    let mut buffers: HashMap<String, Vec<(Stream, Vec<u8>)>> = HashMap::new();
+
+    while let Some(event) = next_event(&mut rx, &closed) {
+        match event {
+            TaskEvent::Message(line) => println!("{}", line),
+            TaskEvent::Output { task_id, stream, data } => {
+                buffers.entry(task_id).or_default().push((stream, data));
+            }
+            TaskEvent::Finished(result) => {
+                let chunks = buffers.remove(&result.task_id).unwrap_or_default();
+                for (stream, line) in prefixed_lines(&result.task_id, &chunks) {
+                    match stream {
+                        Stream::Stdout => println!("{}", line),
+                        Stream::Stderr => eprintln!("{}", line),
+                    }
+                }
+            }
+            TaskEvent::State { .. } => {}
+        }
+    }
+    let _ = std::io::stdout().flush();
+}
+
+/// Split buffered output into lines prefixed with `<task_id> │`
+///
+/// Chunks of the same stream are joined first, so lines split across reads
+/// stay whole.
+pub fn prefixed_lines(task_id: &str, chunks: &[(Stream, Vec<u8>)]) -> Vec<(Stream, String)> {
+    let prefix = format!("{} │", task_id).cyan();
+    let mut merged: Vec<(Stream, Vec<u8>)> = Vec::new();
+    for (stream, data) in chunks {
+        match merged.last_mut() {
+            Some((last, buf)) if last == stream => buf.extend_from_slice(data),
+            _ => merged.push((*stream, data.clone())),
+        }
+    }
+
+    merged
+        .iter()
+        .flat_map(|(stream, data)| {
+            String::from_utf8_lossy(data)
+                .lines()
+                .map(|line| (*stream, format!("{} {}", prefix, line)))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Remove ANSI escape sequences (colors, cursor movement) from task output
+fn strip_ansi(text: &str) -> String {
+    static ANSI: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+    let ansi = ANSI.get_or_init(|| regex::Regex::new(r"\x1b\[[0-9;?]*[ -/]*[@-~]|\x1b\][^\x07]*\x07").expect("valid regex"));
+    ansi.replace_all(text, "").replace('\r', "")
+}
+
+mod tui {
+    use super::*;
+    use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+    use ratatui::crossterm::execute;
+    use ratatui::crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+    use ratatui::layout::{Constraint, Direction, Layout};
+    use ratatui::style::{Color, Modifier, Style};
+    use ratatui::text::{Line, Span};
+    use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+    use ratatui::Frame;
+
+    /// One row of the task list
+    pub struct TaskView {
+        pub id: String,
+        pub state: TaskState,
+        pub cached: bool,
+        started: Option<Instant>,
+        duration_ms: Option<u64>,
+        log: Vec<(Stream, String)>,
+        /// Unterminated last line per stream
+        partial: HashMap<Stream, String>,
+    }
+
+    impl TaskView {
+        fn elapsed(&self) -> Option<Duration> {
+            match (self.duration_ms, self.started) {
+                (Some(ms), _) => Some(Duration::from_millis(ms)),
+                (None, Some(started)) => Some(started.elapsed()),
+                _ => None,
+            }
+        }
+
+        fn push_output(&mut self, stream: Stream, data: &[u8]) {
+            let text = strip_ansi(&String::from_utf8_lossy(data));
+            let buf = self.partial.entry(stream).or_default();
+            buf.push_str(&text);
+            while let Some(pos) = buf.find('\n') {
+                let line: String = buf.drain(..=pos).collect();
+                self.log.push((stream, line.trim_end_matches('\n').to_string()));
+            }
+            if self.log.len() > MAX_LOG_LINES {
+                self.log.drain(..self.log.len() - MAX_LOG_LINES);
+            }
+        }
+
+        pub fn lines(&self) -> impl Iterator<Item = (Stream, &str)> {
+            self.log
+                .iter()
+                .map(|(s, l)| (*s, l.as_str()))
+                .chain(self.partial.iter().filter(|(_, l)| !l.is_empty()).map(|(s, l)| (*s, l.as_str())))
+        }
+    }
+
+    pub struct App {
+        title: String,
+        pub tasks: Vec<TaskView>,
+        index: HashMap<String, usize>,
+        selected: usize,
+        /// Lines scrolled up from the bottom of the log pane
+        scroll: usize,
+        last_message: String,
+    }
+
+    impl App {
+        pub fn new(title: &str, task_ids: &[String]) -> Self {
+            let tasks: Vec<TaskView> = task_ids
+                .iter()
+                .map(|id| TaskView {
+                    id: id.clone(),
+                    state: TaskState::Pending,
+                    cached: false,
+                    started: None,
+                    duration_ms: None,
+                    log: Vec::new(),
+                    partial: HashMap::new(),
+                })
+                .collect();
+            let index = tasks.iter().enumerate().map(|(i, t)| (t.id.clone(), i)).collect();
+            Self {
+                title: title.to_string(),
+                tasks,
+                index,
+                selected: 0,
+                scroll: 0,
+                last_message: String::new(),
+            }
+        }
+
+        fn task(&mut self, id: &str) -> Option<&mut TaskView> {
+            let i = *self.index.get(id)?;
+            self.tasks.get_mut(i)
+        }
+
+        pub fn apply(&mut self, event: TaskEvent) {
+            match event {
+                TaskEvent::Message(line) => {
+                    let line = strip_ansi(&line);
+                    if !line.trim().is_empty() {
+                        self.last_message = line.trim().to_string();
+                    }
+                }
+                TaskEvent::State { task_id, state } => {
+                    let Some(task) = self.task(&task_id) else {
+                        return;
+                    };
+                    if state == TaskState::Running {
+                        if !task.log.is_empty() {
+                            task.log.push((Stream::Stdout, "── re-run ──".to_string()));
+                        }
+                        task.started = Some(Instant::now());
+                        task.duration_ms = None;
+                        task.cached = false;
+                    }
+                    task.state = state;
+                }
+                TaskEvent::Output { task_id, stream, data } => {
+                    if let Some(task) = self.task(&task_id) {
+                        task.push_output(stream, &data);
+                    }
+                }
+                TaskEvent::Finished(result) => {
+                    let Some(task) = self.task(&result.task_id) else {
+                        return;
+                    };
+                    let error = result.error.clone().unwrap_or_default();
+                    task.state = if result.skipped {
+                        TaskState::Skipped(error)
+                    } else if result.success {
+                        TaskState::Completed
+                    } else {
+                        TaskState::Failed(error)
+                    };
+                    task.cached = result.cached;
+                    if !result.skipped {
+                        task.duration_ms = Some(result.duration_ms);
+                    }
+                }
+            }
+        }
+
+        pub fn finished(&self) -> bool {
+            self.tasks
+                .iter()
+                .all(|t| matches!(t.state, TaskState::Completed | TaskState::Failed(_) | TaskState::Skipped(_)))
+        }
+
+        fn select(&mut self, delta: isize) {
+            let last = self.tasks.len().saturating_sub(1) as isize;
+            self.selected = (self.selected as isize + delta).clamp(0, last) as usize;
+            self.scroll = 0;
+        }
+
+        fn draw(&self, frame: &mut Frame) {
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(3), Constraint::Length(2)])
+                .split(frame.area());
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
+                .split(rows[0]);
+
+            let items: Vec<ListItem> = self.tasks.iter().map(task_item).collect();
+            let mut list_state = ListState::default().with_selected(Some(self.selected));
+            let list = List::new(items)
+                .block(Block::default().borders(Borders::ALL).title(format!(" {} ", self.title)))
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+            frame.render_stateful_widget(list, columns[0], &mut list_state);
+
+            if let Some(task) = self.tasks.get(self.selected) {
+                let lines: Vec<Line> = task
+                    .lines()
+                    .map(|(stream, line)| match stream {
+                        Stream::Stdout => Line::raw(line.to_string()),
+                        Stream::Stderr => Line::styled(line.to_string(), Style::default().fg(Color::LightRed)),
+                    })
+                    .collect();
+                let height = columns[1].height.saturating_sub(2) as usize;
+                let top = lines.len().saturating_sub(height).saturating_sub(self.scroll);
+                let title = match &task.state {
+                    TaskState::Failed(error) => format!(" {} — {} ", task.id, error),
+                    _ => format!(" {} ", task.id),
+                };
+                let log = Paragraph::new(lines)
+                    .block(Block::default().borders(Borders::ALL).title(title))
+                    .scroll((top.min(u16::MAX as usize) as u16, 0));
+                frame.render_widget(log, columns[1]);
+            }
+
+            let done = self
+                .tasks
+                .iter()
+                .filter(|t| matches!(t.state, TaskState::Completed | TaskState::Failed(_) | TaskState::Skipped(_)))
+                .count();
+            let running = self.tasks.iter().filter(|t| t.state == TaskState::Running).count();
+            let failed = self.tasks.iter().filter(|t| matches!(t.state, TaskState::Failed(_))).count();
+            let status = format!("{}/{} done · {} running · {} failed  {}", done, self.tasks.len(), running, failed, self.last_message);
+            let keys = "↑/↓ select  PgUp/PgDn scroll  r re-run failed  q quit";
+            let footer = Paragraph::new(vec![
+                Line::raw(status),
+                Line::styled(keys, Style::default().fg(Color::DarkGray)),
+            ]);
+            frame.render_widget(footer, rows[1]);
+        }
+    }
+
+    fn task_item(task: &TaskView) -> ListItem<'static> {
+        let (symbol, color) = match &task.state {
+            TaskState::Pending => ("·", Color::DarkGray),
+            TaskState::Ready => ("○", Color::Gray),
+            TaskState::Running => ("▶", Color::Cyan),
+            TaskState::Completed if task.cached => ("⚡", Color::Green),
+            TaskState::Completed => ("✔", Color::Green),
+            TaskState::Failed(_) => ("✘", Color::Red),
+            TaskState::Skipped(_) => ("⏭", Color::Yellow),
+        };
+        let elapsed = task
+            .elapsed()
+            .map(|d| format!(" {:.1}s", d.as_secs_f64()))
+            .unwrap_or_default();
+        ListItem::new(Line::from(vec![
+            Span::styled(format!("{} ", symbol), Style::default().fg(color)),
+            Span::raw(task.id.clone()),
+            Span::styled(elapsed, Style::default().fg(Color::DarkGray)),
+        ]))
+    }
+
+    /// Restores the terminal even if drawing fails
+    struct TerminalGuard;
+
+    impl Drop for TerminalGuard {
+        fn drop(&mut self) {
+            let _ = disable_raw_mode();
+            let _ = execute!(std::io::stdout(), LeaveAlternateScreen);
+        }
+    }
+
+    pub fn run(
+        mut app: App,
+        mut rx: mpsc::UnboundedReceiver<TaskEvent>,
+        commands: std_mpsc::Sender<UiCommand>,
+        cancel: Arc<Notify>,
+        closed: Arc<AtomicBool>,
+    ) -> Result<()> {
+        enable_raw_mode()?;
+        let _guard = TerminalGuard;
+        execute!(std::io::stdout(), EnterAlternateScreen)?;
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::CrosstermBackend::new(std::io::stdout()))?;
+
+        loop {
+            while let Ok(event) = rx.try_recv() {
+                app.apply(event);
+            }
+            // `finish` without the user quitting (e.g., the run errored out)
+            if closed.load(Ordering::SeqCst) {
+                return Ok(());
+            }
+            terminal.draw(|frame| app.draw(frame))?;
+
+            if !event::poll(TICK)? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => break,
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+                KeyCode::Up | KeyCode::Char('k') => app.select(-1),
+                KeyCode::Down | KeyCode::Char('j') => app.select(1),
+                KeyCode::PageUp => app.scroll += 10,
+                KeyCode::PageDown => app.scroll = app.scroll.saturating_sub(10),
+                KeyCode::Char('r') => {
+                    let task = &mut app.tasks[app.selected];
+                    if matches!(task.state, TaskState::Failed(_)) {
+                        task.state = TaskState::Ready;
+                        let _ = commands.send(UiCommand::Rerun(task.id.clone()));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Quitting mid-run cancels it like Ctrl-C would
+        if !app.finished() {
+            cancel.notify_one();
+        }
+        closed.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::TaskResult;
+
+    #[test]
+    fn test_ui_mode_parse() {
+        assert_eq!(UiMode::parse("tui"), UiMode::Tui);
+        assert_eq!(UiMode::parse("grouped"), UiMode::Grouped);
+        assert_eq!(UiMode::parse("stream"), UiMode::Stream);
+        assert_eq!(UiMode::parse("auto"), UiMode::Auto);
+        assert_eq!(UiMode::Grouped.resolve(), UiMode::Grouped);
+    }
+
+    #[test]
+    fn test_prefixed_lines_joins_split_chunks() {
+        let chunks = vec![
+            (Stream::Stdout, b"compil".to_vec()),
+            (Stream::Stdout, b"ing\ndone\n".to_vec()),
+            (Stream::Stderr, b"warning: x\n".to_vec()),
+        ];
+        let lines: Vec<(Stream, String)> = prefixed_lines("apps/web#build", &chunks)
+            .into_iter()
+            .map(|(stream, line)| (stream, strip_ansi(&line)))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                (Stream::Stdout, "apps/web#build │ compiling".to_string()),
+                (Stream::Stdout, "apps/web#build │ done".to_string()),
+                (Stream::Stderr, "apps/web#build │ warning: x".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tui_app_tracks_states_and_logs() {
+        let ids = vec!["libs/ui#build".to_string(), "apps/web#build".to_string()];
+        let mut app = tui::App::new("airis run build", &ids);
+
+        app.apply(TaskEvent::State { task_id: ids[0].clone(), state: TaskState::Running });
+        app.apply(TaskEvent::Output {
+            task_id: ids[0].clone(),
+            stream: Stream::Stdout,
+            data: b"\x1b[32mok\x1b[0m\npart".to_vec(),
+        });
+        assert_eq!(app.tasks[0].state, TaskState::Running);
+        let log: Vec<&str> = app.tasks[0].lines().map(|(_, line)| line).collect();
+        assert_eq!(log, vec!["ok", "part"]);
+        assert!(!app.finished());
+
+        app.apply(TaskEvent::Finished(TaskResult {
+            task_id: ids[0].clone(),
+            success: false,
+            duration_ms: 1500,
+            error: Some("exit code Some(1)".to_string()),
+            cached: false,
+            skipped: false,
+        }));
+        app.apply(TaskEvent::Finished(TaskResult {
+            task_id: ids[1].clone(),
+            success: false,
+            duration_ms: 0,
+            error: Some("dependency libs/ui#build failed".to_string()),
+            cached: false,
+            skipped: true,
+        }));
+
+        assert_eq!(app.tasks[0].state, TaskState::Failed("exit code Some(1)".to_string()));
+        assert!(matches!(app.tasks[1].state, TaskState::Skipped(_)));
+        assert!(app.finished());
+    }
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(strip_ansi("\x1b[1;31merror\x1b[0m: bad\r"), "error: bad");
+    }
+}
@@ -290,6 +290,13 @@ impl TemplateEngine {
             .context("Failed to render docker-compose.yml")
     }
 
+    /// Render a project-supplied template (e.g., `templates/docker/node.hbs`)
+    pub fn render_string(&self, name: &str, template: &str, data: &impl serde::Serialize) -> Result<String> {
+        self.hbs
+            .render_template(template, data)
+            .with_context(|| format!("Failed to render {}", name))
+    }
+
     pub fn render_dockerfile_dev(&self, manifest: &Manifest) -> Result<String> {
         let data = self.prepare_dockerfile_dev_data(manifest)?;
         self.hbs
@@ -0,0 +1,195 @@
+//! File watching for `airis run <task> --watch`
+//!
+//! Watches package directories and reports which packages changed, in bursts:
+//! events are collected until the tree has been quiet for [`DEBOUNCE`], so a
+//! save-all or `git checkout` triggers one rebuild instead of dozens.
+//!
+//! Paths are filtered through the workspace and package `.gitignore` files
+//! (via the `ignore` crate) plus the task's declared `outputs`, so a build
+//! writing `dist/` doesn't trigger itself.
+
+use anyhow::{Context, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use notify::{RecursiveMode, Watcher as _};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use crate::dag::Dag;
+
+/// Quiet period that ends a burst of changes
+pub const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Never worth rebuilding for
+const ALWAYS_IGNORED: &[&str] = &[".git", "node_modules", ".airis"];
+
+/// Watches package directories for changes
+pub struct Watcher {
+    root: PathBuf,
+    filter: IgnoreFilter,
+    events: mpsc::Receiver<notify::Result<notify::Event>>,
+    // Dropping the watcher stops the events
+    _watcher: notify::RecommendedWatcher,
+}
+
+impl Watcher {
+    /// Watch `packages` (workspace-relative directories), ignoring `outputs` globs in each
+    pub fn new(root: &Path, packages: &[String], outputs: &[String]) -> Result<Self> {
+        let (tx, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx).context("Failed to start file watcher")?;
+        for package in packages {
+            let dir = root.join(package);
+            if dir.is_dir() {
+                watcher
+                    .watch(&dir, RecursiveMode::Recursive)
+                    .with_context(|| format!("Failed to watch {}", dir.display()))?;
+            }
+        }
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            filter: IgnoreFilter::new(root, packages, outputs),
+            events,
+            _watcher: watcher,
+        })
+    }
+
+    /// Block until files change, then return the changed packages (sorted)
+    ///
+    /// Changes to ignored files only are swallowed.
+    pub fn next_change(&self, dag: &Dag) -> Result<Vec<String>> {
+        loop {
+            let first = self.events.recv().context("File watcher stopped")?;
+            let mut paths = event_paths(first);
+            while let Ok(event) = self.events.recv_timeout(DEBOUNCE) {
+                paths.extend(event_paths(event));
+            }
+
+            let changed = changed_packages(dag, &self.root, &self.filter, &paths);
+            if !changed.is_empty() {
+                return Ok(changed);
+            }
+        }
+    }
+}
+
+fn event_paths(event: notify::Result<notify::Event>) -> Vec<PathBuf> {
+    match event {
+        // Opening or reading a file is not a change
+        Ok(event) if !event.kind.is_access() => event.paths,
+        _ => vec![],
+    }
+}
+
+/// Packages owning any of `paths` that aren't ignored
+pub fn changed_packages(dag: &Dag, root: &Path, filter: &IgnoreFilter, paths: &[PathBuf]) -> Vec<String> {
+    let mut changed = BTreeSet::new();
+    for path in paths {
+        let Ok(rel) = path.strip_prefix(root) else {
+            continue;
+        };
+        let rel = rel.to_string_lossy().replace('\\', "/");
+        if rel.split('/').any(|part| ALWAYS_IGNORED.contains(&part)) {
+            continue;
+        }
+        let Some(node) = dag.owner_of(&rel) else {
+            continue;
+        };
+        if !filter.is_ignored(&node.path, path) {
+            changed.insert(node.id.clone());
+        }
+    }
+    changed.into_iter().collect()
+}
+
+/// `.gitignore` rules of the workspace and each watched package
+pub struct IgnoreFilter {
+    workspace: Gitignore,
+    packages: HashMap<String, Gitignore>,
+}
+
+impl IgnoreFilter {
+    pub fn new(root: &Path, packages: &[String], outputs: &[String]) -> Self {
+        let workspace = gitignore(root, &[]);
+        let packages = packages
+            .iter()
+            .map(|p| (p.clone(), gitignore(&root.join(p), outputs)))
+            .collect();
+        Self { workspace, packages }
+    }
+
+    /// Whether a change to `path` inside `package` should be ignored
+    pub fn is_ignored(&self, package: &str, path: &Path) -> bool {
+        let is_dir = path.is_dir();
+        if self.workspace.matched_path_or_any_parents(path, is_dir).is_ignore() {
+            return true;
+        }
+        self.packages
+            .get(package)
+            .is_some_and(|g| g.matched_path_or_any_parents(path, is_dir).is_ignore())
+    }
+}
+
+/// Matcher for `dir/.gitignore` plus extra patterns relative to `dir`
+fn gitignore(dir: &Path, extra: &[String]) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(dir);
+    let file = dir.join(".gitignore");
+    if file.exists() {
+        // Unparseable lines are skipped by the builder
+        let _ = builder.add(file);
+    }
+    for pattern in extra {
+        // "dist/**" must also match the `dist` directory being created
+        let pattern = pattern.strip_suffix("/**").unwrap_or(pattern);
+        let _ = builder.add_line(None, pattern);
+    }
+    builder.build().unwrap_or_else(|_| Gitignore::empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dag::DagNode;
+
+    fn dag() -> Dag {
+        let mut dag = Dag::new();
+        for (id, deps) in [("libs/ui", vec![]), ("apps/web", vec!["libs/ui"])] {
+            dag.add_node(DagNode {
+                id: id.to_string(),
+                name: id.to_string(),
+                path: id.to_string(),
+                deps: deps.into_iter().map(String::from).collect(),
+            });
+        }
+        dag
+    }
+
+    #[test]
+    fn test_changed_packages_respects_ignores() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("libs/ui/src")).unwrap();
+        std::fs::create_dir_all(root.join("apps/web")).unwrap();
+        std::fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+        std::fs::write(root.join("apps/web/.gitignore"), ".next/\n").unwrap();
+
+        let packages = vec!["libs/ui".to_string(), "apps/web".to_string()];
+        let filter = IgnoreFilter::new(root, &packages, &["dist/**".to_string()]);
+        let dag = dag();
+
+        let changed = |files: &[&str]| {
+            let paths: Vec<PathBuf> = files.iter().map(|f| root.join(f)).collect();
+            changed_packages(&dag, root, &filter, &paths)
+        };
+
+        assert_eq!(changed(&["libs/ui/src/button.tsx"]), vec!["libs/ui"]);
+        assert_eq!(changed(&["libs/ui/src/a.ts", "apps/web/page.tsx"]), vec!["apps/web", "libs/ui"]);
+        assert!(changed(&["libs/ui/dist/index.js"]).is_empty());
+        assert!(changed(&["libs/ui/dist"]).is_empty());
+        assert!(changed(&["apps/web/.next/cache.json"]).is_empty());
+        assert!(changed(&["apps/web/debug.log"]).is_empty());
+        assert!(changed(&["libs/ui/node_modules/x/index.js"]).is_empty());
+        assert!(changed(&["README.md"]).is_empty());
+    }
+}
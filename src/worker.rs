@@ -0,0 +1,758 @@
+//! Worker protocol for distributed task execution
+//!
+//! `airis worker` runs pipeline tasks on behalf of `airis run --worker ...`.
+//! The protocol is newline-delimited JSON over stdio or TCP, one object per line:
+//!
+//! ```text
+//! worker → {"type":"hello","protocol":2,"version":"1.62.0","slots":8,"challenge":"<hex>"}
+//! client → {"type":"auth","response":"<hex HMAC-SHA256(token, challenge)>"}
+//! worker → {"type":"authenticated"}
+//! client → {"type":"run","id":"apps/web#build","package":"apps/web","command":"pnpm run build","outputs":["dist/**"]}
+//! worker → {"type":"log","id":"apps/web#build","stream":"stdout","data":"<base64>"}
+//! worker → {"type":"output","id":"apps/web#build","path":"dist/index.js","data":"<base64>"}
+//! worker → {"type":"done","id":"apps/web#build","success":true,"exit_code":0}
+//! ```
+//!
+//! A connection multiplexes several jobs by `id`, up to the worker's `slots`.
+//! Output files are sent back after a successful run so the task cache and
+//! later tasks see them in the client's tree.
+//!
+//! A worker with a shared secret (`AIRIS_WORKER_TOKEN`) sends a fresh
+//! challenge in its hello and drops clients that don't answer it before
+//! anything else.
+
+use anyhow::{bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::{mpsc, Mutex, Semaphore};
+
+use crate::executor::{BoxFuture, ExecBackend, Job, JobOutput, LogSink, Stream};
+use crate::remote_cache::{hex, unhex};
+use crate::task_cache::expand_globs;
+
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// Shared secret of workers and their clients
+pub const TOKEN_ENV: &str = "AIRIS_WORKER_TOKEN";
+
+/// `AIRIS_WORKER_TOKEN`, if set
+pub fn token_from_env() -> Option<String> {
+    std::env::var(TOKEN_ENV).ok().filter(|t| !t.is_empty())
+}
+
+fn challenge_mac(token: &str, challenge: &str) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(token.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(challenge.as_bytes());
+    mac
+}
+
+/// Hex answer to a hello challenge
+fn challenge_response(token: &str, challenge: &str) -> String {
+    hex(&challenge_mac(token, challenge).finalize().into_bytes())
+}
+
+/// Unique per connection, so answers can't be replayed
+fn new_challenge() -> String {
+    static COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    let mut seed = [0u8; 32];
+    let random = std::fs::File::open("/dev/urandom").and_then(|mut f| std::io::Read::read_exact(&mut f, &mut seed));
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&seed);
+    if random.is_err() {
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
+        hasher.update(&now.as_nanos().to_le_bytes());
+        hasher.update(&std::process::id().to_le_bytes());
+    }
+    hasher.update(&COUNTER.fetch_add(1, Ordering::SeqCst).to_le_bytes());
+    hasher.finalize().to_hex().to_string()
+}
+
+/// Client → worker
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    /// Answer to the hello `challenge`
+    Auth {
+        response: String,
+    },
+    Run {
+        id: String,
+        package: String,
+        command: String,
+        #[serde(default)]
+        outputs: Vec<String>,
+    },
+    Shutdown,
+}
+
+/// Worker → client
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    Hello {
+        protocol: u32,
+        version: String,
+        slots: usize,
+        /// Present when the worker requires a token
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        challenge: Option<String>,
+    },
+    Authenticated,
+    Log {
+        id: String,
+        stream: Stream,
+        data: String,
+    },
+    Output {
+        id: String,
+        path: String,
+        data: String,
+    },
+    Done {
+        id: String,
+        success: bool,
+        exit_code: Option<i32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+}
+
+impl Event {
+    fn id(&self) -> Option<&str> {
+        match self {
+            Event::Hello { .. } | Event::Authenticated => None,
+            Event::Log { id, .. } | Event::Output { id, .. } | Event::Done { id, .. } => Some(id),
+        }
+    }
+}
+
+type BoxReader = Box<dyn AsyncRead + Send + Unpin>;
+type BoxWriter = Box<dyn AsyncWrite + Send + Unpin>;
+
+async fn send_line<W, T>(writer: &mut W, value: &T) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+    T: Serialize,
+{
+    let mut line = serde_json::to_string(value)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes()).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Worker side
+// ---------------------------------------------------------------------------
+
+/// Serves jobs from clients using a local backend
+pub struct Worker {
+    backend: Arc<dyn ExecBackend>,
+    root: PathBuf,
+    /// Shared by all connections so several clients can't oversubscribe the host
+    slots: Arc<Semaphore>,
+    /// Clients must prove they know this before running anything
+    token: Option<String>,
+}
+
+impl Worker {
+    pub fn new(backend: Arc<dyn ExecBackend>, root: PathBuf, token: Option<String>) -> Self {
+        let slots = Arc::new(Semaphore::new(backend.slots().max(1)));
+        Self { backend, root, slots, token }
+    }
+
+    /// Handle one client connection until it closes or sends `shutdown`
+    ///
+    /// `shutdown` waits for running jobs; a closed connection cancels them.
+    pub async fn serve<R, W>(self: &Arc<Self>, reader: R, writer: W) -> Result<()>
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+        W: AsyncWrite + Send + Unpin + 'static,
+    {
+        let (tx, mut rx) = mpsc::unbounded_channel::<Event>();
+        let writer_task = tokio::spawn(async move {
+            let mut writer = writer;
+            while let Some(event) = rx.recv().await {
+                send_line(&mut writer, &event).await?;
+            }
+            Ok::<_, anyhow::Error>(())
+        });
+
+        let challenge = self.token.as_ref().map(|_| new_challenge());
+        let _ = tx.send(Event::Hello {
+            protocol: PROTOCOL_VERSION,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            slots: self.backend.slots().max(1),
+            challenge: challenge.clone(),
+        });
+
+        let mut running = tokio::task::JoinSet::new();
+        let mut lines = BufReader::new(reader).lines();
+        let mut shutdown = false;
+        let mut authenticated = challenge.is_none();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let request: Request =
+                serde_json::from_str(&line).with_context(|| format!("Invalid worker request: {}", line))?;
+
+            match request {
+                Request::Auth { response } => {
+                    let (Some(token), Some(challenge)) = (&self.token, &challenge) else {
+                        continue;
+                    };
+                    let valid = unhex(&response)
+                        .is_some_and(|bytes| challenge_mac(token, challenge).verify_slice(&bytes).is_ok());
+                    if !valid {
+                        bail!("client failed authentication");
+                    }
+                    authenticated = true;
+                    let _ = tx.send(Event::Authenticated);
+                }
+                _ if !authenticated => bail!("client sent a request before authenticating"),
+                Request::Shutdown => {
+                    shutdown = true;
+                    break;
+                }
+                Request::Run { id, package, command, outputs } => {
+                    let worker = Arc::clone(self);
+                    let tx = tx.clone();
+                    running.spawn(async move {
+                        let _permit = worker.slots.acquire().await;
+                        let job = Job { id, package, command, outputs, capture: true };
+                        worker.run_job(job, &tx).await;
+                    });
+                }
+            }
+        }
+
+        // A client that hung up (e.g., Ctrl-C) no longer wants its jobs: kill them
+        if !shutdown {
+            running.abort_all();
+        }
+        while running.join_next().await.is_some() {}
+        drop(tx);
+        writer_task.await??;
+        Ok(())
+    }
+
+    /// Run a job, streaming logs, then outputs, then `done`
+    async fn run_job(&self, job: Job, tx: &mpsc::UnboundedSender<Event>) {
+        let id = job.id.clone();
+        let package = job.package.clone();
+        let outputs = job.outputs.clone();
+
+        let (log_tx, mut log_rx) = mpsc::unbounded_channel();
+        let forward = async {
+            while let Some((stream, data)) = log_rx.recv().await {
+                let _ = tx.send(Event::Log { id: id.clone(), stream, data: BASE64.encode(data) });
+            }
+        };
+        let (result, ()) = tokio::join!(self.backend.run(job, Some(log_tx)), forward);
+
+        let done = match result {
+            Ok(output) => {
+                let mut error = output.error;
+                if output.success
+                    && let Err(e) = self.send_outputs(&id, &package, &outputs, tx) {
+                        error = Some(format!("Failed to send outputs: {:#}", e));
+                    }
+                Event::Done {
+                    id,
+                    success: output.success && error.is_none(),
+                    exit_code: output.exit_code,
+                    error,
+                }
+            }
+            Err(e) => Event::Done { id, success: false, exit_code: None, error: Some(format!("{:#}", e)) },
+        };
+        let _ = tx.send(done);
+    }
+
+    fn send_outputs(&self, id: &str, package: &str, outputs: &[String], tx: &mpsc::UnboundedSender<Event>) -> Result<()> {
+        let pkg_dir = self.root.join(package);
+        for rel in expand_globs(&pkg_dir, outputs)? {
+            let data = std::fs::read(pkg_dir.join(&rel))
+                .with_context(|| format!("Failed to read output {}/{}", package, rel))?;
+            let _ = tx.send(Event::Output { id: id.to_string(), path: rel, data: BASE64.encode(data) });
+        }
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Client side
+// ---------------------------------------------------------------------------
+
+/// How to reach a worker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    /// `host:port` or `tcp://host:port` (a running `airis worker --listen`)
+    Tcp(String),
+    /// `stdio:<command>`, e.g. `stdio:ssh build1 airis worker`
+    Stdio(String),
+}
+
+impl Endpoint {
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        if let Some(cmd) = spec.strip_prefix("stdio:") {
+            if cmd.trim().is_empty() {
+                bail!("Invalid worker '{}': missing command after stdio:", spec);
+            }
+            return Ok(Endpoint::Stdio(cmd.trim().to_string()));
+        }
+
+        let addr = spec.strip_prefix("tcp://").unwrap_or(spec);
+        match addr.rsplit_once(':') {
+            Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok() => Ok(Endpoint::Tcp(addr.to_string())),
+            _ => bail!(
+                "Invalid worker '{}'. Expected host:port, tcp://host:port or stdio:<command>",
+                spec
+            ),
+        }
+    }
+
+    async fn open(&self) -> Result<(BoxReader, BoxWriter, Option<tokio::process::Child>)> {
+        match self {
+            Endpoint::Tcp(addr) => {
+                let stream = tokio::net::TcpStream::connect(addr)
+                    .await
+                    .with_context(|| format!("Failed to connect to worker {}", addr))?;
+                let (reader, writer) = stream.into_split();
+                Ok((Box::new(reader), Box::new(writer), None))
+            }
+            Endpoint::Stdio(cmd) => {
+                let mut child = tokio::process::Command::new("sh")
+                    .arg("-c")
+                    .arg(cmd)
+                    .stdin(std::process::Stdio::piped())
+                    .stdout(std::process::Stdio::piped())
+                    .spawn()
+                    .with_context(|| format!("Failed to start worker: {}", cmd))?;
+                let reader = child.stdout.take().context("Failed to capture worker stdout")?;
+                let writer = child.stdin.take().context("Failed to open worker stdin")?;
+                Ok((Box::new(reader), Box::new(writer), Some(child)))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Endpoint::Tcp(addr) => write!(f, "{}", addr),
+            Endpoint::Stdio(cmd) => write!(f, "stdio:{}", cmd),
+        }
+    }
+}
+
+type Pending = Arc<std::sync::Mutex<HashMap<String, mpsc::UnboundedSender<Event>>>>;
+
+/// One open worker connection
+struct Connection {
+    name: String,
+    writer: Mutex<BoxWriter>,
+    pending: Pending,
+    alive: Arc<AtomicBool>,
+    /// stdio workers exit (cancelling their jobs) once stdin closes
+    _child: Option<tokio::process::Child>,
+}
+
+impl Connection {
+    /// Handshake, then route incoming events to the job that owns them
+    async fn start(
+        name: String,
+        reader: BoxReader,
+        writer: BoxWriter,
+        child: Option<tokio::process::Child>,
+        token: Option<&str>,
+    ) -> Result<(Self, usize)> {
+        let mut lines = BufReader::new(reader).lines();
+        let hello = lines
+            .next_line()
+            .await?
+            .with_context(|| format!("Worker {} closed the connection before saying hello", name))?;
+
+        let (slots, challenge) = match serde_json::from_str::<Event>(&hello) {
+            Ok(Event::Hello { protocol, slots, challenge, .. }) if protocol == PROTOCOL_VERSION => (slots.max(1), challenge),
+            Ok(Event::Hello { protocol, version, .. }) => bail!(
+                "Worker {} speaks protocol {} (airis {}), expected {}",
+                name,
+                protocol,
+                version,
+                PROTOCOL_VERSION
+            ),
+            _ => bail!("Worker {} sent an invalid hello: {}", name, hello),
+        };
+
+        let mut writer = writer;
+        if let Some(challenge) = challenge {
+            let Some(token) = token else {
+                bail!("Worker {} requires a token; set {}", name, TOKEN_ENV);
+            };
+            let auth = Request::Auth { response: challenge_response(token, &challenge) };
+            send_line(&mut writer, &auth).await?;
+            match lines.next_line().await? {
+                Some(line) if matches!(serde_json::from_str(&line), Ok(Event::Authenticated)) => {}
+                _ => bail!("Worker {} rejected the token in {}", name, TOKEN_ENV),
+            }
+        }
+
+        let pending: Pending = Arc::default();
+        let alive = Arc::new(AtomicBool::new(true));
+        {
+            let pending = Arc::clone(&pending);
+            let alive = Arc::clone(&alive);
+            tokio::spawn(async move {
+                while let Ok(Some(line)) = lines.next_line().await {
+                    let Ok(event) = serde_json::from_str::<Event>(&line) else {
+                        continue;
+                    };
+                    let Some(id) = event.id() else {
+                        continue;
+                    };
+                    let mut pending = pending.lock().expect("pending lock poisoned");
+                    let done = matches!(event, Event::Done { .. });
+                    if let Some(tx) = pending.get(id) {
+                        let _ = tx.send(event.clone());
+                    }
+                    if done {
+                        pending.remove(id);
+                    }
+                }
+                // Dropping the senders wakes every job still waiting on this worker
+                alive.store(false, Ordering::SeqCst);
+                pending.lock().expect("pending lock poisoned").clear();
+            });
+        }
+
+        let conn = Self {
+            name,
+            writer: Mutex::new(writer),
+            pending,
+            alive,
+            _child: child,
+        };
+        Ok((conn, slots))
+    }
+}
+
+/// Dispatches jobs to `airis worker` processes
+///
+/// Jobs go to the least busy live worker. Logs are echoed as they arrive and
+/// output files are written into `root` before the job completes.
+pub struct WorkerBackend {
+    root: PathBuf,
+    connections: Vec<Connection>,
+    in_flight: std::sync::Mutex<Vec<usize>>,
+    slots: usize,
+}
+
+impl WorkerBackend {
+    /// Connect to every endpoint (all must answer), authenticating with
+    /// `AIRIS_WORKER_TOKEN` where a worker asks for it
+    pub async fn connect(specs: &[String], root: PathBuf) -> Result<Self> {
+        let mut connections = Vec::new();
+        let mut slots = 0;
+        let token = token_from_env();
+
+        for spec in specs {
+            let endpoint = Endpoint::parse(spec)?;
+            let (reader, writer, child) = endpoint.open().await?;
+            let (conn, conn_slots) =
+                Connection::start(endpoint.to_string(), reader, writer, child, token.as_deref()).await?;
+            connections.push(conn);
+            slots += conn_slots;
+        }
+
+        Ok(Self::new(root, connections, slots))
+    }
+
+    fn new(root: PathBuf, connections: Vec<Connection>, slots: usize) -> Self {
+        Self {
+            root,
+            in_flight: std::sync::Mutex::new(vec![0; connections.len()]),
+            connections,
+            slots,
+        }
+    }
+
+    /// Pick the live connection with the fewest running jobs
+    fn acquire(&self) -> Option<usize> {
+        let mut in_flight = self.in_flight.lock().expect("in_flight lock poisoned");
+        let index = (0..self.connections.len())
+            .filter(|&i| self.connections[i].alive.load(Ordering::SeqCst))
+            .min_by_key(|&i| in_flight[i])?;
+        in_flight[index] += 1;
+        Some(index)
+    }
+
+    fn release(&self, index: usize) {
+        self.in_flight.lock().expect("in_flight lock poisoned")[index] -= 1;
+    }
+
+    async fn dispatch(&self, conn: &Connection, job: Job, logs: LogSink) -> Result<JobOutput> {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        conn.pending
+            .lock()
+            .expect("pending lock poisoned")
+            .insert(job.id.clone(), tx);
+
+        let request = Request::Run {
+            id: job.id.clone(),
+            package: job.package.clone(),
+            command: job.command,
+            outputs: job.outputs,
+        };
+        if let Err(e) = send_line(&mut *conn.writer.lock().await, &request).await {
+            conn.pending.lock().expect("pending lock poisoned").remove(&job.id);
+            return Err(e).with_context(|| format!("Failed to send {} to worker {}", job.id, conn.name));
+        }
+
+        let mut output = JobOutput::default();
+        while let Some(event) = rx.recv().await {
+            match event {
+                Event::Log { stream, data, .. } => {
+                    let data = BASE64.decode(data).context("Invalid log data from worker")?;
+                    echo(stream, &data, &logs).await;
+                    match stream {
+                        Stream::Stdout => output.stdout.extend_from_slice(&data),
+                        Stream::Stderr => output.stderr.extend_from_slice(&data),
+                    }
+                }
+                Event::Output { path, data, .. } => {
+                    let data = BASE64.decode(data).context("Invalid output data from worker")?;
+                    write_output(&self.root, &job.package, &path, &data)?;
+                }
+                Event::Done { success, exit_code, error, .. } => {
+                    output.success = success;
+                    output.exit_code = exit_code;
+                    output.error = error;
+                    return Ok(output);
+                }
+                Event::Hello { .. } | Event::Authenticated => {}
+            }
+        }
+
+        bail!("Worker {} disconnected while running {}", conn.name, job.id)
+    }
+}
+
+impl ExecBackend for WorkerBackend {
+    fn describe(&self) -> String {
+        let names: Vec<&str> = self.connections.iter().map(|c| c.name.as_str()).collect();
+        format!("{} worker(s): {}", self.connections.len(), names.join(", "))
+    }
+
+    fn slots(&self) -> usize {
+        self.slots
+    }
+
+    fn run(&self, job: Job, logs: LogSink) -> BoxFuture<'_, Result<JobOutput>> {
+        Box::pin(async move {
+            let Some(index) = self.acquire() else {
+                bail!("No workers available to run {}", job.id);
+            };
+            let result = self.dispatch(&self.connections[index], job, logs).await;
+            self.release(index);
+            result
+        })
+    }
+}
+
+/// Echo a remote log chunk to the terminal (or the caller's sink)
+async fn echo(stream: Stream, data: &[u8], logs: &LogSink) {
+    if let Some(sink) = logs {
+        let _ = sink.send((stream, data.to_vec()));
+        return;
+    }
+    let _ = match stream {
+        Stream::Stdout => tokio::io::stdout().write_all(data).await,
+        Stream::Stderr => tokio::io::stderr().write_all(data).await,
+    };
+}
+
+/// Write an output file sent by a worker, refusing paths outside the package
+fn write_output(root: &Path, package: &str, rel: &str, data: &[u8]) -> Result<()> {
+    let rel_path = Path::new(rel);
+    if !rel_path.components().all(|c| matches!(c, Component::Normal(_))) {
+        bail!("Worker sent an unsafe output path: {}", rel);
+    }
+
+    let path = root.join(package).join(rel_path);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, data).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::run::LocalBackend;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_endpoint_parse() {
+        assert_eq!(Endpoint::parse("127.0.0.1:7878").unwrap(), Endpoint::Tcp("127.0.0.1:7878".to_string()));
+        assert_eq!(
+            Endpoint::parse("tcp://build1:7878").unwrap(),
+            Endpoint::Tcp("build1:7878".to_string())
+        );
+        assert_eq!(
+            Endpoint::parse("stdio:ssh build1 airis worker").unwrap(),
+            Endpoint::Stdio("ssh build1 airis worker".to_string())
+        );
+        assert!(Endpoint::parse("build1").is_err());
+        assert!(Endpoint::parse("stdio:").is_err());
+    }
+
+    #[test]
+    fn test_protocol_wire_format() {
+        let request: Request = serde_json::from_str(
+            r#"{"type":"run","id":"apps/web#build","package":"apps/web","command":"echo hi"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            request,
+            Request::Run {
+                id: "apps/web#build".to_string(),
+                package: "apps/web".to_string(),
+                command: "echo hi".to_string(),
+                outputs: vec![],
+            }
+        );
+
+        let done = Event::Done { id: "a#b".to_string(), success: true, exit_code: Some(0), error: None };
+        assert_eq!(
+            serde_json::to_string(&done).unwrap(),
+            r#"{"type":"done","id":"a#b","success":true,"exit_code":0}"#
+        );
+    }
+
+    #[test]
+    fn test_write_output_rejects_traversal() {
+        let dir = tempdir().unwrap();
+        assert!(write_output(dir.path(), "apps/web", "../../etc/passwd", b"x").is_err());
+        assert!(write_output(dir.path(), "apps/web", "/etc/passwd", b"x").is_err());
+        write_output(dir.path(), "apps/web", "dist/a.js", b"x").unwrap();
+        assert!(dir.path().join("apps/web/dist/a.js").exists());
+    }
+
+    #[tokio::test]
+    async fn test_jobs_run_on_worker_and_stream_back() {
+        let worker_root = tempdir().unwrap();
+        let client_root = tempdir().unwrap();
+        std::fs::create_dir_all(worker_root.path().join("apps/web")).unwrap();
+
+        let backend = Arc::new(LocalBackend::new(worker_root.path().to_path_buf(), None, 2));
+        let worker = Arc::new(Worker::new(backend, worker_root.path().to_path_buf(), None));
+
+        // One pipe per direction so dropping the client closes the worker's input
+        let (client_write, worker_read) = tokio::io::duplex(64 * 1024);
+        let (worker_write, client_read) = tokio::io::duplex(64 * 1024);
+        let server = tokio::spawn(async move { worker.serve(worker_read, worker_write).await });
+
+        let (conn, slots) =
+            Connection::start("test".to_string(), Box::new(client_read), Box::new(client_write), None, None)
+                .await
+                .unwrap();
+        assert_eq!(slots, 2);
+        let client = WorkerBackend::new(client_root.path().to_path_buf(), vec![conn], slots);
+
+        let (sink, mut logs) = mpsc::unbounded_channel();
+        let ok = client.run(
+            Job {
+                id: "apps/web#build".to_string(),
+                package: "apps/web".to_string(),
+                command: "mkdir -p dist && echo built > dist/out.txt && echo hello".to_string(),
+                outputs: vec!["dist/**".to_string()],
+                capture: true,
+            },
+            Some(sink),
+        );
+        let failed = client.run(
+            Job {
+                id: "apps/web#lint".to_string(),
+                package: "apps/web".to_string(),
+                command: "echo oops >&2; exit 3".to_string(),
+                outputs: vec![],
+                capture: true,
+            },
+            None,
+        );
+        let (ok, failed) = tokio::join!(ok, failed);
+
+        let ok = ok.unwrap();
+        assert!(ok.success);
+        assert_eq!(ok.stdout, b"hello\n");
+        assert_eq!(logs.recv().await.unwrap(), (Stream::Stdout, b"hello\n".to_vec()));
+        assert_eq!(
+            std::fs::read_to_string(client_root.path().join("apps/web/dist/out.txt")).unwrap(),
+            "built\n"
+        );
+
+        let failed = failed.unwrap();
+        assert!(!failed.success);
+        assert_eq!(failed.exit_code, Some(3));
+        assert_eq!(failed.stderr, b"oops\n");
+
+        drop(client);
+        server.await.unwrap().unwrap();
+    }
+
+    /// Connect a client with `token` to a worker that requires "s3cret"
+    async fn handshake(token: Option<&str>) -> (Result<usize>, Result<()>) {
+        let root = tempdir().unwrap();
+        let backend = Arc::new(LocalBackend::new(root.path().to_path_buf(), None, 1));
+        let worker = Arc::new(Worker::new(backend, root.path().to_path_buf(), Some("s3cret".to_string())));
+
+        let (client_write, worker_read) = tokio::io::duplex(64 * 1024);
+        let (worker_write, client_read) = tokio::io::duplex(64 * 1024);
+        let server = tokio::spawn(async move { worker.serve(worker_read, worker_write).await });
+
+        let client = Connection::start("test".to_string(), Box::new(client_read), Box::new(client_write), None, token)
+            .await
+            .map(|(conn, slots)| {
+                drop(conn);
+                slots
+            });
+        (client, server.await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_worker_token_handshake() {
+        let (client, server) = handshake(Some("s3cret")).await;
+        assert_eq!(client.unwrap(), 1);
+        server.unwrap();
+
+        let (client, server) = handshake(Some("wrong")).await;
+        assert!(client.unwrap_err().to_string().contains("rejected the token"));
+        assert!(server.is_err());
+
+        let (client, _) = handshake(None).await;
+        assert!(client.unwrap_err().to_string().contains(TOKEN_ENV));
+    }
+
+    #[tokio::test]
+    async fn test_worker_refuses_run_before_auth() {
+        let root = tempdir().unwrap();
+        let backend = Arc::new(LocalBackend::new(root.path().to_path_buf(), None, 1));
+        let worker = Arc::new(Worker::new(backend, root.path().to_path_buf(), Some("s3cret".to_string())));
+
+        let request = r#"{"type":"run","id":"a#b","package":".","command":"touch pwned"}"#;
+        let input = std::io::Cursor::new(format!("{}\n", request).into_bytes());
+        let result = worker.serve(input, tokio::io::sink()).await;
+        assert!(result.unwrap_err().to_string().contains("before authenticating"));
+        assert!(!root.path().join("pwned").exists());
+    }
+}
@@ -0,0 +1,275 @@
+//! Workspace dependency graph shared by affected, deps, build and bundle
+//!
+//! Sources, in order of preference:
+//! 1. pnpm-lock.yaml importers (what pnpm actually linked)
+//! 2. package.json files matched by pnpm-workspace.yaml / manifest.toml
+//!    `[packages].workspaces` (before the first `pnpm install`)
+//!
+//! Node IDs are package paths relative to the root (e.g., "libs/ui");
+//! names come from each package.json.
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::dag::{build_dag, Dag, DagNode};
+use crate::manifest::Manifest;
+use crate::pnpm::{build_workspace_map, normalize_path, PnpmLock, PnpmWorkspace};
+
+/// Used when neither pnpm-workspace.yaml nor manifest.toml lists workspaces
+const DEFAULT_WORKSPACES: &[&str] = &["apps/*", "libs/*", "libs/*/*", "packages/*"];
+
+/// Dependency fields that create graph edges
+const DEP_FIELDS: &[&str] = &[
+    "dependencies",
+    "devDependencies",
+    "peerDependencies",
+    "optionalDependencies",
+];
+
+/// Load the workspace graph rooted at `root`
+pub fn load(root: &Path) -> Result<Dag> {
+    let lock_path = root.join("pnpm-lock.yaml");
+    if lock_path.exists() {
+        let lock = PnpmLock::load(&lock_path).context("Failed to parse pnpm-lock.yaml")?;
+        return Ok(from_lockfile(root, &lock));
+    }
+
+    from_package_json(root)
+}
+
+/// Build the graph from lockfile importers, naming nodes from package.json
+pub fn from_lockfile(root: &Path, lock: &PnpmLock) -> Dag {
+    let mut dag = build_dag(&build_workspace_map(lock));
+
+    for node in dag.nodes.values_mut() {
+        if let Some(name) = read_package_json(&root.join(&node.path))
+            .and_then(|json| json["name"].as_str().map(String::from))
+        {
+            node.name = name;
+        }
+    }
+
+    dag
+}
+
+/// Build the graph by scanning workspace package.json files
+pub fn from_package_json(root: &Path) -> Result<Dag> {
+    let mut packages: Vec<(String, Value)> = Vec::new();
+
+    for pattern in workspace_patterns(root)? {
+        let full = root.join(&pattern);
+        let full = full.to_str().context("Workspace path contains non-UTF-8 characters")?;
+        for dir in glob::glob(full).with_context(|| format!("Invalid workspace glob: {}", pattern))? {
+            let dir = dir?;
+            let Some(json) = read_package_json(&dir) else {
+                continue;
+            };
+            // glob drops a leading "./", so a relative root may not be a prefix
+            let rel = dir.strip_prefix(root).unwrap_or(&dir);
+            let rel = rel.to_string_lossy().replace('\\', "/");
+            if !packages.iter().any(|(path, _)| *path == rel) {
+                packages.push((rel, json));
+            }
+        }
+    }
+
+    let by_name: HashMap<String, String> = packages
+        .iter()
+        .filter_map(|(path, json)| Some((json["name"].as_str()?.to_string(), path.clone())))
+        .collect();
+
+    let mut dag = Dag::new();
+    for (path, json) in &packages {
+        let mut deps = Vec::new();
+        for field in DEP_FIELDS {
+            let Some(map) = json[field].as_object() else {
+                continue;
+            };
+            for (dep_name, spec) in map {
+                let Some(spec) = spec.as_str() else {
+                    continue;
+                };
+                if let Some(dep) = resolve_spec(path, dep_name, spec, &by_name, &packages)
+                    && dep != *path
+                    && !deps.contains(&dep)
+                {
+                    deps.push(dep);
+                }
+            }
+        }
+        deps.sort();
+
+        let name = json["name"]
+            .as_str()
+            .map(String::from)
+            .unwrap_or_else(|| path.rsplit('/').next().unwrap_or(path).to_string());
+
+        dag.add_node(DagNode {
+            id: path.clone(),
+            name,
+            path: path.clone(),
+            deps,
+        });
+    }
+
+    Ok(dag)
+}
+
+/// Resolve a package.json dependency specifier to a workspace package path
+///
+/// - `workspace:*`, `workspace:^1.0.0` → package named `dep_name`
+/// - `workspace:@scope/other@*` (alias) → package named `@scope/other`
+/// - `link:../ui`, `file:../ui` → package at that path
+/// - plain ranges → package named `dep_name` if it is in the workspace
+/// - `npm:` aliases and registry-only names → None
+fn resolve_spec(
+    pkg_path: &str,
+    dep_name: &str,
+    spec: &str,
+    by_name: &HashMap<String, String>,
+    packages: &[(String, Value)],
+) -> Option<String> {
+    if let Some(range) = spec.strip_prefix("workspace:") {
+        let target = match range.rsplit_once('@') {
+            Some((alias, _)) if !alias.is_empty() => alias,
+            _ if range.starts_with('@') || range.chars().next().is_some_and(|c| c.is_ascii_alphabetic()) => range,
+            _ => dep_name,
+        };
+        return by_name.get(target).cloned();
+    }
+
+    if let Some(rel) = spec.strip_prefix("link:").or_else(|| spec.strip_prefix("file:")) {
+        let target = normalize_path(pkg_path, rel);
+        return packages.iter().find(|(path, _)| *path == target).map(|(path, _)| path.clone());
+    }
+
+    if spec.starts_with("npm:") {
+        return None;
+    }
+
+    by_name.get(dep_name).cloned()
+}
+
+/// Workspace globs: pnpm-workspace.yaml, then manifest.toml, then defaults
+fn workspace_patterns(root: &Path) -> Result<Vec<String>> {
+    let ws_path = root.join("pnpm-workspace.yaml");
+    let mut patterns = if ws_path.exists() {
+        PnpmWorkspace::load(&ws_path)?.packages
+    } else {
+        let manifest_path = root.join("manifest.toml");
+        if manifest_path.exists() {
+            Manifest::load(&manifest_path)?.packages.workspaces
+        } else {
+            Vec::new()
+        }
+    };
+
+    if patterns.is_empty() {
+        patterns = DEFAULT_WORKSPACES.iter().map(|s| s.to_string()).collect();
+    }
+
+    // Exclusions ("!**/test/**") only matter to pnpm itself
+    patterns.retain(|p| !p.starts_with('!'));
+    Ok(patterns)
+}
+
+pub fn read_package_json(dir: &Path) -> Option<Value> {
+    let content = fs::read_to_string(dir.join("package.json")).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write(root: &Path, rel: &str, content: &str) {
+        let path = root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn sample_workspace(root: &Path) {
+        write(root, "pnpm-workspace.yaml", "packages:\n  - apps/*\n  - libs/*\n  - libs/supabase/*\n");
+        write(
+            root,
+            "apps/web/package.json",
+            r#"{
+                "name": "@acme/web",
+                "dependencies": {
+                    "@acme/ui": "workspace:*",
+                    "config": "link:../../libs/config",
+                    "types": "file:../../libs/supabase/types",
+                    "react": "^18.0.0"
+                },
+                "devDependencies": { "ui-alias": "workspace:@acme/ui@^" }
+            }"#,
+        );
+        write(root, "libs/ui/package.json", r#"{ "name": "@acme/ui", "dependencies": { "@acme/config": "^1.0.0" } }"#);
+        write(root, "libs/config/package.json", r#"{ "name": "@acme/config" }"#);
+        write(root, "libs/supabase/types/package.json", r#"{ "name": "@acme/types" }"#);
+    }
+
+    #[test]
+    fn test_package_json_fallback_specifiers() {
+        let dir = tempdir().unwrap();
+        sample_workspace(dir.path());
+
+        let dag = load(dir.path()).unwrap();
+
+        assert_eq!(dag.ids(), vec!["apps/web", "libs/config", "libs/supabase/types", "libs/ui"]);
+        assert_eq!(
+            dag.get("apps/web").unwrap().deps,
+            vec!["libs/config", "libs/supabase/types", "libs/ui"]
+        );
+        // Plain range to a workspace package name
+        assert_eq!(dag.get("libs/ui").unwrap().deps, vec!["libs/config"]);
+        assert_eq!(dag.find_by_name("@acme/types").unwrap().id, "libs/supabase/types");
+    }
+
+    #[test]
+    fn test_lockfile_source_preferred() {
+        let dir = tempdir().unwrap();
+        sample_workspace(dir.path());
+        write(
+            dir.path(),
+            "pnpm-lock.yaml",
+            r#"
+lockfileVersion: '9.0'
+importers:
+  apps/web:
+    dependencies:
+      '@acme/ui':
+        specifier: workspace:*
+        version: link:../../libs/ui
+      types:
+        specifier: file:../../libs/supabase/types
+        version: file:libs/supabase/types
+  libs/ui: {}
+  libs/supabase/types: {}
+"#,
+        );
+
+        let dag = load(dir.path()).unwrap();
+
+        assert_eq!(dag.ids(), vec!["apps/web", "libs/supabase/types", "libs/ui"]);
+        let mut deps = dag.get("apps/web").unwrap().deps.clone();
+        deps.sort();
+        assert_eq!(deps, vec!["libs/supabase/types", "libs/ui"]);
+        // Names come from package.json, not the directory
+        assert_eq!(dag.get("libs/ui").unwrap().name, "@acme/ui");
+    }
+
+    #[test]
+    fn test_resolve_spec_npm_alias_ignored() {
+        let by_name = HashMap::from([("@acme/ui".to_string(), "libs/ui".to_string())]);
+        assert_eq!(resolve_spec("apps/web", "@acme/ui", "npm:@acme/ui@1.0.0", &by_name, &[]), None);
+        assert_eq!(
+            resolve_spec("apps/web", "@acme/ui", "workspace:^", &by_name, &[]),
+            Some("libs/ui".to_string())
+        );
+    }
+}
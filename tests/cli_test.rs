@@ -6,7 +6,7 @@ use assert_cmd::Command;
 use predicates::prelude::*;
 
 fn airis() -> Command {
-    Command::cargo_bin("airis").unwrap()
+    assert_cmd::cargo::cargo_bin_cmd!("airis")
 }
 
 #[test]
@@ -49,8 +49,11 @@ fn test_build_docker_requires_project() {
 #[test]
 fn test_invalid_channel() {
     // This should fail because the project doesn't exist, but we can test channel parsing
-    // by checking that valid channels don't cause parse errors
+    // by checking that valid channels don't cause parse errors.
+    // Run outside the checkout so nothing is written under .airis/
+    let temp = tempfile::tempdir().unwrap();
     airis()
+        .current_dir(temp.path())
         .args(["build", "apps/nonexistent", "--docker", "--channel", "lts"])
         .assert()
         .failure()
@@ -80,7 +83,7 @@ fn test_bundle_help() {
         .args(["bundle", "--help"])
         .assert()
         .success()
-        .stdout(predicate::str::contains("image.tar"))
+        .stdout(predicate::str::contains("OCI image layout"))
         .stdout(predicate::str::contains("artifact.tar.gz"))
         .stdout(predicate::str::contains("bundle.json"));
 }
@@ -94,6 +97,18 @@ fn test_bundle_requires_project() {
         .failure();
 }
 
+#[test]
+fn test_bundle_verify_requires_provenance() {
+    let temp = tempfile::tempdir().unwrap();
+    std::fs::write(temp.path().join("bundle.json"), "{}").unwrap();
+    airis()
+        .args(["bundle", "verify"])
+        .arg(temp.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("provenance.intoto.json"));
+}
+
 #[test]
 fn test_policy_help() {
     airis()